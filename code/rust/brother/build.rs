@@ -1,5 +1,5 @@
 // brother/build.rs
-use std::{env, fs, path::{Path, PathBuf}};
+use std::{env, fs, path::PathBuf};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let crate_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR")?);
@@ -17,8 +17,12 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let gen_dir = crate_dir.join("src/generated");
     fs::create_dir_all(&gen_dir)?;
 
+    // Descriptors for server reflection, see `pb::FILE_DESCRIPTOR_SET`.
+    let descriptors = PathBuf::from(env::var("OUT_DIR")?).join("brother_descriptor.bin");
+
     tonic_build::configure()
         .out_dir(&gen_dir)
+        .file_descriptor_set_path(&descriptors)
         .build_client(true)
         .build_server(true)
         .compile_protos(&[&brother_proto], &[&proto_dir])?;
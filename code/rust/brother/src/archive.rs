@@ -0,0 +1,892 @@
+//! src/archive.rs
+//! Tenant archives behind `ExportTenant` / `ImportTenant`, and
+//! `brother tenant export|import`.
+//!
+//! An archive is [`MAGIC`] followed by length-delimited `ArchiveRecord`s:
+//! a header, the tenant's live objects by (type, id), its live edges by
+//! (type, source, target) – inverse rows as stored – and a trailer with
+//! the counts and a SHA-256 of every byte before it.  The hash is over the
+//! bytes as written, not re-encoded records: map order is not stable.
+//!
+//! Export reads one `REPEATABLE READ` snapshot.  Import is one transaction
+//! into a tenant with no rows at all, tombstones included; it keeps ids,
+//! versions, `time` and `position` and rebuilds the tenant's
+//! `association_counts`.  History, tombstones and expired rows are not
+//! carried, and `created_at` restarts at the import.  Schemas are global:
+//! the target database must already have them.  The tenant's unique
+//! indexes ride in the header and are declared again, claiming the
+//! imported values, before the import commits.
+//!
+//! Ids are kept, so an archive restored into another tenant of the
+//! cluster it came from repeats every id of its objects there.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Read, Write},
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use brother::pb::{
+    archive_record::Record, ArchiveHeader, ArchiveRecord, ArchiveTrailer, Association, Object,
+    UniqueIndex,
+};
+use clap::Subcommand;
+use prost::Message;
+use sha2::{Digest, Sha256};
+use sqlx::{postgres::PgRow, Postgres, Row, Transaction};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use tonic::Status;
+
+use crate::auth::Tenant;
+use crate::db::{db_err, PgPool};
+use crate::value;
+use crate::watch::ChangeStream;
+
+/// First bytes of every archive.
+pub const MAGIC: &[u8] = b"BROTHER-ARCHIVE\n";
+/// `ArchiveHeader.format` written, and the only one read.
+pub const FORMAT: u32 = 1;
+/// Rows per export query and per import insert.
+const PAGE: i64 = 1000;
+/// Bytes per streamed chunk; well below `limits.max_message_bytes`.
+const CHUNK: usize = 1024 * 1024;
+/// Largest single record a reader accepts.
+const MAX_RECORD: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Write a tenant's objects and associations to an archive file.
+    Export {
+        tenant: u32,
+        file: PathBuf,
+    },
+    /// Restore an archive into a tenant with no data.
+    Import {
+        file: PathBuf,
+        /// Restore into this tenant instead of the archive's.  In the
+        /// cluster it came from, its ids then exist in both tenants.
+        #[arg(long)]
+        tenant: Option<u32>,
+    },
+}
+
+/// `brother tenant <command>`.  Writes go straight to the database, past
+/// any running server's cache.
+pub async fn run(command: Command, pool: &PgPool) -> anyhow::Result<()> {
+    let db = Arc::new(pool.clone());
+    match command {
+        Command::Export { tenant, file } => {
+            // Written aside and renamed, so a failed export leaves no
+            // archive that merely looks truncated.
+            let mut partial = file.clone().into_os_string();
+            partial.push(".partial");
+            let mut out = BufWriter::new(File::create(&partial)?);
+
+            let mut check = Reader::default();
+            let mut chunks = export(db, Tenant(tenant));
+            while let Some(chunk) = chunks.next().await {
+                let chunk = chunk?;
+                out.write_all(&chunk)?;
+                check.feed(&chunk);
+                while check.next()?.is_some() {}
+            }
+            let (objects, associations) = check.finish()?;
+            out.into_inner()?.sync_all()?;
+            std::fs::rename(&partial, &file)?;
+            println!(
+                "exported tenant {tenant}: {objects} objects, {associations} associations to {}",
+                file.display()
+            );
+        }
+        Command::Import { file, tenant } => {
+            let mut import = Import::new(db, tenant.map(Tenant));
+            let mut file = File::open(file)?;
+            let mut buf = vec![0; CHUNK];
+            loop {
+                let n = file.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                import.feed(&buf[..n]).await?;
+            }
+            let done = import.finish().await?;
+            println!(
+                "imported {} objects, {} associations and {} unique indexes into tenant {}",
+                done.objects, done.associations, done.unique_indexes, done.tenant.0
+            );
+        }
+    }
+    Ok(())
+}
+
+// ─────────────────── Format ───────────────────
+
+/// Frames records and keeps the trailer's counts and hash.
+pub struct Writer {
+    hash: Sha256,
+    objects: u64,
+    associations: u64,
+}
+
+impl Writer {
+    /// Magic and header.
+    pub fn start(tenant: Tenant, unique_indexes: Vec<UniqueIndex>, out: &mut Vec<u8>) -> Self {
+        let mut w = Writer {
+            hash: Sha256::new(),
+            objects: 0,
+            associations: 0,
+        };
+        out.extend_from_slice(MAGIC);
+        w.hash.update(MAGIC);
+        w.push(
+            Record::Header(ArchiveHeader {
+                format: FORMAT,
+                tenant: tenant.0,
+                created_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |d| d.as_millis() as u64),
+                unique_indexes,
+            }),
+            out,
+        );
+        w
+    }
+
+    pub fn push(&mut self, record: Record, out: &mut Vec<u8>) {
+        match record {
+            Record::Object(_) => self.objects += 1,
+            Record::Association(_) => self.associations += 1,
+            _ => {}
+        }
+        let from = out.len();
+        ArchiveRecord { record: Some(record) }
+            .encode_length_delimited(out)
+            .expect("Vec grows as needed");
+        self.hash.update(&out[from..]);
+    }
+
+    pub fn finish(self, out: &mut Vec<u8>) {
+        let trailer = ArchiveTrailer {
+            objects: self.objects,
+            associations: self.associations,
+            sha256: self.hash.finalize().to_vec(),
+        };
+        ArchiveRecord {
+            record: Some(Record::Trailer(trailer)),
+        }
+        .encode_length_delimited(out)
+        .expect("Vec grows as needed");
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Stage {
+    #[default]
+    Magic,
+    Header,
+    Body,
+    Done,
+}
+
+/// Takes an archive in chunks cut anywhere and hands back whole records,
+/// checking order, counts and the hash on the way.
+#[derive(Default)]
+pub struct Reader {
+    buf: Vec<u8>,
+    /// Start of the bytes not yet decoded.
+    pos: usize,
+    hash: Sha256,
+    stage: Stage,
+    objects: u64,
+    associations: u64,
+}
+
+impl Reader {
+    pub fn feed(&mut self, chunk: &[u8]) {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// The next record, or `None` until more is fed.  The trailer comes
+    /// back once it has checked out.
+    pub fn next(&mut self) -> Result<Option<Record>, Status> {
+        let rest = &self.buf[self.pos..];
+        match self.stage {
+            Stage::Done if rest.is_empty() => return Ok(None),
+            Stage::Done => return Err(damaged("bytes after the trailer")),
+            Stage::Magic if rest.len() < MAGIC.len() => {
+                return match MAGIC.starts_with(rest) {
+                    true => Ok(None),
+                    false => Err(not_an_archive()),
+                };
+            }
+            Stage::Magic => {
+                if &rest[..MAGIC.len()] != MAGIC {
+                    return Err(not_an_archive());
+                }
+                self.hash.update(MAGIC);
+                self.pos += MAGIC.len();
+                self.stage = Stage::Header;
+                return self.next();
+            }
+            Stage::Header | Stage::Body => {}
+        }
+
+        // A length varint is at most 10 bytes, the last without the high bit.
+        let Some(end) = rest.iter().take(10).position(|b| b & 0x80 == 0) else {
+            return match rest.len() < 10 {
+                true => Ok(None),
+                false => Err(damaged("bad record length")),
+            };
+        };
+        let len = prost::decode_length_delimiter(&rest[..=end])
+            .map_err(|e| damaged(&e.to_string()))?;
+        if len > MAX_RECORD {
+            return Err(damaged("record too large"));
+        }
+        let frame = end + 1 + len;
+        if rest.len() < frame {
+            return Ok(None);
+        }
+        let record = ArchiveRecord::decode(&rest[end + 1..frame])
+            .map_err(|e| damaged(&e.to_string()))?
+            .record
+            .ok_or_else(|| damaged("empty record"))?;
+
+        match (&record, self.stage) {
+            (Record::Header(h), Stage::Header) if h.format != FORMAT => {
+                return Err(Status::failed_precondition(format!(
+                    "archive format {} is not supported (this brother reads {FORMAT})",
+                    h.format
+                )));
+            }
+            (Record::Header(_), Stage::Header) => self.stage = Stage::Body,
+            (_, Stage::Header) => return Err(damaged("no header")),
+            (Record::Header(_), _) => return Err(damaged("second header")),
+            (Record::Object(_), _) => self.objects += 1,
+            (Record::Association(_), _) => self.associations += 1,
+            (Record::Trailer(t), _) => {
+                if t.sha256 != self.hash.clone().finalize().as_slice() {
+                    return Err(Status::data_loss("archive checksum mismatch"));
+                }
+                if (t.objects, t.associations) != (self.objects, self.associations) {
+                    return Err(damaged("record counts differ from the trailer"));
+                }
+                self.stage = Stage::Done;
+            }
+        }
+        if self.stage != Stage::Done {
+            self.hash.update(&rest[..frame]);
+        }
+        self.pos += frame;
+        Ok(Some(record))
+    }
+
+    /// Objects and associations read, once the trailer has checked out and
+    /// nothing follows it.
+    pub fn finish(&self) -> Result<(u64, u64), Status> {
+        if self.stage != Stage::Done {
+            return Err(Status::data_loss("archive is truncated"));
+        }
+        if self.pos != self.buf.len() {
+            return Err(damaged("bytes after the trailer"));
+        }
+        Ok((self.objects, self.associations))
+    }
+}
+
+fn damaged(why: &str) -> Status {
+    Status::data_loss(format!("archive is damaged: {why}"))
+}
+
+fn not_an_archive() -> Status {
+    Status::invalid_argument("not a brother archive")
+}
+
+// ─────────────────── Export ───────────────────
+
+/// The archive of `tenant`, in chunks of about [`CHUNK`] bytes.
+pub fn export(db: Arc<PgPool>, tenant: Tenant) -> ChangeStream<Vec<u8>> {
+    let (tx, rx) = mpsc::channel(4);
+    tokio::spawn(async move {
+        if let Err(e) = write_archive(&db, tenant, &tx).await {
+            let _ = tx.send(Err(e)).await;
+        }
+    });
+    Box::pin(ReceiverStream::new(rx))
+}
+
+type Chunks = mpsc::Sender<Result<Vec<u8>, Status>>;
+
+async fn write_archive(db: &PgPool, tenant: Tenant, tx: &Chunks) -> Result<(), Status> {
+    let mut snapshot = db.begin().await.map_err(db_err)?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+        .execute(&mut *snapshot)
+        .await
+        .map_err(db_err)?;
+
+    let unique_indexes = sqlx::query(
+        r#"SELECT type, path FROM tao.unique_keys WHERE tenant = $1 ORDER BY type, path"#,
+    )
+    .bind(tenant.db())
+    .fetch_all(&mut *snapshot)
+    .await
+    .map_err(db_err)?
+    .iter()
+    .map(|r| UniqueIndex {
+        otype: r.get::<i32, _>("type") as u32,
+        path: r.get("path"),
+    })
+    .collect();
+
+    let mut out = Vec::with_capacity(2 * CHUNK);
+    let mut w = Writer::start(tenant, unique_indexes, &mut out);
+
+    let mut after = (i32::MIN, i64::MIN);
+    loop {
+        let rows = sqlx::query(
+            r#"SELECT type, id, version, attributes,
+                      (extract(epoch FROM expires_at) * 1000)::BIGINT AS expires_at
+                 FROM tao.objects
+                WHERE tenant = $1
+                  AND deleted_at IS NULL
+                  AND (expires_at IS NULL OR expires_at > now())
+                  AND (type, id) > ($2, $3)
+                ORDER BY type, id
+                LIMIT $4"#,
+        )
+        .bind(tenant.db())
+        .bind(after.0)
+        .bind(after.1)
+        .bind(PAGE)
+        .fetch_all(&mut *snapshot)
+        .await
+        .map_err(db_err)?;
+
+        for r in &rows {
+            after = (r.get("type"), r.get("id"));
+            w.push(Record::Object(object(tenant, r)), &mut out);
+        }
+        send_full(tx, &mut out).await?;
+        if (rows.len() as i64) < PAGE {
+            break;
+        }
+    }
+
+    let mut after = (String::new(), i64::MIN, i64::MIN);
+    loop {
+        let rows = sqlx::query(
+            r#"SELECT type, source_id, target_id, time, position, attributes,
+                      (extract(epoch FROM expires_at) * 1000)::BIGINT AS expires_at
+                 FROM tao.associations
+                WHERE tenant = $1
+                  AND deleted_at IS NULL
+                  AND (expires_at IS NULL OR expires_at > now())
+                  AND (type, source_id, target_id) > ($2, $3, $4)
+                ORDER BY type, source_id, target_id
+                LIMIT $5"#,
+        )
+        .bind(tenant.db())
+        .bind(&after.0)
+        .bind(after.1)
+        .bind(after.2)
+        .bind(PAGE)
+        .fetch_all(&mut *snapshot)
+        .await
+        .map_err(db_err)?;
+
+        for r in &rows {
+            after = (r.get("type"), r.get("source_id"), r.get("target_id"));
+            w.push(Record::Association(association(tenant, r)), &mut out);
+        }
+        send_full(tx, &mut out).await?;
+        if (rows.len() as i64) < PAGE {
+            break;
+        }
+    }
+
+    snapshot.commit().await.map_err(db_err)?;
+    w.finish(&mut out);
+    send_full(tx, &mut out).await?;
+    send(tx, out).await
+}
+
+/// Sends whole [`CHUNK`]s off the front of `out`.
+async fn send_full(tx: &Chunks, out: &mut Vec<u8>) -> Result<(), Status> {
+    while out.len() >= CHUNK {
+        let rest = out.split_off(CHUNK);
+        send(tx, std::mem::replace(out, rest)).await?;
+    }
+    Ok(())
+}
+
+async fn send(tx: &Chunks, chunk: Vec<u8>) -> Result<(), Status> {
+    tx.send(Ok(chunk))
+        .await
+        .map_err(|_| Status::cancelled("export abandoned"))
+}
+
+fn object(tenant: Tenant, r: &PgRow) -> Object {
+    Object {
+        tenant: tenant.0,
+        r#type: r.get::<i32, _>("type") as u32,
+        id: r.get::<i64, _>("id") as u64,
+        version: r.get::<i32, _>("version") as u32,
+        attributes: value::json_to_attrs(r.get("attributes")),
+        expires_at: r.get::<Option<i64>, _>("expires_at").map(|t| t as u64),
+    }
+}
+
+fn association(tenant: Tenant, r: &PgRow) -> Association {
+    Association {
+        tenant: tenant.0,
+        r#type: r.get("type"),
+        source_id: r.get::<i64, _>("source_id") as u64,
+        target_id: r.get::<i64, _>("target_id") as u64,
+        time: r.get::<i64, _>("time") as u64,
+        position: r.get::<i64, _>("position") as u64,
+        attributes: value::json_to_attrs(r.get("attributes")),
+        expires_at: r.get::<Option<i64>, _>("expires_at").map(|t| t as u64),
+    }
+}
+
+// ─────────────────── Import ───────────────────
+
+/// What an import restored.
+#[derive(Debug, Clone, Copy)]
+pub struct Imported {
+    pub tenant: Tenant,
+    pub objects: u64,
+    pub associations: u64,
+    pub unique_indexes: u32,
+}
+
+/// An import in progress: [`feed`](Self::feed) the archive, then
+/// [`finish`](Self::finish).  Dropped before that, nothing is written.
+pub struct Import {
+    db: Arc<PgPool>,
+    into: Option<Tenant>,
+    reader: Reader,
+    /// Opened at the header, once the target tenant is known.
+    txn: Option<(Tenant, Transaction<'static, Postgres>)>,
+    /// From the header; declared once everything is in.
+    unique_indexes: Vec<UniqueIndex>,
+    objects: Vec<Object>,
+    associations: Vec<Association>,
+}
+
+impl Import {
+    /// `into` overrides the archive's own tenant.
+    pub fn new(db: Arc<PgPool>, into: Option<Tenant>) -> Self {
+        Import {
+            db,
+            into,
+            reader: Reader::default(),
+            txn: None,
+            unique_indexes: Vec::new(),
+            objects: Vec::new(),
+            associations: Vec::new(),
+        }
+    }
+
+    pub async fn feed(&mut self, chunk: &[u8]) -> Result<(), Status> {
+        self.reader.feed(chunk);
+        while let Some(record) = self.reader.next()? {
+            match record {
+                Record::Header(h) => {
+                    self.unique_indexes = h.unique_indexes;
+                    self.begin(self.into.unwrap_or(Tenant(h.tenant))).await?
+                }
+                Record::Object(o) => {
+                    self.objects.push(o);
+                    if self.objects.len() as i64 >= PAGE {
+                        self.flush().await?;
+                    }
+                }
+                Record::Association(a) => {
+                    self.associations.push(a);
+                    if self.associations.len() as i64 >= PAGE {
+                        self.flush().await?;
+                    }
+                }
+                Record::Trailer(_) => {} // checked by the reader
+            }
+        }
+        Ok(())
+    }
+
+    /// Commits, once the whole archive has checked out.
+    pub async fn finish(mut self) -> Result<Imported, Status> {
+        let (objects, associations) = self.reader.finish()?;
+        self.flush().await?;
+        let (tenant, mut txn) = self.txn.take().expect("a finished archive has a header");
+
+        sqlx::query(
+            r#"INSERT INTO tao.association_counts (tenant, type, source_id, count)
+               SELECT tenant, type, source_id, count(*)
+                 FROM tao.associations
+                WHERE tenant = $1 AND deleted_at IS NULL
+                GROUP BY tenant, type, source_id
+               ON CONFLICT (tenant, type, source_id) DO UPDATE SET count = EXCLUDED.count"#,
+        )
+        .bind(tenant.db())
+        .execute(&mut *txn)
+        .await
+        .map_err(db_err)?;
+
+        // Claims the imported values; a shared one fails the import.
+        for index in &self.unique_indexes {
+            sqlx::query("SELECT tao.tao_declare_unique($1, $2, $3)")
+                .bind(tenant.db())
+                .bind(index.otype as i32)
+                .bind(&index.path)
+                .execute(&mut *txn)
+                .await
+                .map_err(db_err)?;
+        }
+
+        txn.commit().await.map_err(db_err)?;
+        Ok(Imported {
+            tenant,
+            objects,
+            associations,
+            unique_indexes: self.unique_indexes.len() as u32,
+        })
+    }
+
+    async fn begin(&mut self, tenant: Tenant) -> Result<(), Status> {
+        let mut txn = self.db.begin().await.map_err(db_err)?;
+        // Two imports into one tenant would each find it empty.
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtext('brother.import'), $1)")
+            .bind(tenant.0 as i32)
+            .execute(&mut *txn)
+            .await
+            .map_err(db_err)?;
+        let taken: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (SELECT 1 FROM tao.objects      WHERE tenant = $1)
+                   OR EXISTS (SELECT 1 FROM tao.associations WHERE tenant = $1)"#,
+        )
+        .bind(tenant.db())
+        .fetch_one(&mut *txn)
+        .await
+        .map_err(db_err)?;
+        if taken {
+            return Err(Status::failed_precondition(format!(
+                "tenant {} already has objects or associations",
+                tenant.0
+            )));
+        }
+        self.txn = Some((tenant, txn));
+        Ok(())
+    }
+
+    /// Inserts what is buffered.  Plain `INSERT`s: the touch trigger only
+    /// bumps versions on `UPDATE`.
+    async fn flush(&mut self) -> Result<(), Status> {
+        let Some((tenant, txn)) = self.txn.as_mut() else {
+            return Ok(());
+        };
+
+        if !self.objects.is_empty() {
+            let objects = std::mem::take(&mut self.objects);
+            let otypes: Vec<i32> = objects.iter().map(|o| o.r#type as i32).collect();
+            let ids: Vec<i64> = objects.iter().map(|o| o.id as i64).collect();
+            let versions: Vec<i32> = objects.iter().map(|o| o.version as i32).collect();
+            let attrs: Vec<_> = objects.iter().map(|o| value::attrs_to_json(&o.attributes)).collect();
+            let expires: Vec<_> = objects.iter().map(|o| o.expires_at.map(|t| t as i64)).collect();
+            sqlx::query(
+                r#"INSERT INTO tao.objects (tenant, type, id, version, attributes, expires_at)
+                   SELECT $1, t, i, v, a, to_timestamp(e / 1000.0)
+                     FROM unnest($2::INT[], $3::BIGINT[], $4::INT[], $5::JSONB[], $6::BIGINT[])
+                          AS x (t, i, v, a, e)"#,
+            )
+            .bind(tenant.db())
+            .bind(&otypes)
+            .bind(&ids)
+            .bind(&versions)
+            .bind(&attrs)
+            .bind(&expires)
+            .execute(&mut **txn)
+            .await
+            .map_err(db_err)?;
+        }
+
+        if !self.associations.is_empty() {
+            let edges = std::mem::take(&mut self.associations);
+            let types: Vec<&str> = edges.iter().map(|a| a.r#type.as_str()).collect();
+            let sources: Vec<i64> = edges.iter().map(|a| a.source_id as i64).collect();
+            let targets: Vec<i64> = edges.iter().map(|a| a.target_id as i64).collect();
+            let times: Vec<i64> = edges.iter().map(|a| a.time as i64).collect();
+            let positions: Vec<i64> = edges.iter().map(|a| a.position as i64).collect();
+            let attrs: Vec<_> = edges.iter().map(|a| value::attrs_to_json(&a.attributes)).collect();
+            let expires: Vec<_> = edges.iter().map(|a| a.expires_at.map(|t| t as i64)).collect();
+            sqlx::query(
+                r#"INSERT INTO tao.associations
+                          (tenant, type, source_id, target_id, time, position, attributes, expires_at)
+                   SELECT $1, t, s, d, tm, p, a, to_timestamp(e / 1000.0)
+                     FROM unnest($2::TEXT[], $3::BIGINT[], $4::BIGINT[], $5::BIGINT[],
+                                 $6::BIGINT[], $7::JSONB[], $8::BIGINT[])
+                          AS x (t, s, d, tm, p, a, e)"#,
+            )
+            .bind(tenant.db())
+            .bind(&types)
+            .bind(&sources)
+            .bind(&targets)
+            .bind(&times)
+            .bind(&positions)
+            .bind(&attrs)
+            .bind(&expires)
+            .execute(&mut **txn)
+            .await
+            .map_err(db_err)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+    use tonic::Code;
+
+    use super::*;
+    use crate::db;
+    use crate::store::{AssocRange, PgStore, TaoStore};
+
+    fn index() -> UniqueIndex {
+        UniqueIndex {
+            otype: 5,
+            path: vec!["name".to_owned()],
+        }
+    }
+
+    fn named(id: u64, name: &str) -> Object {
+        Object {
+            r#type: 5,
+            id,
+            attributes: HashMap::from([("name".to_owned(), value::from_json(json!(name)))]),
+            ..Default::default()
+        }
+    }
+
+    fn edge(source_id: u64, target_id: u64) -> Association {
+        Association {
+            r#type: "follows".to_owned(),
+            source_id,
+            target_id,
+            time: 1,
+            position: 2,
+            ..Default::default()
+        }
+    }
+
+    /// Three objects and two edges of tenant 7.
+    fn records() -> Vec<Record> {
+        vec![
+            Record::Object(named(1, "ada")),
+            Record::Object(named(2, "bea")),
+            Record::Object(named(3, "cy")),
+            Record::Association(edge(1, 2)),
+            Record::Association(edge(2, 3)),
+        ]
+    }
+
+    fn archive(records: Vec<Record>) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut w = Writer::start(Tenant(7), vec![index()], &mut out);
+        for record in records {
+            w.push(record, &mut out);
+        }
+        w.finish(&mut out);
+        out
+    }
+
+    /// Everything `chunks` hold, or the first error.
+    fn read(chunks: &[&[u8]]) -> Result<(Reader, Vec<Record>), Status> {
+        let mut reader = Reader::default();
+        let mut records = Vec::new();
+        for chunk in chunks {
+            reader.feed(chunk);
+            while let Some(record) = reader.next()? {
+                records.push(record);
+            }
+        }
+        Ok((reader, records))
+    }
+
+    fn error(bytes: &[u8]) -> Status {
+        match read(&[bytes]) {
+            Ok((reader, _)) => reader.finish().unwrap_err(),
+            Err(e) => e,
+        }
+    }
+
+    #[test]
+    fn chunks_cut_at_every_offset_read_back() {
+        let bytes = archive(records());
+        for cut in 0..=bytes.len() {
+            let (reader, got) = read(&[&bytes[..cut], &bytes[cut..]]).unwrap();
+            assert_eq!(reader.finish().unwrap(), (3, 2), "cut at {cut}");
+
+            let Some(Record::Header(header)) = got.first() else {
+                panic!("no header first, cut at {cut}");
+            };
+            assert_eq!((header.format, header.tenant), (FORMAT, 7));
+            assert_eq!(header.unique_indexes, [index()]);
+            assert_eq!(got[1..got.len() - 1], records());
+            assert!(matches!(got.last(), Some(Record::Trailer(_))));
+        }
+    }
+
+    #[test]
+    fn a_truncated_archive_is_data_loss() {
+        let bytes = archive(records());
+        for len in 0..bytes.len() {
+            let status = error(&bytes[..len]);
+            assert_eq!(status.code(), Code::DataLoss, "{len} bytes");
+            assert_eq!(status.message(), "archive is truncated", "{len} bytes");
+        }
+    }
+
+    #[tokio::test]
+    async fn an_import_of_a_truncated_archive_fails_before_it_writes() {
+        // Never connected: the header, where it would be, is cut off.
+        let pool = PgPool::connect_lazy("postgres://localhost/nowhere").unwrap();
+        let bytes = archive(records());
+        let mut import = Import::new(Arc::new(pool), None);
+        import.feed(&bytes[..MAGIC.len() + 3]).await.unwrap();
+        let status = import.finish().await.unwrap_err();
+        assert_eq!(status.code(), Code::DataLoss);
+    }
+
+    #[test]
+    fn a_flipped_byte_fails_the_checksum() {
+        let mut bytes = archive(records());
+        let at = bytes.windows(3).position(|w| w == b"bea").unwrap();
+        bytes[at] = b'B';
+        let status = error(&bytes);
+        assert_eq!(status.code(), Code::DataLoss);
+        assert_eq!(status.message(), "archive checksum mismatch");
+    }
+
+    #[test]
+    fn malformed_archives() {
+        let mut trailing = archive(records());
+        trailing.push(0);
+        let status = error(&trailing);
+        assert_eq!(
+            (status.code(), status.message()),
+            (Code::DataLoss, "archive is damaged: bytes after the trailer")
+        );
+
+        for bytes in [&b"BROTHER-ARCHIVE?"[..], b"PK\x03\x04", b"brother"] {
+            let status = error(bytes);
+            assert_eq!(status.code(), Code::InvalidArgument, "{bytes:?}");
+            assert_eq!(status.message(), "not a brother archive");
+        }
+
+        let mut future = MAGIC.to_vec();
+        let header = ArchiveHeader {
+            format: 2,
+            tenant: 7,
+            ..Default::default()
+        };
+        ArchiveRecord { record: Some(Record::Header(header)) }
+            .encode_length_delimited(&mut future)
+            .unwrap();
+        let status = error(&future);
+        assert_eq!(status.code(), Code::FailedPrecondition);
+        assert!(status.message().contains("format 2 is not supported"), "{}", status.message());
+
+        let header = ArchiveHeader {
+            format: FORMAT,
+            tenant: 7,
+            ..Default::default()
+        };
+        let second = archive(vec![Record::Object(named(1, "ada")), Record::Header(header)]);
+        let status = error(&second);
+        assert_eq!(status.message(), "archive is damaged: second header");
+
+        let mut huge = MAGIC.to_vec();
+        prost::encode_length_delimiter(MAX_RECORD + 1, &mut huge).unwrap();
+        let status = error(&huge);
+        assert_eq!(
+            (status.code(), status.message()),
+            (Code::DataLoss, "archive is damaged: record too large")
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn export_then_import_into_another_tenant() {
+        let db = Arc::new(db::test_pool().await);
+        let store = PgStore::new(db.clone());
+        let (from, into) = (db::test_tenant(), db::test_tenant());
+
+        let mut ids = Vec::new();
+        for name in ["ada", "bea", "cy"] {
+            ids.push(store.put_object(from, &named(0, name)).await.unwrap().id);
+        }
+        let gone = store.put_object(from, &named(0, "dee")).await.unwrap().id;
+        store.delete_object(from, 5, gone, None).await.unwrap();
+        store.put_association(from, &edge(ids[0], ids[1])).await.unwrap();
+        store.put_association(from, &edge(ids[0], ids[2])).await.unwrap();
+        sqlx::query("SELECT tao.tao_declare_unique($1, 5, ARRAY['name'])")
+            .bind(from.db())
+            .execute(&*db)
+            .await
+            .unwrap();
+
+        let mut import = Import::new(db.clone(), Some(into));
+        let mut chunks = export(db.clone(), from);
+        while let Some(chunk) = chunks.next().await {
+            import.feed(&chunk.unwrap()).await.unwrap();
+        }
+        let done = import.finish().await.unwrap();
+        assert_eq!(done.tenant, into);
+        assert_eq!((done.objects, done.associations, done.unique_indexes), (3, 2, 1));
+
+        for &id in &ids {
+            let ours = store.get_object(from, 5, id).await.unwrap().unwrap();
+            let theirs = store.get_object(into, 5, id).await.unwrap().unwrap();
+            assert_eq!((theirs.tenant, theirs.version), (into.0, ours.version));
+            assert_eq!(theirs.attributes, ours.attributes);
+        }
+        assert!(store.get_object(into, 5, gone).await.unwrap().is_none());
+
+        let range = AssocRange {
+            atype: "follows".to_owned(),
+            source_id: ids[0] as i64,
+            after: None,
+            ascending: true,
+            time_from: None,
+            time_to: None,
+            limit: 10,
+        };
+        let targets: Vec<u64> = store.associations(into, &range).await.unwrap().iter().map(|a| a.target_id).collect();
+        assert_eq!(targets, [ids[1], ids[2]]);
+        assert_eq!(store.count_associations(into, "follows", ids[0] as i64).await.unwrap(), 2);
+
+        // The index came along and holds the imported values.
+        let taken = store.put_object(into, &named(0, "ada")).await.unwrap_err();
+        assert_eq!(taken.code(), Code::AlreadyExists);
+
+        // Exactly once.
+        let mut again = Import::new(db.clone(), Some(into));
+        let mut chunks = export(db.clone(), from);
+        let mut refused = None;
+        while let Some(chunk) = chunks.next().await {
+            if let Err(e) = again.feed(&chunk.unwrap()).await {
+                refused = Some(e);
+                break;
+            }
+        }
+        assert_eq!(refused.unwrap().code(), Code::FailedPrecondition);
+    }
+}
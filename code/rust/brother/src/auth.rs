@@ -0,0 +1,187 @@
+//! src/auth.rs
+//! Caller authentication + tenant resolution for the Brother service.
+//!
+//! Every RPC passes through [`Authenticator`] (a tonic interceptor) which
+//! works out *who* is calling and stores the resulting [`Tenant`] in the
+//! request extensions.  Handlers never trust a tenant coming from the
+//! request body – they call [`tenant`] and, where the body carries one,
+//! [`ensure_tenant`].
+//!
+//! Two identities are understood, checked in this order:
+//!
+//! * **mTLS** – the SHA-256 fingerprint (lower-case hex) of the client's
+//!   leaf certificate, looked up in `BROTHER_CLIENT_CERTS`.
+//! * **Bearer token** – the `authorization: Bearer <token>` header, looked
+//!   up in `BROTHER_AUTH_TOKENS`.
+//!
+//! Both env-vars are comma separated `<key>=<tenant>` lists, e.g.
+//! `BROTHER_AUTH_TOKENS="s3cr3t=1,0th3r=2"`.
+//!
+//! Tenants listed in `BROTHER_ADMIN_TENANTS` (comma separated ids) may also
+//! call the admin RPCs – see [`ensure_admin`].
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context};
+use sha2::{Digest, Sha256};
+use tonic::{service::Interceptor, Request, Status};
+
+/// The tenant an authenticated caller acts on behalf of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tenant(pub u32);
+
+impl Tenant {
+    /// Value bound to the `tenant BIGINT` columns.
+    pub fn db(self) -> i64 {
+        self.0 as i64
+    }
+}
+
+/// Request extension marking a caller allowed to use the admin RPCs.
+#[derive(Debug, Clone, Copy)]
+struct Admin;
+
+/// Interceptor that resolves the caller's [`Tenant`].
+#[derive(Clone, Default)]
+pub struct Authenticator {
+    tokens: Arc<HashMap<String, u32>>,
+    certs:  Arc<HashMap<String, u32>>,
+    admins: Arc<HashSet<u32>>,
+}
+
+impl Authenticator {
+    pub fn new(tokens: HashMap<String, u32>, certs: HashMap<String, u32>) -> Self {
+        Self {
+            tokens: Arc::new(tokens),
+            certs:  Arc::new(certs),
+            admins: Arc::default(),
+        }
+    }
+
+    /// Tenants that may call the admin RPCs.
+    pub fn with_admins(mut self, admins: HashSet<u32>) -> Self {
+        self.admins = Arc::new(admins);
+        self
+    }
+
+    /// Build from `BROTHER_AUTH_TOKENS` / `BROTHER_CLIENT_CERTS`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let tokens = parse_map("BROTHER_AUTH_TOKENS")?;
+        let certs = parse_map("BROTHER_CLIENT_CERTS")?
+            .into_iter()
+            .map(|(fp, t)| (fp.to_ascii_lowercase(), t))
+            .collect::<HashMap<_, _>>();
+
+        if tokens.is_empty() && certs.is_empty() {
+            tracing::warn!("no credentials configured – every RPC will be rejected");
+        }
+        Ok(Self::new(tokens, certs).with_admins(parse_ids("BROTHER_ADMIN_TENANTS")?))
+    }
+
+    fn resolve<T>(&self, req: &Request<T>) -> Result<Tenant, Status> {
+        if let Some(leaf) = req.peer_certs().as_ref().and_then(|c| c.first().cloned()) {
+            let fp = fingerprint(&leaf);
+            if let Some(t) = self.certs.get(&fp) {
+                return Ok(Tenant(*t));
+            }
+        }
+
+        let Some(header) = req.metadata().get("authorization") else {
+            return Err(Status::unauthenticated("missing credentials"));
+        };
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|h| h.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("malformed authorization header"))?;
+
+        self.tokens
+            .get(token.trim())
+            .map(|t| Tenant(*t))
+            .ok_or_else(|| Status::unauthenticated("invalid credentials"))
+    }
+}
+
+impl Interceptor for Authenticator {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        let tenant = self.resolve(&req)?;
+        if self.admins.contains(&tenant.0) {
+            req.extensions_mut().insert(Admin);
+        }
+        req.extensions_mut().insert(tenant);
+        Ok(req)
+    }
+}
+
+/// The tenant the interceptor attached to this request.
+pub fn tenant<T>(req: &Request<T>) -> Result<Tenant, Status> {
+    req.extensions()
+        .get::<Tenant>()
+        .copied()
+        .ok_or_else(|| Status::unauthenticated("request was not authenticated"))
+}
+
+/// Reject a body tenant that disagrees with the caller.
+///
+/// `0` is the proto3 default and means "not set" – it is accepted and the
+/// caller's tenant is used.
+pub fn ensure_tenant(caller: Tenant, body: u32) -> Result<(), Status> {
+    if body != 0 && body != caller.0 {
+        return Err(Status::permission_denied(format!(
+            "tenant {body} does not match the authenticated tenant"
+        )));
+    }
+    Ok(())
+}
+
+/// Reject a caller that is not an admin tenant.
+pub fn ensure_admin<T>(req: &Request<T>) -> Result<(), Status> {
+    match req.extensions().get::<Admin>() {
+        Some(Admin) => Ok(()),
+        None => Err(Status::permission_denied("admin privileges required")),
+    }
+}
+
+/// Lower-case hex SHA-256 of a DER certificate.
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn parse_map(var: &str) -> anyhow::Result<HashMap<String, u32>> {
+    let Ok(raw) = std::env::var(var) else {
+        return Ok(HashMap::new());
+    };
+
+    raw.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|entry| {
+            let (key, tenant) = entry
+                .rsplit_once('=')
+                .ok_or_else(|| anyhow!("{var}: expected `<key>=<tenant>`, got `{entry}`"))?;
+            let tenant = tenant
+                .trim()
+                .parse::<u32>()
+                .with_context(|| format!("{var}: bad tenant in `{entry}`"))?;
+            Ok((key.trim().to_owned(), tenant))
+        })
+        .collect()
+}
+
+fn parse_ids(var: &str) -> anyhow::Result<HashSet<u32>> {
+    let Ok(raw) = std::env::var(var) else {
+        return Ok(HashSet::new());
+    };
+
+    raw.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .map(|id| id.parse::<u32>().with_context(|| format!("{var}: bad tenant `{id}`")))
+        .collect()
+}
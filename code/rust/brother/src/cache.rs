@@ -0,0 +1,540 @@
+//! src/cache.rs
+//! Read-through cache in front of `GetObject` / `GetAssociations`.
+//!
+//! Entries live in one LRU per tenant, so a busy tenant evicts its own
+//! entries first: past its per-tenant budget it pops its own oldest, and
+//! when the whole cache is full the largest tenant gives one up.
+//!
+//! Writes through this service forget what they touched once they have
+//! committed.  A read that raced such a write must not put the old row
+//! back, so every fill carries the tenant's epoch from before its query
+//! and is dropped if a write bumped it since.  Writes made behind the
+//! service's back are not seen; a maximum entry age bounds how long such
+//! a write can stay hidden.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU64, Ordering::Relaxed},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use brother::pb::{GetAssociationsResponse, Object};
+use lru::LruCache;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+use crate::auth::Tenant;
+
+/// One `GetAssociations` page: the edge list plus how it was asked for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AssocPage {
+    pub atype: String,
+    pub source_id: i64,
+    pub limit: i64,
+    pub cursor: Vec<u8>,
+    pub order: i32,
+    pub time_from: Option<u64>,
+    pub time_to: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Object { otype: u32, id: u64 },
+    Assocs(AssocPage),
+}
+
+enum Entry {
+    Object(Object),
+    Assocs(GetAssociationsResponse),
+}
+
+struct Slot {
+    entry: Entry,
+    filled: Instant,
+}
+
+#[derive(Default)]
+struct Shard {
+    lru: Option<LruCache<Key, Slot>>,
+    /// Bumped by every write; see the module docs.
+    epoch: u64,
+    /// Cached pages by source node, so an edge write finds them.
+    pages: HashMap<i64, HashSet<Key>>,
+}
+
+impl Shard {
+    fn lru(&mut self) -> &mut LruCache<Key, Slot> {
+        self.lru.get_or_insert_with(LruCache::unbounded)
+    }
+
+    fn len(&self) -> usize {
+        self.lru.as_ref().map_or(0, LruCache::len)
+    }
+
+    fn remove(&mut self, key: &Key) -> bool {
+        let removed = self.lru().pop(key).is_some();
+        if removed {
+            self.unindex(key);
+        }
+        removed
+    }
+
+    fn pop_oldest(&mut self) -> bool {
+        match self.lru().pop_lru() {
+            Some((key, _)) => {
+                self.unindex(&key);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn unindex(&mut self, key: &Key) {
+        if let Key::Assocs(p) = key {
+            if let Some(keys) = self.pages.get_mut(&p.source_id) {
+                keys.remove(key);
+                if keys.is_empty() {
+                    self.pages.remove(&p.source_id);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    shards: HashMap<Tenant, Shard>,
+    len: usize,
+}
+
+#[derive(Default)]
+struct Counters {
+    object_hits: AtomicU64,
+    object_misses: AtomicU64,
+    assoc_hits: AtomicU64,
+    assoc_misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Point-in-time copy of the cache counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub object_hits: u64,
+    pub object_misses: u64,
+    pub assoc_hits: u64,
+    pub assoc_misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+}
+
+pub struct Cache {
+    /// Entries across all tenants; 0 = disabled.
+    capacity: usize,
+    /// Entries a single tenant may hold.
+    per_tenant: usize,
+    /// Entries older than this are not served; `None` = no limit.
+    max_age: Option<Duration>,
+    inner: Mutex<Inner>,
+    counters: Counters,
+}
+
+impl Cache {
+    pub fn new(capacity: usize, per_tenant: usize) -> Self {
+        Self {
+            capacity,
+            per_tenant: per_tenant.min(capacity),
+            max_age: None,
+            inner: Mutex::default(),
+            counters: Counters::default(),
+        }
+    }
+
+    /// Stop serving entries once they are `max_age` old.
+    pub fn with_max_age(mut self, max_age: Option<Duration>) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// A cache that never holds anything.
+    pub fn disabled() -> Self {
+        Self::new(0, 0)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Take before the query whose result will be passed to a `fill_*`.
+    pub fn epoch(&self, tenant: Tenant) -> u64 {
+        if !self.enabled() {
+            return 0;
+        }
+        self.lock().shards.get(&tenant).map_or(0, |s| s.epoch)
+    }
+
+    pub fn object(&self, tenant: Tenant, otype: u32, id: u64) -> Option<Object> {
+        if !self.enabled() {
+            return None;
+        }
+        let key = Key::Object { otype, id };
+        let hit = self.get(tenant, &key, |e| match e {
+            Entry::Object(o) if live(o.expires_at) => Some(o.clone()),
+            _ => None,
+        });
+        self.count(hit.is_some(), &self.counters.object_hits, &self.counters.object_misses);
+        hit
+    }
+
+    pub fn fill_object(&self, tenant: Tenant, epoch: u64, object: &Object) {
+        let key = Key::Object {
+            otype: object.r#type,
+            id: object.id,
+        };
+        self.fill(tenant, epoch, key, Entry::Object(object.clone()));
+    }
+
+    pub fn associations(&self, tenant: Tenant, page: &AssocPage) -> Option<GetAssociationsResponse> {
+        if !self.enabled() {
+            return None;
+        }
+        let key = Key::Assocs(page.clone());
+        let hit = self.get(tenant, &key, |e| match e {
+            Entry::Assocs(r) if r.associations.iter().all(|a| live(a.expires_at)) => {
+                Some(r.clone())
+            }
+            _ => None,
+        });
+        self.count(hit.is_some(), &self.counters.assoc_hits, &self.counters.assoc_misses);
+        hit
+    }
+
+    pub fn fill_associations(
+        &self,
+        tenant: Tenant,
+        epoch: u64,
+        page: AssocPage,
+        response: &GetAssociationsResponse,
+    ) {
+        self.fill(tenant, epoch, Key::Assocs(page), Entry::Assocs(response.clone()));
+    }
+
+    /// After a committed write to the object.
+    pub fn forget_object(&self, tenant: Tenant, otype: u32, id: u64) {
+        self.forget(tenant, |shard| {
+            shard.remove(&Key::Object { otype, id });
+        });
+    }
+
+    /// After a committed write to the whole tenant (an import).
+    pub fn forget_tenant(&self, tenant: Tenant) {
+        self.forget(tenant, |shard| {
+            shard.lru = None;
+            shard.pages.clear();
+        });
+    }
+
+    /// After a committed write to an edge touching `node`, either end.
+    /// Every type is dropped, which covers a registered inverse too.
+    pub fn forget_edges(&self, tenant: Tenant, node: u64) {
+        self.forget(tenant, |shard| {
+            for key in shard.pages.remove(&(node as i64)).unwrap_or_default() {
+                shard.lru().pop(&key);
+            }
+        });
+    }
+
+    pub fn stats(&self) -> Stats {
+        let c = &self.counters;
+        Stats {
+            object_hits: c.object_hits.load(Relaxed),
+            object_misses: c.object_misses.load(Relaxed),
+            assoc_hits: c.assoc_hits.load(Relaxed),
+            assoc_misses: c.assoc_misses.load(Relaxed),
+            evictions: c.evictions.load(Relaxed),
+            entries: self.lock().len,
+        }
+    }
+
+    /// Every `every`, log the counters if anything changed.
+    pub fn spawn_report(self: Arc<Self>, every: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(every);
+            let mut last = None;
+            loop {
+                tick.tick().await;
+                let s = self.stats();
+                if last == Some(s) {
+                    continue;
+                }
+                last = Some(s);
+                info!(
+                    object_hits = s.object_hits,
+                    object_misses = s.object_misses,
+                    assoc_hits = s.assoc_hits,
+                    assoc_misses = s.assoc_misses,
+                    evictions = s.evictions,
+                    entries = s.entries,
+                    "cache stats"
+                );
+            }
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        // Every critical section leaves the maps consistent, so a panic
+        // elsewhere does not poison the data itself.
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn count(&self, hit: bool, hits: &AtomicU64, misses: &AtomicU64) {
+        if hit { hits } else { misses }.fetch_add(1, Relaxed);
+    }
+
+    /// `read` decides whether a fresh entry is still servable; if not, or
+    /// if the entry is too old, it is dropped.
+    fn get<T>(&self, tenant: Tenant, key: &Key, read: impl FnOnce(&Entry) -> Option<T>) -> Option<T> {
+        let mut inner = self.lock();
+        let shard = inner.shards.get_mut(&tenant)?;
+        let slot = shard.lru().get(key)?;
+        let fresh = self.max_age.is_none_or(|age| slot.filled.elapsed() < age);
+        let found = if fresh { read(&slot.entry) } else { None };
+        if found.is_none() && shard.remove(key) {
+            inner.len -= 1;
+        }
+        found
+    }
+
+    fn fill(&self, tenant: Tenant, epoch: u64, key: Key, entry: Entry) {
+        if !self.enabled() {
+            return;
+        }
+        let mut inner = self.lock();
+        let inner = &mut *inner;
+        let shard = inner.shards.entry(tenant).or_default();
+        if shard.epoch != epoch {
+            return;
+        }
+
+        let replaced = shard.remove(&key);
+        if !replaced {
+            inner.len += 1;
+        }
+        if let Key::Assocs(p) = &key {
+            shard.pages.entry(p.source_id).or_default().insert(key.clone());
+        }
+        shard.lru().put(
+            key,
+            Slot {
+                entry,
+                filled: Instant::now(),
+            },
+        );
+
+        // Over its own budget: the tenant pays.  Over the total: the
+        // largest tenant does.
+        let mut evicted = 0;
+        if shard.len() > self.per_tenant && shard.pop_oldest() {
+            evicted += 1;
+        }
+        while inner.len - evicted > self.capacity {
+            let Some(largest) = inner.shards.values_mut().max_by_key(|s| s.len()) else {
+                break;
+            };
+            if !largest.pop_oldest() {
+                break;
+            }
+            evicted += 1;
+        }
+        inner.len -= evicted;
+        self.counters.evictions.fetch_add(evicted as u64, Relaxed);
+    }
+
+    fn forget(&self, tenant: Tenant, drop: impl FnOnce(&mut Shard)) {
+        if !self.enabled() {
+            return;
+        }
+        let mut inner = self.lock();
+        let inner = &mut *inner;
+        // Even with nothing cached yet: a read in flight must not fill.
+        let shard = inner.shards.entry(tenant).or_default();
+        shard.epoch += 1;
+        let before = shard.len();
+        drop(shard);
+        inner.len -= before - shard.len();
+    }
+}
+
+/// Whether an `expires_at` (epoch-ms, unset = never) is still ahead.
+fn live(expires_at: Option<u64>) -> bool {
+    let Some(at) = expires_at else {
+        return true;
+    };
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64);
+    at > now
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(id: u64) -> Object {
+        Object {
+            r#type: 1,
+            id,
+            ..Default::default()
+        }
+    }
+
+    fn page(source_id: i64) -> AssocPage {
+        AssocPage {
+            atype: "likes".into(),
+            source_id,
+            limit: 10,
+            cursor: vec![],
+            order: 0,
+            time_from: None,
+            time_to: None,
+        }
+    }
+
+    fn fill(cache: &Cache, tenant: Tenant, id: u64) {
+        cache.fill_object(tenant, cache.epoch(tenant), &object(id));
+    }
+
+    fn cached(cache: &Cache, tenant: Tenant, id: u64) -> bool {
+        cache.object(tenant, 1, id).is_some()
+    }
+
+    #[test]
+    fn fills_and_forgets() {
+        let cache = Cache::new(10, 10);
+        let t = Tenant(1);
+        assert!(!cached(&cache, t, 1));
+        fill(&cache, t, 1);
+        assert_eq!(cache.object(t, 1, 1), Some(object(1)));
+        assert_eq!(cache.object(t, 2, 1), None);
+
+        cache.forget_object(t, 1, 1);
+        assert!(!cached(&cache, t, 1));
+        let s = cache.stats();
+        assert_eq!((s.object_hits, s.object_misses, s.entries), (1, 3, 0));
+    }
+
+    #[test]
+    fn a_fill_that_raced_a_write_is_dropped() {
+        let cache = Cache::new(10, 10);
+        let t = Tenant(1);
+        let before = cache.epoch(t);
+        // The write commits and forgets while the read is in flight.
+        cache.forget_object(t, 1, 1);
+        cache.fill_object(t, before, &object(1));
+        assert!(!cached(&cache, t, 1));
+        assert_eq!(cache.stats().entries, 0);
+
+        // The epoch is the tenant's: another tenant's write leaves the
+        // fill alone; an edge write of the same tenant drops it.
+        let before = cache.epoch(t);
+        cache.forget_object(Tenant(2), 1, 1);
+        cache.fill_object(t, before, &object(1));
+        assert!(cached(&cache, t, 1));
+        let before = cache.epoch(t);
+        cache.forget_edges(t, 99);
+        cache.fill_object(t, before, &object(2));
+        assert!(!cached(&cache, t, 2));
+    }
+
+    #[test]
+    fn edge_writes_forget_pages_of_either_end() {
+        let cache = Cache::new(10, 10);
+        let t = Tenant(1);
+        let response = GetAssociationsResponse::default();
+        for source in [1, 2] {
+            cache.fill_associations(t, cache.epoch(t), page(source), &response);
+        }
+        assert_eq!(cache.associations(t, &page(1)), Some(response.clone()));
+
+        cache.forget_edges(t, 1);
+        assert_eq!(cache.associations(t, &page(1)), None);
+        assert_eq!(cache.associations(t, &page(2)), Some(response));
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[test]
+    fn a_tenant_over_its_budget_evicts_its_own() {
+        let cache = Cache::new(10, 2);
+        let (busy, quiet) = (Tenant(1), Tenant(2));
+        fill(&cache, quiet, 1);
+        for id in 1..=3 {
+            fill(&cache, busy, id);
+        }
+        assert!(!cached(&cache, busy, 1));
+        assert!(cached(&cache, busy, 2) && cached(&cache, busy, 3));
+        assert!(cached(&cache, quiet, 1));
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.stats().entries, 3);
+    }
+
+    #[test]
+    fn a_full_cache_evicts_from_the_largest_tenant() {
+        let cache = Cache::new(4, 3);
+        let (big, small) = (Tenant(1), Tenant(2));
+        for id in 1..=3 {
+            fill(&cache, big, id);
+        }
+        fill(&cache, small, 1);
+        fill(&cache, small, 2);
+        assert!(!cached(&cache, big, 1));
+        assert!(cached(&cache, small, 1) && cached(&cache, small, 2));
+        assert_eq!(cache.stats().entries, 4);
+    }
+
+    #[test]
+    fn forgetting_a_tenant_leaves_the_others() {
+        let cache = Cache::new(10, 10);
+        fill(&cache, Tenant(1), 1);
+        fill(&cache, Tenant(2), 1);
+        let before = cache.epoch(Tenant(1));
+        cache.forget_tenant(Tenant(1));
+        cache.fill_object(Tenant(1), before, &object(2));
+        assert!(!cached(&cache, Tenant(1), 1) && !cached(&cache, Tenant(1), 2));
+        assert!(cached(&cache, Tenant(2), 1));
+        assert_eq!(cache.stats().entries, 1);
+    }
+
+    #[test]
+    fn old_entries_are_not_served() {
+        let cache = Cache::new(10, 10).with_max_age(Some(Duration::from_millis(50)));
+        let t = Tenant(1);
+        fill(&cache, t, 1);
+        assert!(cached(&cache, t, 1));
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!cached(&cache, t, 1));
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn expired_rows_are_not_served() {
+        let cache = Cache::new(10, 10);
+        let t = Tenant(1);
+        let expired = Object {
+            expires_at: Some(1),
+            ..object(1)
+        };
+        cache.fill_object(t, cache.epoch(t), &expired);
+        assert!(!cached(&cache, t, 1));
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn a_disabled_cache_holds_nothing() {
+        let cache = Cache::disabled();
+        fill(&cache, Tenant(1), 1);
+        assert!(!cached(&cache, Tenant(1), 1));
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
@@ -0,0 +1,533 @@
+//! src/config.rs
+//! Typed settings of the brother binary.
+//!
+//! Read from the TOML file named by `BROTHER_CONFIG` (if set), then
+//! overridden by environment variables, then validated – a bad value
+//! stops start-up with the setting's name in the message.  Every key is
+//! optional; see [`Config::default`] for what an empty file means.
+//!
+//! ```toml
+//! listen = "0.0.0.0:42069"
+//!
+//! [database]
+//! url = "postgres://yugabyte@localhost:5433/yugabyte"
+//! max_connections = 32
+//!
+//! [database.read]                # optional: bounded-stale reads
+//! follower_reads = true          # or url = "postgres://…replica…"
+//! staleness_ms = 30_000
+//!
+//! [tls]
+//! cert = "/etc/brother/server.pem"
+//! key = "/etc/brother/server.key"
+//! client_ca = "/etc/brother/clients.pem"   # optional: enables mTLS
+//!
+//! [limits]
+//! max_message_bytes = 8_388_608
+//! concurrency_per_connection = 64
+//!
+//! [keepalive]
+//! http2_interval_secs = 30
+//!
+//! [cache]
+//! entries = 100_000
+//! max_age_secs = 300
+//!
+//! [lifecycle]
+//! shutdown_grace_secs = 30
+//! ```
+//!
+//! Credentials stay in the environment (`BROTHER_AUTH_TOKENS` etc., see
+//! `auth.rs`).
+
+use std::{net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
+
+use anyhow::{bail, Context};
+use serde::Deserialize;
+
+use crate::{idempotency, service};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// gRPC listen address.  Env: `BROTHER_LISTEN`.
+    pub listen: SocketAddr,
+    pub database: Database,
+    /// Unset = plaintext.
+    pub tls: Option<Tls>,
+    pub limits: Limits,
+    pub keepalive: Keepalive,
+    pub maintenance: Maintenance,
+    pub cache: Cache,
+    pub lifecycle: Lifecycle,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Database {
+    /// Required.  Env: `DATABASE_URL`.
+    pub url: Option<String>,
+    /// Env: `BROTHER_DB_MAX_CONNECTIONS`.
+    pub max_connections: u32,
+    /// Env: `BROTHER_DB_MIN_CONNECTIONS`.
+    pub min_connections: u32,
+    /// Wait for a free connection.  Env: `BROTHER_DB_ACQUIRE_TIMEOUT_SECS`.
+    pub acquire_timeout_secs: u64,
+    /// Close connections idle this long; 0 = never.
+    /// Env: `BROTHER_DB_IDLE_TIMEOUT_SECS`.
+    pub idle_timeout_secs: u64,
+    /// Shard stamped into new object ids, 0–31.  Env: `BROTHER_ID_SHARD`.
+    pub id_shard: u8,
+    /// Unset = every read goes to the primary.
+    pub read: Option<ReadPool>,
+}
+
+/// A second pool for reads that may trail the primary (see
+/// `Consistency` in the proto).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReadPool {
+    /// A replica; unset = `database.url`.  Env: `BROTHER_DB_READ_URL`.
+    pub url: Option<String>,
+    /// YugabyteDB follower reads (`yb_read_from_followers`).
+    /// Env: `BROTHER_DB_FOLLOWER_READS`.
+    pub follower_reads: bool,
+    /// How far behind the leader a follower read may be.
+    /// Env: `BROTHER_DB_READ_STALENESS_MS`.
+    pub staleness_ms: u64,
+    /// Env: `BROTHER_DB_READ_MAX_CONNECTIONS`.
+    pub max_connections: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Tls {
+    /// PEM certificate chain.  Env: `BROTHER_TLS_CERT`.
+    pub cert: PathBuf,
+    /// PEM private key.  Env: `BROTHER_TLS_KEY`.
+    pub key: PathBuf,
+    /// PEM roots client certificates must chain to; set = mTLS, with
+    /// bearer tokens still accepted.  Env: `BROTHER_TLS_CLIENT_CA`.
+    pub client_ca: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Largest request or response message.  Env: `BROTHER_MAX_MESSAGE_BYTES`.
+    pub max_message_bytes: usize,
+    /// In-flight requests per connection; 0 = unlimited.
+    /// Env: `BROTHER_CONCURRENCY_PER_CONNECTION`.
+    pub concurrency_per_connection: usize,
+    /// HTTP/2 streams per connection; 0 = hyper's default.
+    /// Env: `BROTHER_MAX_CONCURRENT_STREAMS`.
+    pub max_concurrent_streams: u32,
+    /// Per-request deadline; 0 = none.  Env: `BROTHER_REQUEST_TIMEOUT_SECS`.
+    pub request_timeout_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keepalive {
+    /// HTTP/2 PING interval; 0 = off.  Env: `BROTHER_HTTP2_KEEPALIVE_SECS`.
+    pub http2_interval_secs: u64,
+    /// Drop the connection if a PING is not answered within this.
+    /// Env: `BROTHER_HTTP2_KEEPALIVE_TIMEOUT_SECS`.
+    pub http2_timeout_secs: u64,
+    /// TCP keepalive; 0 = off.  Env: `BROTHER_TCP_KEEPALIVE_SECS`.
+    pub tcp_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Maintenance {
+    /// How long a deleted object stays restorable.
+    /// Env: `BROTHER_TOMBSTONE_RETENTION_SECS`.
+    pub tombstone_retention_secs: u64,
+    /// Env: `BROTHER_PURGE_INTERVAL_SECS`.
+    pub purge_interval_secs: u64,
+    /// Env: `BROTHER_REAP_INTERVAL_SECS`.
+    pub reap_interval_secs: u64,
+    /// How long an `idempotency-key` is remembered.
+    /// Env: `BROTHER_IDEMPOTENCY_WINDOW_SECS`.
+    pub idempotency_window_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Cache {
+    /// Entries across all tenants; 0 = no cache.  Env: `BROTHER_CACHE_ENTRIES`.
+    pub entries: usize,
+    /// Entries one tenant may hold; unset = `entries`.
+    /// Env: `BROTHER_CACHE_TENANT_ENTRIES`.
+    pub tenant_entries: Option<usize>,
+    /// Oldest entry served, which bounds how long a write made behind the
+    /// service's back stays hidden; 0 = no limit.
+    /// Env: `BROTHER_CACHE_MAX_AGE_SECS`.
+    pub max_age_secs: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Lifecycle {
+    /// How often the health service checks the database.
+    /// Env: `BROTHER_HEALTH_PROBE_SECS`.
+    pub health_probe_secs: u64,
+    /// On SIGTERM / Ctrl-C, how long in-flight calls may finish before the
+    /// server exits anyway.  Env: `BROTHER_SHUTDOWN_GRACE_SECS`.
+    pub shutdown_grace_secs: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            listen: SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], 42069)),
+            database: Database::default(),
+            tls: None,
+            limits: Limits::default(),
+            keepalive: Keepalive::default(),
+            maintenance: Maintenance::default(),
+            cache: Cache::default(),
+            lifecycle: Lifecycle::default(),
+        }
+    }
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self {
+            url: None,
+            max_connections: 8,
+            min_connections: 0,
+            acquire_timeout_secs: 5,
+            idle_timeout_secs: 600,
+            id_shard: 0,
+            read: None,
+        }
+    }
+}
+
+impl Default for ReadPool {
+    fn default() -> Self {
+        Self {
+            url: None,
+            follower_reads: false,
+            // YugabyteDB's own default.
+            staleness_ms: 30_000,
+            max_connections: 8,
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_message_bytes: 4 * 1024 * 1024,
+            concurrency_per_connection: 0,
+            max_concurrent_streams: 0,
+            request_timeout_secs: 0,
+        }
+    }
+}
+
+impl Default for Keepalive {
+    fn default() -> Self {
+        Self {
+            http2_interval_secs: 0,
+            http2_timeout_secs: 20,
+            tcp_secs: 0,
+        }
+    }
+}
+
+impl Default for Maintenance {
+    fn default() -> Self {
+        Self {
+            tombstone_retention_secs: service::DEFAULT_RETENTION.as_secs(),
+            purge_interval_secs: 3600,
+            reap_interval_secs: 60,
+            idempotency_window_secs: idempotency::DEFAULT_WINDOW.as_secs(),
+        }
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Self {
+        Self {
+            entries: 100_000,
+            tenant_entries: None,
+            max_age_secs: 300,
+        }
+    }
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            health_probe_secs: 5,
+            shutdown_grace_secs: 30,
+        }
+    }
+}
+
+impl Config {
+    /// File, then environment, then validation.
+    pub fn load() -> anyhow::Result<Self> {
+        let mut config = match std::env::var_os("BROTHER_CONFIG") {
+            Some(path) => Self::from_file(&PathBuf::from(path))?,
+            None => Self::default(),
+        };
+        config.apply_env()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn from_file(path: &PathBuf) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("reading config {}", path.display()))?;
+        toml::from_str(&raw).with_context(|| format!("parsing config {}", path.display()))
+    }
+
+    fn apply_env(&mut self) -> anyhow::Result<()> {
+        env("BROTHER_LISTEN", &mut self.listen)?;
+
+        let db = &mut self.database;
+        if let Ok(url) = std::env::var("DATABASE_URL") {
+            db.url = Some(url);
+        }
+        env("BROTHER_DB_MAX_CONNECTIONS", &mut db.max_connections)?;
+        env("BROTHER_DB_MIN_CONNECTIONS", &mut db.min_connections)?;
+        env("BROTHER_DB_ACQUIRE_TIMEOUT_SECS", &mut db.acquire_timeout_secs)?;
+        env("BROTHER_DB_IDLE_TIMEOUT_SECS", &mut db.idle_timeout_secs)?;
+        env("BROTHER_ID_SHARD", &mut db.id_shard)?;
+
+        // Either switches the read pool on.
+        if let Ok(url) = std::env::var("BROTHER_DB_READ_URL") {
+            db.read.get_or_insert_with(ReadPool::default).url = Some(url);
+        }
+        if std::env::var_os("BROTHER_DB_FOLLOWER_READS").is_some() {
+            let read = db.read.get_or_insert_with(ReadPool::default);
+            env("BROTHER_DB_FOLLOWER_READS", &mut read.follower_reads)?;
+        }
+        if let Some(read) = &mut db.read {
+            env("BROTHER_DB_READ_STALENESS_MS", &mut read.staleness_ms)?;
+            env("BROTHER_DB_READ_MAX_CONNECTIONS", &mut read.max_connections)?;
+        }
+
+        // The pair switches TLS on (or replaces the file's); the CA alone
+        // only makes sense on top.
+        if let (Ok(cert), Ok(key)) = (
+            std::env::var("BROTHER_TLS_CERT"),
+            std::env::var("BROTHER_TLS_KEY"),
+        ) {
+            let client_ca = self.tls.take().and_then(|t| t.client_ca);
+            self.tls = Some(Tls {
+                cert: cert.into(),
+                key: key.into(),
+                client_ca,
+            });
+        }
+        if let Ok(ca) = std::env::var("BROTHER_TLS_CLIENT_CA") {
+            let Some(tls) = &mut self.tls else {
+                bail!("BROTHER_TLS_CLIENT_CA needs a server certificate (tls.cert / BROTHER_TLS_CERT)");
+            };
+            tls.client_ca = Some(ca.into());
+        }
+
+        let l = &mut self.limits;
+        env("BROTHER_MAX_MESSAGE_BYTES", &mut l.max_message_bytes)?;
+        env("BROTHER_CONCURRENCY_PER_CONNECTION", &mut l.concurrency_per_connection)?;
+        env("BROTHER_MAX_CONCURRENT_STREAMS", &mut l.max_concurrent_streams)?;
+        env("BROTHER_REQUEST_TIMEOUT_SECS", &mut l.request_timeout_secs)?;
+
+        let k = &mut self.keepalive;
+        env("BROTHER_HTTP2_KEEPALIVE_SECS", &mut k.http2_interval_secs)?;
+        env("BROTHER_HTTP2_KEEPALIVE_TIMEOUT_SECS", &mut k.http2_timeout_secs)?;
+        env("BROTHER_TCP_KEEPALIVE_SECS", &mut k.tcp_secs)?;
+
+        let m = &mut self.maintenance;
+        env("BROTHER_TOMBSTONE_RETENTION_SECS", &mut m.tombstone_retention_secs)?;
+        env("BROTHER_PURGE_INTERVAL_SECS", &mut m.purge_interval_secs)?;
+        env("BROTHER_REAP_INTERVAL_SECS", &mut m.reap_interval_secs)?;
+        env("BROTHER_IDEMPOTENCY_WINDOW_SECS", &mut m.idempotency_window_secs)?;
+
+        env("BROTHER_CACHE_ENTRIES", &mut self.cache.entries)?;
+        if std::env::var_os("BROTHER_CACHE_TENANT_ENTRIES").is_some() {
+            let mut n = 0;
+            env("BROTHER_CACHE_TENANT_ENTRIES", &mut n)?;
+            self.cache.tenant_entries = Some(n);
+        }
+        env("BROTHER_CACHE_MAX_AGE_SECS", &mut self.cache.max_age_secs)?;
+
+        env("BROTHER_HEALTH_PROBE_SECS", &mut self.lifecycle.health_probe_secs)?;
+        env("BROTHER_SHUTDOWN_GRACE_SECS", &mut self.lifecycle.shutdown_grace_secs)?;
+        Ok(())
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let db = &self.database;
+        if db.url.as_deref().is_none_or(str::is_empty) {
+            bail!("database.url is required (or set DATABASE_URL)");
+        }
+        if db.max_connections == 0 {
+            bail!("database.max_connections must be at least 1");
+        }
+        if db.min_connections > db.max_connections {
+            bail!(
+                "database.min_connections ({}) exceeds database.max_connections ({})",
+                db.min_connections,
+                db.max_connections
+            );
+        }
+        if db.id_shard >= 32 {
+            bail!("database.id_shard must be 0..=31, got {}", db.id_shard);
+        }
+        if let Some(read) = &db.read {
+            if read.url.as_deref().is_none_or(str::is_empty) && !read.follower_reads {
+                bail!("database.read needs a url or follower_reads = true");
+            }
+            if read.max_connections == 0 {
+                bail!("database.read.max_connections must be at least 1");
+            }
+            if read.follower_reads && read.staleness_ms == 0 {
+                bail!("database.read.staleness_ms must be positive");
+            }
+        }
+
+        if let Some(tls) = &self.tls {
+            for (name, path) in [
+                ("tls.cert", Some(&tls.cert)),
+                ("tls.key", Some(&tls.key)),
+                ("tls.client_ca", tls.client_ca.as_ref()),
+            ] {
+                if let Some(path) = path {
+                    if !path.is_file() {
+                        bail!("{name}: {} is not a readable file", path.display());
+                    }
+                }
+            }
+        }
+
+        if self.limits.max_message_bytes == 0 {
+            bail!("limits.max_message_bytes must be positive");
+        }
+
+        let m = &self.maintenance;
+        for (name, secs) in [
+            ("maintenance.purge_interval_secs", m.purge_interval_secs),
+            ("maintenance.reap_interval_secs", m.reap_interval_secs),
+            ("maintenance.idempotency_window_secs", m.idempotency_window_secs),
+            ("lifecycle.health_probe_secs", self.lifecycle.health_probe_secs),
+        ] {
+            if secs == 0 {
+                bail!("{name} must be positive");
+            }
+        }
+
+        if let Some(n) = self.cache.tenant_entries {
+            if n == 0 {
+                bail!("cache.tenant_entries must be positive (unset means cache.entries)");
+            }
+            if n > self.cache.entries {
+                bail!(
+                    "cache.tenant_entries ({n}) exceeds cache.entries ({})",
+                    self.cache.entries
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Seconds → `Duration`, with 0 meaning "off".
+pub fn secs(s: u64) -> Option<Duration> {
+    (s > 0).then(|| Duration::from_secs(s))
+}
+
+/// Overwrite `field` from `var` if set.
+fn env<T: FromStr>(var: &str, field: &mut T) -> anyhow::Result<()>
+where
+    T::Err: std::fmt::Display,
+{
+    if let Ok(v) = std::env::var(var) {
+        *field = v
+            .parse()
+            .map_err(|e| anyhow::anyhow!("{var}: cannot use `{v}`: {e}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The defaults plus the one required setting.
+    fn valid() -> Config {
+        let mut config = Config::default();
+        config.database.url = Some("postgres://localhost/brother".to_owned());
+        config
+    }
+
+    #[test]
+    fn validate_names_the_bad_setting() {
+        type Break = fn(&mut Config);
+        let cases: [(&str, Break); 9] = [
+            ("database.url", |c| c.database.url = None),
+            ("database.id_shard", |c| c.database.id_shard = 32),
+            ("database.min_connections (9)", |c| c.database.min_connections = 9),
+            ("database.max_connections", |c| c.database.max_connections = 0),
+            ("database.read needs", |c| c.database.read = Some(ReadPool::default())),
+            ("tls.cert", |c| {
+                c.tls = Some(Tls {
+                    cert: "/nonexistent/server.pem".into(),
+                    key: "/nonexistent/server.key".into(),
+                    client_ca: None,
+                })
+            }),
+            ("maintenance.idempotency_window_secs", |c| c.maintenance.idempotency_window_secs = 0),
+            ("cache.tenant_entries must", |c| c.cache.tenant_entries = Some(0)),
+            ("cache.tenant_entries (200001)", |c| c.cache.tenant_entries = Some(200_001)),
+        ];
+        valid().validate().unwrap();
+        for (setting, break_it) in cases {
+            let mut config = valid();
+            break_it(&mut config);
+            let err = config.validate().unwrap_err().to_string();
+            assert!(err.starts_with(setting), "{setting}: {err}");
+        }
+    }
+
+    /// All environment cases in one test: the variables are process-wide.
+    #[test]
+    fn environment_overrides_then_validates() {
+        let load = |vars: &[(&str, &str)]| {
+            for (var, value) in vars {
+                std::env::set_var(var, value);
+            }
+            let mut config = valid();
+            let result = config.apply_env().and_then(|()| config.validate());
+            for (var, _) in vars {
+                std::env::remove_var(var);
+            }
+            result.map(|()| config).map_err(|e| e.to_string())
+        };
+        let fails = |vars: &[(&str, &str)], start: &str| {
+            let err = load(vars).unwrap_err();
+            assert!(err.starts_with(start), "{vars:?}: {err}");
+        };
+
+        let config = load(&[("BROTHER_ID_SHARD", "31"), ("BROTHER_CACHE_TENANT_ENTRIES", "10")]).unwrap();
+        assert_eq!((config.database.id_shard, config.cache.tenant_entries), (31, Some(10)));
+        let config = load(&[("BROTHER_DB_READ_URL", "postgres://replica/brother")]).unwrap();
+        assert!(config.database.read.is_some_and(|r| !r.follower_reads));
+
+        fails(&[("BROTHER_ID_SHARD", "32")], "database.id_shard");
+        fails(&[("BROTHER_ID_SHARD", "one")], "BROTHER_ID_SHARD");
+        fails(&[("BROTHER_DB_MIN_CONNECTIONS", "9")], "database.min_connections");
+        fails(&[("BROTHER_DB_FOLLOWER_READS", "false")], "database.read needs");
+        fails(&[("BROTHER_TLS_CLIENT_CA", "/etc/brother/clients.pem")], "BROTHER_TLS_CLIENT_CA");
+        fails(&[("BROTHER_CACHE_TENANT_ENTRIES", "200000")], "cache.tenant_entries");
+        fails(&[("BROTHER_CACHE_TENANT_ENTRIES", "0")], "cache.tenant_entries");
+        fails(&[("BROTHER_IDEMPOTENCY_WINDOW_SECS", "0")], "maintenance.idempotency_window_secs");
+    }
+}
@@ -0,0 +1,213 @@
+/*======================================================================
+  Unique attribute keys
+  ----------------------------------------------------------------------
+  • unique_keys declares "attribute <path> of object type <type> is
+    unique per tenant"; unique_values holds the claimed values
+  • The objects_unique trigger claims / releases values in the writing
+    statement, so every upsert path is covered atomically.  A value
+    held by another live object raises SQLSTATE 'TAOUQ' with DETAIL
+    {"path": [...], "id": <holder>}
+  • Missing and JSON-null values are not claimed; tombstoned objects
+    release theirs and re-claim on restore
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE TABLE IF NOT EXISTS unique_keys (
+    type        INT         NOT NULL,
+    path        TEXT[]      NOT NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT unique_keys_pk PRIMARY KEY (type, path)
+);
+
+CREATE TABLE IF NOT EXISTS unique_values (
+    tenant      BIGINT      NOT NULL,
+    type        INT         NOT NULL,
+    path        TEXT[]      NOT NULL,
+    value       JSONB       NOT NULL,
+    id          BIGINT      NOT NULL,
+
+    CONSTRAINT unique_values_pk PRIMARY KEY (tenant, type, path, value)
+);
+
+CREATE INDEX IF NOT EXISTS unique_values_owner_idx
+    ON unique_values (tenant, type, id);
+
+-----------------------------------------------------------------------
+-- 1. Claim / release trigger
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION trg_objects_unique()
+RETURNS trigger LANGUAGE plpgsql AS $$
+DECLARE
+    _path   TEXT[];
+    _value  JSONB;
+    _holder BIGINT;
+BEGIN
+    IF TG_OP = 'UPDATE'
+       AND NEW.attributes IS NOT DISTINCT FROM OLD.attributes
+       AND (NEW.deleted_at IS NULL) = (OLD.deleted_at IS NULL) THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        DELETE FROM unique_values
+         WHERE tenant = OLD.tenant AND type = OLD.type AND id = OLD.id;
+    END IF;
+
+    IF TG_OP = 'DELETE' OR NEW.deleted_at IS NOT NULL THEN
+        RETURN NULL;
+    END IF;
+
+    FOR _path IN SELECT k.path FROM unique_keys k WHERE k.type = NEW.type LOOP
+        _value := NEW.attributes #> _path;
+        CONTINUE WHEN _value IS NULL OR _value = 'null'::jsonb;
+
+        -- Waits for a concurrent claimant, then sees its row.
+        INSERT INTO unique_values (tenant, type, path, value, id)
+             VALUES (NEW.tenant, NEW.type, _path, _value, NEW.id)
+        ON CONFLICT ON CONSTRAINT unique_values_pk DO NOTHING;
+
+        IF NOT FOUND THEN
+            SELECT u.id INTO _holder
+              FROM unique_values u
+             WHERE u.tenant = NEW.tenant AND u.type = NEW.type
+               AND u.path = _path AND u.value = _value;
+
+            RAISE EXCEPTION
+              'unique key % already taken (tenant %, type %)',
+              array_to_string(_path, '.'), NEW.tenant, NEW.type
+              USING ERRCODE = 'TAOUQ',
+                    DETAIL  = jsonb_build_object('path', _path, 'id', _holder)::text;
+        END IF;
+    END LOOP;
+
+    RETURN NULL;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS objects_unique ON objects;
+CREATE TRIGGER objects_unique
+AFTER INSERT OR UPDATE OR DELETE ON objects
+FOR EACH ROW
+EXECUTE FUNCTION trg_objects_unique();
+
+/*--------------------------------------------------------------
+  tao_declare_unique
+  • Declares p_path of p_type unique and claims the values of the
+    existing live objects; TRUE if the key is new
+  • Fails with 'TAOUQ' if existing objects already share a value
+  • Holds off writers to objects while it backfills
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_declare_unique(
+    p_type INT,
+    p_path TEXT[]
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+DECLARE
+    _dup RECORD;
+BEGIN
+    LOCK TABLE objects IN SHARE MODE;
+
+    INSERT INTO unique_keys (type, path) VALUES (p_type, p_path)
+    ON CONFLICT ON CONSTRAINT unique_keys_pk DO NOTHING;
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    SELECT o.tenant, min(o.id) AS id
+      INTO _dup
+      FROM objects o
+     WHERE o.type = p_type
+       AND o.deleted_at IS NULL
+       AND o.attributes #> p_path IS NOT NULL
+       AND o.attributes #> p_path <> 'null'::jsonb
+     GROUP BY o.tenant, o.attributes #> p_path
+    HAVING count(*) > 1
+     LIMIT 1;
+
+    IF FOUND THEN
+        RAISE EXCEPTION
+          'existing objects of type % share a value of %',
+          p_type, array_to_string(p_path, '.')
+          USING ERRCODE = 'TAOUQ',
+                DETAIL  = jsonb_build_object('path', p_path, 'id', _dup.id)::text;
+    END IF;
+
+    INSERT INTO unique_values (tenant, type, path, value, id)
+         SELECT o.tenant, o.type, p_path, o.attributes #> p_path, o.id
+           FROM objects o
+          WHERE o.type = p_type
+            AND o.deleted_at IS NULL
+            AND o.attributes #> p_path IS NOT NULL
+            AND o.attributes #> p_path <> 'null'::jsonb;
+    RETURN TRUE;
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_drop_unique
+  • Forgets the key and its claimed values; TRUE if it existed
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_drop_unique(
+    p_type INT,
+    p_path TEXT[]
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+BEGIN
+    DELETE FROM unique_values WHERE type = p_type AND path = p_path;
+    DELETE FROM unique_keys   WHERE type = p_type AND path = p_path;
+    RETURN FOUND;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 2. Batch upsert: a taken key fails only its own item
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION tao_upsert_objects(
+    p_tenant    BIGINT,
+    p_types     INT[],
+    p_ids       BIGINT[],
+    p_exp_vers  INT[],
+    p_attrs     JSONB[]
+) RETURNS TABLE (
+    idx         INT,
+    id          BIGINT,
+    created     BOOLEAN,
+    version     INT,
+    err_state   TEXT,
+    err_message TEXT,
+    err_detail  TEXT
+) LANGUAGE plpgsql AS $$
+BEGIN
+    FOR i IN 1 .. COALESCE(array_length(p_types, 1), 0) LOOP
+        idx         := i;
+        err_state   := NULL;
+        err_message := NULL;
+        err_detail  := NULL;
+
+        BEGIN
+            SELECT u.id, u.created, u.version
+              INTO id, created, version
+              FROM tao_upsert_object(p_tenant, p_types[i], p_ids[i],
+                                     p_exp_vers[i], p_attrs[i]) AS u;
+        EXCEPTION
+            WHEN SQLSTATE 'TAOVC'
+              OR SQLSTATE 'TAODL'
+              OR SQLSTATE 'TAOUQ'
+              OR data_exception
+              OR integrity_constraint_violation
+              OR raise_exception THEN
+                id      := p_ids[i];
+                created := FALSE;
+                version := NULL;
+                GET STACKED DIAGNOSTICS
+                    err_state   = RETURNED_SQLSTATE,
+                    err_message = MESSAGE_TEXT,
+                    err_detail  = PG_EXCEPTION_DETAIL;
+        END;
+
+        RETURN NEXT;
+    END LOOP;
+END;
+$$;
+
+-- End of migration
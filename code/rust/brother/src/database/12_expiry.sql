@@ -0,0 +1,417 @@
+/*======================================================================
+  Expiry  –  expires_at on objects and associations
+  ----------------------------------------------------------------------
+  • Rows past expires_at read as absent; tao_reap_expired physically
+    removes them in bounded batches, logging an 'expire' event
+  • Until it is reaped, writing an expired row revives it like any
+    other update (the new expires_at replaces the old one)
+  • An expired edge stops counting at once; an expired object's unique
+    keys may be claimed by another object at once
+  • tao_upsert_object / tao_upsert_objects / tao_upsert_association take
+    the expiry as a trailing argument, NULL = never
+======================================================================*/
+
+SET search_path TO tao, public;
+
+-----------------------------------------------------------------------
+-- 1. Columns
+-----------------------------------------------------------------------
+ALTER TABLE objects      ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+ALTER TABLE associations ADD COLUMN IF NOT EXISTS expires_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS objects_expiry_idx
+    ON objects (expires_at) WHERE expires_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS associations_expiry_idx
+    ON associations (expires_at) WHERE expires_at IS NOT NULL;
+
+-- tao_count_associations subtracts expired edges not reaped yet
+CREATE INDEX IF NOT EXISTS associations_src_expiry_idx
+    ON associations (tenant, type, source_id, expires_at)
+ WHERE expires_at IS NOT NULL;
+
+-----------------------------------------------------------------------
+-- 2. Objects
+-----------------------------------------------------------------------
+DROP FUNCTION IF EXISTS tao_upsert_object(BIGINT, INT, BIGINT, INT, JSONB);
+
+CREATE OR REPLACE FUNCTION tao_upsert_object(
+    p_tenant     BIGINT,
+    p_type       INT,
+    p_id         BIGINT,     -- 0 / NULL ⇒ insert
+    p_exp_ver    INT,        -- expected version
+    p_attrs      JSONB,
+    p_expires_at TIMESTAMPTZ DEFAULT NULL
+) RETURNS TABLE (id BIGINT, created BOOLEAN, version INT)
+LANGUAGE plpgsql AS $$
+DECLARE
+    _created  BOOLEAN := FALSE;
+    _version  INT     := 0;
+    _deleted  TIMESTAMPTZ;
+    _inserted INT;
+BEGIN
+    /* ---------- INSERT path (no id supplied) ----------------------- */
+    IF p_id IS NULL OR p_id = 0 THEN
+        INSERT INTO objects (tenant, type, version, attributes, expires_at)
+             VALUES (p_tenant, p_type, 0, p_attrs, p_expires_at)
+          RETURNING objects.id INTO p_id;
+        _created := TRUE;
+
+    ELSE
+        /* ---------- create at explicit id if missing --------------- */
+        INSERT INTO objects (tenant, type, id, version, attributes, expires_at)
+             VALUES (p_tenant, p_type, p_id, 0, p_attrs, p_expires_at)
+        ON CONFLICT ON CONSTRAINT objects_pk DO NOTHING;   -- `id` is also an OUT column
+        GET DIAGNOSTICS _inserted = ROW_COUNT;
+        _created := _inserted = 1;
+
+        /* ---------- UPDATE path with optimistic check -------------- */
+        IF NOT _created THEN
+            UPDATE objects o
+               SET attributes = p_attrs,
+                   expires_at = p_expires_at,
+                   updated_at = now(),
+                   version    = o.version + 1
+             WHERE o.tenant  = p_tenant
+               AND o.type    = p_type
+               AND o.id      = p_id
+               AND o.version = p_exp_ver
+               AND o.deleted_at IS NULL
+         RETURNING o.version INTO _version;
+
+            IF NOT FOUND THEN
+                SELECT o.version, o.deleted_at INTO _version, _deleted
+                  FROM objects o
+                 WHERE o.tenant = p_tenant
+                   AND o.type   = p_type
+                   AND o.id     = p_id;
+
+                IF _deleted IS NOT NULL THEN
+                    RAISE EXCEPTION
+                      'tao_upsert_object: object is deleted (tenant %, type %, id %)',
+                      p_tenant, p_type, p_id
+                      USING ERRCODE = 'TAODL';
+                END IF;
+
+                RAISE EXCEPTION
+                  'tao_upsert_object: version clash (tenant %, type %, id %)',
+                  p_tenant, p_type, p_id
+                  USING ERRCODE = 'TAOVC',
+                        DETAIL  = _version::text;
+            END IF;
+        END IF;
+    END IF;
+
+    RETURN QUERY SELECT p_id, _created, _version;
+END;
+$$;
+
+DROP FUNCTION IF EXISTS tao_upsert_objects(BIGINT, INT[], BIGINT[], INT[], JSONB[]);
+
+CREATE OR REPLACE FUNCTION tao_upsert_objects(
+    p_tenant    BIGINT,
+    p_types     INT[],
+    p_ids       BIGINT[],
+    p_exp_vers  INT[],
+    p_attrs     JSONB[],
+    p_expires   TIMESTAMPTZ[] DEFAULT NULL
+) RETURNS TABLE (
+    idx         INT,
+    id          BIGINT,
+    created     BOOLEAN,
+    version     INT,
+    err_state   TEXT,
+    err_message TEXT,
+    err_detail  TEXT
+) LANGUAGE plpgsql AS $$
+BEGIN
+    FOR i IN 1 .. COALESCE(array_length(p_types, 1), 0) LOOP
+        idx         := i;
+        err_state   := NULL;
+        err_message := NULL;
+        err_detail  := NULL;
+
+        BEGIN
+            SELECT u.id, u.created, u.version
+              INTO id, created, version
+              FROM tao_upsert_object(p_tenant, p_types[i], p_ids[i],
+                                     p_exp_vers[i], p_attrs[i], p_expires[i]) AS u;
+        EXCEPTION
+            WHEN SQLSTATE 'TAOVC'
+              OR SQLSTATE 'TAODL'
+              OR SQLSTATE 'TAOUQ'
+              OR data_exception
+              OR integrity_constraint_violation
+              OR raise_exception THEN
+                id      := p_ids[i];
+                created := FALSE;
+                version := NULL;
+                GET STACKED DIAGNOSTICS
+                    err_state   = RETURNED_SQLSTATE,
+                    err_message = MESSAGE_TEXT,
+                    err_detail  = PG_EXCEPTION_DETAIL;
+        END;
+
+        RETURN NEXT;
+    END LOOP;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 3. Associations
+-----------------------------------------------------------------------
+DROP FUNCTION IF EXISTS tao_upsert_association(BIGINT, TEXT, BIGINT, BIGINT, BIGINT, BIGINT, JSONB);
+
+CREATE OR REPLACE FUNCTION tao_upsert_association(
+    p_tenant     BIGINT,
+    p_type       TEXT,
+    p_source     BIGINT,
+    p_target     BIGINT,
+    p_time       BIGINT,
+    p_position   BIGINT,
+    p_attrs      JSONB,
+    p_expires_at TIMESTAMPTZ DEFAULT NULL
+) RETURNS VOID LANGUAGE plpgsql AS $$
+DECLARE
+    _inserted INT;
+    _revived  BOOLEAN;
+BEGIN
+    INSERT INTO associations (tenant, type, source_id, target_id, time,
+                               position, attributes, expires_at)
+         VALUES (p_tenant, p_type, p_source, p_target,
+                 p_time,   p_position, p_attrs, p_expires_at)
+    ON CONFLICT (tenant, type, source_id, target_id) DO NOTHING;
+    GET DIAGNOSTICS _inserted = ROW_COUNT;
+
+    IF _inserted = 0 THEN
+        SELECT deleted_at IS NOT NULL INTO _revived
+          FROM associations
+         WHERE tenant    = p_tenant
+           AND type      = p_type
+           AND source_id = p_source
+           AND target_id = p_target
+           FOR UPDATE;
+
+        UPDATE associations
+           SET time       = p_time,
+               position   = p_position,
+               attributes = p_attrs,
+               expires_at = p_expires_at,
+               deleted_at = NULL
+         WHERE tenant    = p_tenant
+           AND type      = p_type
+           AND source_id = p_source
+           AND target_id = p_target;
+    END IF;
+
+    IF _inserted = 1 OR _revived THEN
+        INSERT INTO association_counts (tenant, type, source_id, count)
+             VALUES (p_tenant, p_type, p_source, 1)
+        ON CONFLICT (tenant, type, source_id) DO UPDATE
+                SET count = association_counts.count + 1;
+    END IF;
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_count_associations
+  • Live edges only: the stored count still includes expired
+    edges until the reaper removes them
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_count_associations(
+    p_tenant BIGINT,
+    p_type   TEXT,
+    p_src    BIGINT
+) RETURNS BIGINT LANGUAGE sql STABLE AS $$
+    SELECT COALESCE(
+        (SELECT count
+           FROM association_counts
+          WHERE tenant    = p_tenant
+            AND type      = p_type
+            AND source_id = p_src),
+        0)
+      - (SELECT count(*)
+           FROM associations
+          WHERE tenant     = p_tenant
+            AND type       = p_type
+            AND source_id  = p_src
+            AND expires_at <= now()
+            AND deleted_at IS NULL);
+$$;
+
+-----------------------------------------------------------------------
+-- 4. Unique keys: an expired holder gives its value up
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION trg_objects_unique()
+RETURNS trigger LANGUAGE plpgsql AS $$
+DECLARE
+    _path   TEXT[];
+    _value  JSONB;
+    _holder BIGINT;
+BEGIN
+    IF TG_OP = 'UPDATE'
+       AND NEW.attributes IS NOT DISTINCT FROM OLD.attributes
+       AND (NEW.deleted_at IS NULL) = (OLD.deleted_at IS NULL) THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        DELETE FROM unique_values
+         WHERE tenant = OLD.tenant AND type = OLD.type AND id = OLD.id;
+    END IF;
+
+    IF TG_OP = 'DELETE' OR NEW.deleted_at IS NOT NULL THEN
+        RETURN NULL;
+    END IF;
+
+    FOR _path IN SELECT k.path FROM unique_keys k WHERE k.type = NEW.type LOOP
+        _value := NEW.attributes #> _path;
+        CONTINUE WHEN _value IS NULL OR _value = 'null'::jsonb;
+
+        -- Waits for a concurrent claimant, then sees its row.
+        INSERT INTO unique_values (tenant, type, path, value, id)
+             VALUES (NEW.tenant, NEW.type, _path, _value, NEW.id)
+        ON CONFLICT ON CONSTRAINT unique_values_pk DO NOTHING;
+
+        IF NOT FOUND THEN
+            UPDATE unique_values u
+               SET id = NEW.id
+              FROM objects o
+             WHERE u.tenant = NEW.tenant AND u.type = NEW.type
+               AND u.path = _path AND u.value = _value
+               AND o.tenant = u.tenant AND o.type = u.type AND o.id = u.id
+               AND o.expires_at <= now();
+        END IF;
+
+        IF NOT FOUND THEN
+            SELECT u.id INTO _holder
+              FROM unique_values u
+             WHERE u.tenant = NEW.tenant AND u.type = NEW.type
+               AND u.path = _path AND u.value = _value;
+
+            RAISE EXCEPTION
+              'unique key % already taken (tenant %, type %)',
+              array_to_string(_path, '.'), NEW.tenant, NEW.type
+              USING ERRCODE = 'TAOUQ',
+                    DETAIL  = jsonb_build_object('path', _path, 'id', _holder)::text;
+        END IF;
+    END LOOP;
+
+    RETURN NULL;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 5. Changelog: removing a live expired row is an 'expire'
+-----------------------------------------------------------------------
+ALTER TABLE changelog DROP CONSTRAINT IF EXISTS changelog_op_check;
+ALTER TABLE changelog ADD CONSTRAINT changelog_op_check
+    CHECK (op IN ('put', 'delete', 'expire'));
+
+CREATE OR REPLACE FUNCTION trg_objects_changelog()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        IF OLD.deleted_at IS NULL THEN
+            INSERT INTO changelog (tenant, kind, op, otype, id, old_version)
+                 VALUES (OLD.tenant, 'object',
+                         CASE WHEN OLD.expires_at <= now() THEN 'expire' ELSE 'delete' END,
+                         OLD.type, OLD.id, OLD.version);
+        END IF;
+        RETURN OLD;
+    END IF;
+
+    IF TG_OP = 'UPDATE' AND NEW.deleted_at IS NOT NULL THEN
+        IF OLD.deleted_at IS NULL THEN
+            INSERT INTO changelog (tenant, kind, op, otype, id, old_version)
+                 VALUES (OLD.tenant, 'object', 'delete', OLD.type, OLD.id, OLD.version);
+        END IF;
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO changelog (tenant, kind, op, otype, id, old_version, new_version)
+         VALUES (NEW.tenant, 'object', 'put', NEW.type, NEW.id,
+                 CASE WHEN TG_OP = 'UPDATE' THEN OLD.version END,
+                 NEW.version);
+    RETURN NEW;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION trg_associations_changelog()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    IF (TG_OP = 'DELETE' AND OLD.deleted_at IS NULL)
+    OR (TG_OP = 'UPDATE' AND NEW.deleted_at IS NOT NULL AND OLD.deleted_at IS NULL) THEN
+        INSERT INTO changelog (tenant, kind, op, atype, id, target_id)
+             VALUES (OLD.tenant, 'association',
+                     CASE WHEN TG_OP = 'DELETE' AND OLD.expires_at <= now()
+                          THEN 'expire' ELSE 'delete' END,
+                     OLD.type, OLD.source_id, OLD.target_id);
+    ELSIF TG_OP <> 'DELETE' AND NEW.deleted_at IS NULL THEN
+        INSERT INTO changelog (tenant, kind, op, atype, id, target_id)
+             VALUES (NEW.tenant, 'association', 'put', NEW.type,
+                     NEW.source_id, NEW.target_id);
+    END IF;
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 6. Reaper
+-----------------------------------------------------------------------
+/*--------------------------------------------------------------
+  tao_reap_expired
+  • Removes up to p_limit expired objects and p_limit expired
+    associations, keeping association_counts in step
+  • Returns the number of rows removed; done once it is < p_limit
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_reap_expired(
+    p_limit INT
+) RETURNS BIGINT LANGUAGE plpgsql AS $$
+DECLARE
+    _objects BIGINT;
+    _assocs  BIGINT;
+BEGIN
+    DELETE FROM objects o
+     USING (SELECT tenant, type, id
+              FROM objects
+             WHERE expires_at <= now()
+             LIMIT p_limit) d
+     WHERE o.tenant = d.tenant
+       AND o.type   = d.type
+       AND o.id     = d.id;
+    GET DIAGNOSTICS _objects = ROW_COUNT;
+
+    WITH gone AS (
+        DELETE FROM associations a
+         USING (SELECT tenant, type, source_id, target_id
+                  FROM associations
+                 WHERE expires_at <= now()
+                 LIMIT p_limit) d
+         WHERE a.tenant    = d.tenant
+           AND a.type      = d.type
+           AND a.source_id = d.source_id
+           AND a.target_id = d.target_id
+     RETURNING a.tenant, a.type, a.source_id, a.deleted_at
+    ), counted AS (
+        UPDATE association_counts c
+           SET count = c.count - g.n
+          FROM (SELECT tenant, type, source_id, count(*) AS n
+                  FROM gone
+                 WHERE deleted_at IS NULL      -- tombstones were already uncounted
+                 GROUP BY tenant, type, source_id) g
+         WHERE c.tenant    = g.tenant
+           AND c.type      = g.type
+           AND c.source_id = g.source_id
+    )
+    SELECT count(*) INTO _assocs FROM gone;
+
+    RETURN _objects + _assocs;
+END;
+$$;
+
+-- End of migration
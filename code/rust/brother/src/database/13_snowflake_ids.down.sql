@@ -0,0 +1,88 @@
+/*======================================================================
+  Snowflake ids  –  revert
+  ----------------------------------------------------------------------
+  • tao_upsert_object goes back to the BIGSERIAL default for new ids
+  • Ids already handed out stay; they sit far above the serial range
+======================================================================*/
+
+SET search_path TO tao, public;
+
+/*--------------------------------------------------------------
+  tao_upsert_object – as of 12_expiry
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_upsert_object(
+    p_tenant     BIGINT,
+    p_type       INT,
+    p_id         BIGINT,     -- 0 / NULL ⇒ insert
+    p_exp_ver    INT,        -- expected version
+    p_attrs      JSONB,
+    p_expires_at TIMESTAMPTZ DEFAULT NULL
+) RETURNS TABLE (id BIGINT, created BOOLEAN, version INT)
+LANGUAGE plpgsql AS $$
+DECLARE
+    _created  BOOLEAN := FALSE;
+    _version  INT     := 0;
+    _deleted  TIMESTAMPTZ;
+    _inserted INT;
+BEGIN
+    /* ---------- INSERT path (no id supplied) ----------------------- */
+    IF p_id IS NULL OR p_id = 0 THEN
+        INSERT INTO objects (tenant, type, version, attributes, expires_at)
+             VALUES (p_tenant, p_type, 0, p_attrs, p_expires_at)
+          RETURNING objects.id INTO p_id;
+        _created := TRUE;
+
+    ELSE
+        /* ---------- create at explicit id if missing --------------- */
+        INSERT INTO objects (tenant, type, id, version, attributes, expires_at)
+             VALUES (p_tenant, p_type, p_id, 0, p_attrs, p_expires_at)
+        ON CONFLICT ON CONSTRAINT objects_pk DO NOTHING;   -- `id` is also an OUT column
+        GET DIAGNOSTICS _inserted = ROW_COUNT;
+        _created := _inserted = 1;
+
+        /* ---------- UPDATE path with optimistic check -------------- */
+        IF NOT _created THEN
+            UPDATE objects o
+               SET attributes = p_attrs,
+                   expires_at = p_expires_at,
+                   updated_at = now(),
+                   version    = o.version + 1
+             WHERE o.tenant  = p_tenant
+               AND o.type    = p_type
+               AND o.id      = p_id
+               AND o.version = p_exp_ver
+               AND o.deleted_at IS NULL
+         RETURNING o.version INTO _version;
+
+            IF NOT FOUND THEN
+                SELECT o.version, o.deleted_at INTO _version, _deleted
+                  FROM objects o
+                 WHERE o.tenant = p_tenant
+                   AND o.type   = p_type
+                   AND o.id     = p_id;
+
+                IF _deleted IS NOT NULL THEN
+                    RAISE EXCEPTION
+                      'tao_upsert_object: object is deleted (tenant %, type %, id %)',
+                      p_tenant, p_type, p_id
+                      USING ERRCODE = 'TAODL';
+                END IF;
+
+                RAISE EXCEPTION
+                  'tao_upsert_object: version clash (tenant %, type %, id %)',
+                  p_tenant, p_type, p_id
+                  USING ERRCODE = 'TAOVC',
+                        DETAIL  = _version::text;
+            END IF;
+        END IF;
+    END IF;
+
+    RETURN QUERY SELECT p_id, _created, _version;
+END;
+$$;
+
+DROP FUNCTION IF EXISTS tao_id_time(BIGINT);
+DROP FUNCTION IF EXISTS tao_next_id(INT);
+DROP SEQUENCE IF EXISTS object_id_seq;
+
+-- End of revert
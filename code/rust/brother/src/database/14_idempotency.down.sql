@@ -0,0 +1,12 @@
+/*======================================================================
+  Idempotency keys  –  revert
+  ----------------------------------------------------------------------
+  • Remembered keys are lost: a retry after this replays nothing
+======================================================================*/
+
+SET search_path TO tao, public;
+
+DROP FUNCTION IF EXISTS tao_purge_idempotency_keys(INTERVAL, INT);
+DROP TABLE IF EXISTS idempotency_keys;
+
+-- End of revert
@@ -0,0 +1,49 @@
+/*======================================================================
+  Idempotency keys  –  replay of mutating RPCs
+  ----------------------------------------------------------------------
+  • One row per (tenant, key): the RPC, its request and, once it has
+    succeeded, its response – both prost-encoded
+  • `response IS NULL` while the first call is still running; a failed
+    call removes its row so the key can be retried
+  • Rows older than the configured window are purged in batches
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    tenant      BIGINT      NOT NULL,
+    key         TEXT        NOT NULL,
+    method      TEXT        NOT NULL,
+    request     BYTEA       NOT NULL,
+    response    BYTEA,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT idempotency_keys_pk PRIMARY KEY (tenant, key)
+);
+
+CREATE INDEX IF NOT EXISTS idempotency_keys_created_idx
+    ON idempotency_keys (created_at);
+
+/*--------------------------------------------------------------
+  tao_purge_idempotency_keys
+  • Removes up to p_limit keys older than p_window
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_purge_idempotency_keys(
+    p_window INTERVAL,
+    p_limit  INT
+) RETURNS BIGINT LANGUAGE plpgsql AS $$
+DECLARE
+    _n BIGINT;
+BEGIN
+    DELETE FROM idempotency_keys k
+     USING (SELECT tenant, key
+              FROM idempotency_keys
+             WHERE created_at < now() - p_window
+             LIMIT p_limit) d
+     WHERE k.tenant = d.tenant
+       AND k.key    = d.key;
+    GET DIAGNOSTICS _n = ROW_COUNT;
+    RETURN _n;
+END;
+$$;
+
+-- End of migration
@@ -0,0 +1,71 @@
+/*======================================================================
+  Batch helpers, associations
+  ----------------------------------------------------------------------
+  • tao_upsert_associations: tao_upsert_association for each edge plus
+    its registered inverse, in one statement
+  • Each edge and its inverse run in their own sub-transaction, so a bad
+    row fails only that item; its SQLSTATE / message / detail are
+    returned instead of raised (as tao_upsert_objects does)
+  • Serialization failures (40001 & co.) are NOT caught – they still
+    abort the whole statement so the caller can retry it
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE OR REPLACE FUNCTION tao_upsert_associations(
+    p_tenant    BIGINT,
+    p_types     TEXT[],
+    p_sources   BIGINT[],
+    p_targets   BIGINT[],
+    p_times     BIGINT[],
+    p_positions BIGINT[],
+    p_attrs     JSONB[],
+    p_expires   BIGINT[]   -- epoch-ms, NULL = never
+) RETURNS TABLE (
+    idx         INT,       -- 1-based position in the input arrays
+    err_state   TEXT,      -- NULL on success
+    err_message TEXT,
+    err_detail  TEXT
+) LANGUAGE plpgsql AS $$
+DECLARE
+    _inverse TEXT;
+    _expires TIMESTAMPTZ;
+BEGIN
+    FOR i IN 1 .. COALESCE(array_length(p_types, 1), 0) LOOP
+        idx         := i;
+        err_state   := NULL;
+        err_message := NULL;
+        err_detail  := NULL;
+
+        BEGIN
+            _expires := to_timestamp(p_expires[i] / 1000.0);
+            PERFORM tao_upsert_association(p_tenant, p_types[i], p_sources[i],
+                                           p_targets[i], p_times[i], p_positions[i],
+                                           p_attrs[i], _expires);
+
+            -- The mirror of a symmetric self-edge is the edge itself.
+            SELECT inverse INTO _inverse
+              FROM association_inverses
+             WHERE type = p_types[i];
+            IF _inverse IS NOT NULL
+               AND (_inverse <> p_types[i] OR p_sources[i] <> p_targets[i]) THEN
+                PERFORM tao_upsert_association(p_tenant, _inverse, p_targets[i],
+                                               p_sources[i], p_times[i], p_positions[i],
+                                               p_attrs[i], _expires);
+            END IF;
+        EXCEPTION
+            WHEN data_exception
+              OR integrity_constraint_violation
+              OR raise_exception THEN
+                GET STACKED DIAGNOSTICS
+                    err_state   = RETURNED_SQLSTATE,
+                    err_message = MESSAGE_TEXT,
+                    err_detail  = PG_EXCEPTION_DETAIL;
+        END;
+
+        RETURN NEXT;
+    END LOOP;
+END;
+$$;
+
+-- End of migration
@@ -0,0 +1,24 @@
+/*======================================================================
+  Changelog lsns in commit order  –  revert
+  ----------------------------------------------------------------------
+  • lsns come from changelog_lsn_seq again, starting above every lsn
+    handed out so far, so resume points held by clients stay valid
+  • The key stays (tenant, lsn): lsns counted per tenant repeat across
+    tenants
+======================================================================*/
+
+SET search_path TO tao, public;
+
+DROP TRIGGER IF EXISTS changelog_lsn ON changelog;
+DROP FUNCTION IF EXISTS trg_changelog_lsn();
+
+CREATE SEQUENCE IF NOT EXISTS changelog_lsn_seq OWNED BY changelog.lsn;
+SELECT setval('changelog_lsn_seq',
+              GREATEST((SELECT max(lsn) FROM changelog),
+                       (SELECT max(lsn) FROM changelog_heads), 0) + 1,
+              false);
+ALTER TABLE changelog ALTER COLUMN lsn SET DEFAULT nextval('changelog_lsn_seq');
+
+DROP TABLE IF EXISTS changelog_heads;
+
+-- End of revert
@@ -0,0 +1,72 @@
+/*======================================================================
+  Object history by incarnation  –  revert
+  ----------------------------------------------------------------------
+  • History is keyed on (tenant, type, id, version) again.  Where an id
+    was created more than once, only the newest incarnation's versions
+    are kept; the older ones are deleted
+  • tao_object_versions and the history triggers are those of
+    8_object_versions
+======================================================================*/
+
+SET search_path TO tao, public;
+
+DELETE FROM object_versions h
+ WHERE EXISTS (SELECT 1 FROM object_versions n
+                WHERE n.tenant = h.tenant AND n.type = h.type AND n.id = h.id
+                  AND n.incarnation > h.incarnation)
+    OR EXISTS (SELECT 1 FROM objects o
+                WHERE o.tenant = h.tenant AND o.type = h.type AND o.id = h.id
+                  AND o.created_at > h.incarnation);
+
+ALTER TABLE object_versions DROP CONSTRAINT IF EXISTS object_versions_pk;
+ALTER TABLE object_versions ADD CONSTRAINT object_versions_pk
+    PRIMARY KEY (tenant, type, id, version);
+
+DROP FUNCTION IF EXISTS tao_object_versions(BIGINT, INT, BIGINT, BOOLEAN);
+ALTER TABLE object_versions DROP COLUMN IF EXISTS incarnation;
+
+CREATE OR REPLACE FUNCTION trg_objects_touch()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO object_versions (tenant, type, id, version, attributes,
+                                 valid_from, deleted_at)
+         VALUES (OLD.tenant, OLD.type, OLD.id, OLD.version, OLD.attributes,
+                 OLD.updated_at, OLD.deleted_at)
+    ON CONFLICT ON CONSTRAINT object_versions_pk DO NOTHING;
+
+    NEW.updated_at := now();
+    NEW.version    := OLD.version + 1;
+    RETURN NEW;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION trg_objects_archive()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO object_versions (tenant, type, id, version, attributes,
+                                 valid_from, deleted_at)
+         VALUES (OLD.tenant, OLD.type, OLD.id, OLD.version, OLD.attributes,
+                 OLD.updated_at, COALESCE(OLD.deleted_at, now()))
+    ON CONFLICT ON CONSTRAINT object_versions_pk DO NOTHING;
+    RETURN OLD;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION tao_object_versions(
+    p_tenant BIGINT,
+    p_type   INT,
+    p_id     BIGINT
+) RETURNS TABLE (version INT, attributes JSONB, valid_from TIMESTAMPTZ,
+                 deleted_at TIMESTAMPTZ)
+LANGUAGE sql STABLE AS $$
+    SELECT o.version, o.attributes, o.updated_at, o.deleted_at
+      FROM objects o
+     WHERE o.tenant = p_tenant AND o.type = p_type AND o.id = p_id
+    UNION ALL
+    SELECT h.version, h.attributes, h.valid_from, h.deleted_at
+      FROM object_versions h
+     WHERE h.tenant = p_tenant AND h.type = p_type AND h.id = p_id
+     ORDER BY 1 DESC;
+$$;
+
+-- End of revert
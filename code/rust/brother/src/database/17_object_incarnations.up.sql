@@ -0,0 +1,91 @@
+/*======================================================================
+  Object history by incarnation
+  ----------------------------------------------------------------------
+  • An id can be created again once its tombstone is purged (or the row
+    reaped), starting over at version 0.  History was keyed on
+    (tenant, type, id, version) with ON CONFLICT DO NOTHING, so the new
+    object's versions were dropped and as-of reads returned the old one
+  • object_versions.incarnation = the created_at of the row a version
+    belonged to; it joins the primary key and collisions now raise
+  • tao_object_versions returns the current incarnation (the live or
+    tombstoned row's, else the newest in history) unless p_all, which
+    point-in-time reads by time use
+  • Rows from before this migration that predate the live row, or whose
+    row is gone, become one incarnation at -infinity
+======================================================================*/
+
+SET search_path TO tao, public;
+
+ALTER TABLE object_versions ADD COLUMN IF NOT EXISTS incarnation TIMESTAMPTZ;
+
+UPDATE object_versions h
+   SET incarnation = o.created_at
+  FROM objects o
+ WHERE h.incarnation IS NULL
+   AND o.tenant = h.tenant AND o.type = h.type AND o.id = h.id
+   AND h.valid_from >= o.created_at;
+
+UPDATE object_versions
+   SET incarnation = '-infinity'
+ WHERE incarnation IS NULL;
+
+ALTER TABLE object_versions ALTER COLUMN incarnation SET NOT NULL;
+ALTER TABLE object_versions DROP CONSTRAINT IF EXISTS object_versions_pk;
+ALTER TABLE object_versions ADD CONSTRAINT object_versions_pk
+    PRIMARY KEY (tenant, type, id, incarnation, version);
+
+CREATE OR REPLACE FUNCTION trg_objects_touch()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO object_versions (tenant, type, id, incarnation, version,
+                                 attributes, valid_from, deleted_at)
+         VALUES (OLD.tenant, OLD.type, OLD.id, OLD.created_at, OLD.version,
+                 OLD.attributes, OLD.updated_at, OLD.deleted_at);
+
+    NEW.updated_at := now();
+    NEW.version    := OLD.version + 1;
+    RETURN NEW;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION trg_objects_archive()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO object_versions (tenant, type, id, incarnation, version,
+                                 attributes, valid_from, deleted_at)
+         VALUES (OLD.tenant, OLD.type, OLD.id, OLD.created_at, OLD.version,
+                 OLD.attributes, OLD.updated_at, COALESCE(OLD.deleted_at, now()));
+    RETURN OLD;
+END;
+$$;
+
+DROP FUNCTION IF EXISTS tao_object_versions(BIGINT, INT, BIGINT);
+
+CREATE OR REPLACE FUNCTION tao_object_versions(
+    p_tenant BIGINT,
+    p_type   INT,
+    p_id     BIGINT,
+    p_all    BOOLEAN DEFAULT FALSE
+) RETURNS TABLE (version INT, attributes JSONB, valid_from TIMESTAMPTZ,
+                 deleted_at TIMESTAMPTZ, incarnation TIMESTAMPTZ)
+LANGUAGE sql STABLE AS $$
+    WITH current AS (
+        SELECT COALESCE(
+                   (SELECT o.created_at FROM objects o
+                     WHERE o.tenant = p_tenant AND o.type = p_type AND o.id = p_id),
+                   (SELECT max(h.incarnation) FROM object_versions h
+                     WHERE h.tenant = p_tenant AND h.type = p_type AND h.id = p_id)
+               ) AS incarnation
+    )
+    SELECT o.version, o.attributes, o.updated_at, o.deleted_at, o.created_at
+      FROM objects o
+     WHERE o.tenant = p_tenant AND o.type = p_type AND o.id = p_id
+    UNION ALL
+    SELECT h.version, h.attributes, h.valid_from, h.deleted_at, h.incarnation
+      FROM object_versions h, current c
+     WHERE h.tenant = p_tenant AND h.type = p_type AND h.id = p_id
+       AND (p_all OR h.incarnation = c.incarnation)
+     ORDER BY 5 DESC, 1 DESC;
+$$;
+
+-- End of migration
@@ -0,0 +1,184 @@
+/*======================================================================
+  Unique keys per tenant
+  ----------------------------------------------------------------------
+  • unique_keys gains tenant: a key is declared by and for one tenant,
+    per (tenant, object type, attribute path)
+  • Keys declared before this migration apply to every tenant that has
+    objects of the type; a tenant without any declares its own
+  • tao_declare_unique no longer takes LOCK TABLE, which YugabyteDB
+    rejects.  It locks the tenant's changelog_heads row instead: every
+    object write bumps that row (objects_changelog fires before
+    objects_unique), so writers of the tenant wait for the declaration,
+    then see the key, and the backfill sees every write committed
+    before it.  Expired holders are not claimed, as in the trigger
+======================================================================*/
+
+SET search_path TO tao, public;
+
+ALTER TABLE unique_keys ADD COLUMN IF NOT EXISTS tenant BIGINT;
+ALTER TABLE unique_keys DROP CONSTRAINT IF EXISTS unique_keys_pk;
+
+INSERT INTO unique_keys (tenant, type, path, created_at)
+SELECT DISTINCT o.tenant, k.type, k.path, k.created_at
+  FROM unique_keys k
+  JOIN objects o ON o.type = k.type
+ WHERE k.tenant IS NULL;
+
+DELETE FROM unique_keys WHERE tenant IS NULL;
+
+ALTER TABLE unique_keys ALTER COLUMN tenant SET NOT NULL;
+ALTER TABLE unique_keys ADD CONSTRAINT unique_keys_pk
+    PRIMARY KEY (tenant, type, path);
+
+DELETE FROM unique_values u
+ WHERE NOT EXISTS (SELECT 1 FROM unique_keys k
+                    WHERE k.tenant = u.tenant AND k.type = u.type
+                      AND k.path = u.path);
+
+-----------------------------------------------------------------------
+-- 1. Claim / release trigger: the writer's tenant's keys only
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION trg_objects_unique()
+RETURNS trigger LANGUAGE plpgsql AS $$
+DECLARE
+    _path   TEXT[];
+    _value  JSONB;
+    _holder BIGINT;
+BEGIN
+    IF TG_OP = 'UPDATE'
+       AND NEW.attributes IS NOT DISTINCT FROM OLD.attributes
+       AND (NEW.deleted_at IS NULL) = (OLD.deleted_at IS NULL) THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        DELETE FROM unique_values
+         WHERE tenant = OLD.tenant AND type = OLD.type AND id = OLD.id;
+    END IF;
+
+    IF TG_OP = 'DELETE' OR NEW.deleted_at IS NOT NULL THEN
+        RETURN NULL;
+    END IF;
+
+    FOR _path IN SELECT k.path FROM unique_keys k
+                  WHERE k.tenant = NEW.tenant AND k.type = NEW.type LOOP
+        _value := NEW.attributes #> _path;
+        CONTINUE WHEN _value IS NULL OR _value = 'null'::jsonb;
+
+        -- Waits for a concurrent claimant, then sees its row.
+        INSERT INTO unique_values (tenant, type, path, value, id)
+             VALUES (NEW.tenant, NEW.type, _path, _value, NEW.id)
+        ON CONFLICT ON CONSTRAINT unique_values_pk DO NOTHING;
+
+        IF NOT FOUND THEN
+            UPDATE unique_values u
+               SET id = NEW.id
+              FROM objects o
+             WHERE u.tenant = NEW.tenant AND u.type = NEW.type
+               AND u.path = _path AND u.value = _value
+               AND o.tenant = u.tenant AND o.type = u.type AND o.id = u.id
+               AND o.expires_at <= now();
+        END IF;
+
+        IF NOT FOUND THEN
+            SELECT u.id INTO _holder
+              FROM unique_values u
+             WHERE u.tenant = NEW.tenant AND u.type = NEW.type
+               AND u.path = _path AND u.value = _value;
+
+            RAISE EXCEPTION
+              'unique key % already taken (tenant %, type %)',
+              array_to_string(_path, '.'), NEW.tenant, NEW.type
+              USING ERRCODE = 'TAOUQ',
+                    DETAIL  = jsonb_build_object('path', _path, 'id', _holder)::text;
+        END IF;
+    END LOOP;
+
+    RETURN NULL;
+END;
+$$;
+
+DROP FUNCTION IF EXISTS tao_declare_unique(INT, TEXT[]);
+DROP FUNCTION IF EXISTS tao_drop_unique(INT, TEXT[]);
+
+/*--------------------------------------------------------------
+  tao_declare_unique
+  • Declares p_path of p_type unique within p_tenant and claims the
+    values of the tenant's live objects; TRUE if the key is new
+  • Fails with 'TAOUQ' if existing objects already share a value
+  • Holds off the tenant's writers (changelog_heads row lock) while it
+    backfills; must run at READ COMMITTED so the backfill reads past
+    writers that committed while it waited
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_declare_unique(
+    p_tenant BIGINT,
+    p_type   INT,
+    p_path   TEXT[]
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+DECLARE
+    _dup RECORD;
+BEGIN
+    INSERT INTO changelog_heads AS h (tenant, lsn)
+         VALUES (p_tenant, 0)
+    ON CONFLICT (tenant) DO UPDATE
+            SET lsn = h.lsn;
+
+    INSERT INTO unique_keys (tenant, type, path) VALUES (p_tenant, p_type, p_path)
+    ON CONFLICT ON CONSTRAINT unique_keys_pk DO NOTHING;
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    SELECT min(o.id) AS id
+      INTO _dup
+      FROM objects o
+     WHERE o.tenant = p_tenant
+       AND o.type = p_type
+       AND o.deleted_at IS NULL
+       AND (o.expires_at IS NULL OR o.expires_at > now())
+       AND o.attributes #> p_path IS NOT NULL
+       AND o.attributes #> p_path <> 'null'::jsonb
+     GROUP BY o.attributes #> p_path
+    HAVING count(*) > 1
+     LIMIT 1;
+
+    IF FOUND THEN
+        RAISE EXCEPTION
+          'existing objects of type % share a value of %',
+          p_type, array_to_string(p_path, '.')
+          USING ERRCODE = 'TAOUQ',
+                DETAIL  = jsonb_build_object('path', p_path, 'id', _dup.id)::text;
+    END IF;
+
+    INSERT INTO unique_values (tenant, type, path, value, id)
+         SELECT o.tenant, o.type, p_path, o.attributes #> p_path, o.id
+           FROM objects o
+          WHERE o.tenant = p_tenant
+            AND o.type = p_type
+            AND o.deleted_at IS NULL
+            AND (o.expires_at IS NULL OR o.expires_at > now())
+            AND o.attributes #> p_path IS NOT NULL
+            AND o.attributes #> p_path <> 'null'::jsonb;
+    RETURN TRUE;
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_drop_unique
+  • Forgets the tenant's key and its claimed values; TRUE if it existed
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_drop_unique(
+    p_tenant BIGINT,
+    p_type   INT,
+    p_path   TEXT[]
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+BEGIN
+    DELETE FROM unique_values
+     WHERE tenant = p_tenant AND type = p_type AND path = p_path;
+    DELETE FROM unique_keys
+     WHERE tenant = p_tenant AND type = p_type AND path = p_path;
+    RETURN FOUND;
+END;
+$$;
+
+-- End of migration
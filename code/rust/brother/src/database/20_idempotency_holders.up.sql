@@ -0,0 +1,24 @@
+/*======================================================================
+  Idempotency claims that never let a write run twice
+  ----------------------------------------------------------------------
+  • The response is stored after the write commits, so a call that died
+    in between released its key (or had it taken over once abandoned)
+    and the retry wrote again.  A slow first call was taken over while
+    still running
+  • holder: the claiming call's random token.  Takeover replaces it, and
+    a call acts on its row only while the token is still its own
+  • writing: set by the holder just before its write.  From then on the
+    key is never released or taken over; without a stored response a
+    retry learns the outcome is unknown instead of running again
+======================================================================*/
+
+SET search_path TO tao, public;
+
+ALTER TABLE idempotency_keys
+    ADD COLUMN IF NOT EXISTS holder  BIGINT  NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS writing BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Claims from before this migration may have written.
+UPDATE idempotency_keys SET writing = TRUE WHERE response IS NULL;
+
+-- End of migration
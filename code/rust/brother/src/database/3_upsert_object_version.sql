@@ -0,0 +1,76 @@
+/*======================================================================
+  tao_upsert_object  –  report the new version, typed version clashes
+  ----------------------------------------------------------------------
+  • Returns (id, created, version) so callers can chain updates
+  • A version clash raises SQLSTATE 'TAOVC' (not 40001, which is kept
+    for genuine serialization failures) with the *current* stored
+    version in DETAIL
+  • An explicit id that does not exist yet is created at version 0,
+    exactly like the auto-id path
+======================================================================*/
+
+SET search_path TO tao, public;
+
+DROP FUNCTION IF EXISTS tao_upsert_object(BIGINT, INT, BIGINT, INT, JSONB);
+
+CREATE OR REPLACE FUNCTION tao_upsert_object(
+    p_tenant   BIGINT,
+    p_type     INT,
+    p_id       BIGINT,     -- 0 / NULL ⇒ insert
+    p_exp_ver  INT,        -- expected version
+    p_attrs    JSONB
+) RETURNS TABLE (id BIGINT, created BOOLEAN, version INT)
+LANGUAGE plpgsql AS $$
+DECLARE
+    _created  BOOLEAN := FALSE;
+    _version  INT     := 0;
+    _inserted INT;
+BEGIN
+    /* ---------- INSERT path (no id supplied) ----------------------- */
+    IF p_id IS NULL OR p_id = 0 THEN
+        INSERT INTO objects (tenant, type, version, attributes)
+             VALUES (p_tenant, p_type, 0, p_attrs)
+          RETURNING objects.id INTO p_id;
+        _created := TRUE;
+
+    ELSE
+        /* ---------- create at explicit id if missing --------------- */
+        INSERT INTO objects (tenant, type, id, version, attributes)
+             VALUES (p_tenant, p_type, p_id, 0, p_attrs)
+        ON CONFLICT ON CONSTRAINT objects_pk DO NOTHING;   -- `id` is also an OUT column
+        GET DIAGNOSTICS _inserted = ROW_COUNT;
+        _created := _inserted = 1;
+
+        /* ---------- UPDATE path with optimistic check -------------- */
+        IF NOT _created THEN
+            UPDATE objects o
+               SET attributes = p_attrs,
+                   updated_at = now(),
+                   version    = o.version + 1
+             WHERE o.tenant  = p_tenant
+               AND o.type    = p_type
+               AND o.id      = p_id
+               AND o.version = p_exp_ver
+         RETURNING o.version INTO _version;
+
+            IF NOT FOUND THEN
+                SELECT o.version INTO _version
+                  FROM objects o
+                 WHERE o.tenant = p_tenant
+                   AND o.type   = p_type
+                   AND o.id     = p_id;
+
+                RAISE EXCEPTION
+                  'tao_upsert_object: version clash (tenant %, type %, id %)',
+                  p_tenant, p_type, p_id
+                  USING ERRCODE = 'TAOVC',
+                        DETAIL  = _version::text;
+            END IF;
+        END IF;
+    END IF;
+
+    RETURN QUERY SELECT p_id, _created, _version;
+END;
+$$;
+
+-- End of migration
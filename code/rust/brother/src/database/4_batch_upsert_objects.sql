@@ -0,0 +1,60 @@
+/*======================================================================
+  Batch helpers
+  ----------------------------------------------------------------------
+  • tao_upsert_objects: many tao_upsert_object calls in one statement
+  • Each item runs in its own sub-transaction, so a version clash or a
+    bad row fails only that item; its SQLSTATE / message / detail are
+    returned instead of raised
+  • Serialization failures (40001 & co.) are NOT caught – they still
+    abort the whole statement so the caller can retry it
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE OR REPLACE FUNCTION tao_upsert_objects(
+    p_tenant    BIGINT,
+    p_types     INT[],
+    p_ids       BIGINT[],
+    p_exp_vers  INT[],
+    p_attrs     JSONB[]
+) RETURNS TABLE (
+    idx         INT,       -- 1-based position in the input arrays
+    id          BIGINT,
+    created     BOOLEAN,
+    version     INT,
+    err_state   TEXT,      -- NULL on success
+    err_message TEXT,
+    err_detail  TEXT
+) LANGUAGE plpgsql AS $$
+BEGIN
+    FOR i IN 1 .. COALESCE(array_length(p_types, 1), 0) LOOP
+        idx         := i;
+        err_state   := NULL;
+        err_message := NULL;
+        err_detail  := NULL;
+
+        BEGIN
+            SELECT u.id, u.created, u.version
+              INTO id, created, version
+              FROM tao_upsert_object(p_tenant, p_types[i], p_ids[i],
+                                     p_exp_vers[i], p_attrs[i]) AS u;
+        EXCEPTION
+            WHEN SQLSTATE 'TAOVC'
+              OR data_exception
+              OR integrity_constraint_violation
+              OR raise_exception THEN
+                id      := p_ids[i];
+                created := FALSE;
+                version := NULL;
+                GET STACKED DIAGNOSTICS
+                    err_state   = RETURNED_SQLSTATE,
+                    err_message = MESSAGE_TEXT,
+                    err_detail  = PG_EXCEPTION_DETAIL;
+        END;
+
+        RETURN NEXT;
+    END LOOP;
+END;
+$$;
+
+-- End of migration
@@ -0,0 +1,89 @@
+/*======================================================================
+  Object version history
+  ----------------------------------------------------------------------
+  • Append-only: the touch trigger copies the *previous* row into
+    object_versions on every UPDATE, so (history ∪ objects) holds every
+    version an object ever had
+  • valid_from = the updated_at of that version, i.e. when it became
+    current; point-in-time reads pick the newest version with
+    valid_from <= t
+  • Tombstoned versions keep their deleted_at so as-of reads can tell
+    "deleted at that moment" from "never existed"
+  • History outlives the tombstone purge – the purged row itself is
+    archived on DELETE – it is the audit trail
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE TABLE IF NOT EXISTS object_versions (
+    tenant      BIGINT      NOT NULL,
+    type        INT         NOT NULL,
+    id          BIGINT      NOT NULL,
+    version     INT         NOT NULL,
+    attributes  JSONB       NOT NULL,
+    valid_from  TIMESTAMPTZ NOT NULL,
+    deleted_at  TIMESTAMPTZ,
+
+    CONSTRAINT object_versions_pk PRIMARY KEY (tenant, type, id, version)
+);
+
+-- Touch trigger: bump updated_at + version, keep the old version
+CREATE OR REPLACE FUNCTION trg_objects_touch()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO object_versions (tenant, type, id, version, attributes,
+                                 valid_from, deleted_at)
+         VALUES (OLD.tenant, OLD.type, OLD.id, OLD.version, OLD.attributes,
+                 OLD.updated_at, OLD.deleted_at)
+    ON CONFLICT ON CONSTRAINT object_versions_pk DO NOTHING;
+
+    NEW.updated_at := now();
+    NEW.version    := OLD.version + 1;
+    RETURN NEW;
+END;
+$$;
+
+-- Archive trigger: a physically deleted (purged) row joins the history
+CREATE OR REPLACE FUNCTION trg_objects_archive()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO object_versions (tenant, type, id, version, attributes,
+                                 valid_from, deleted_at)
+         VALUES (OLD.tenant, OLD.type, OLD.id, OLD.version, OLD.attributes,
+                 OLD.updated_at, COALESCE(OLD.deleted_at, now()))
+    ON CONFLICT ON CONSTRAINT object_versions_pk DO NOTHING;
+    RETURN OLD;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS objects_archive ON objects;
+CREATE TRIGGER objects_archive
+AFTER DELETE ON objects
+FOR EACH ROW
+EXECUTE FUNCTION trg_objects_archive();
+
+/*--------------------------------------------------------------
+  tao_object_versions
+  • Every known version of one object, newest first, history and
+    the live row alike
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_object_versions(
+    p_tenant BIGINT,
+    p_type   INT,
+    p_id     BIGINT
+) RETURNS TABLE (version INT, attributes JSONB, valid_from TIMESTAMPTZ,
+                 deleted_at TIMESTAMPTZ)
+LANGUAGE sql STABLE AS $$
+    SELECT o.version, o.attributes, o.updated_at, o.deleted_at
+      FROM objects o
+     WHERE o.tenant = p_tenant AND o.type = p_type AND o.id = p_id
+    UNION ALL
+    SELECT h.version, h.attributes, h.valid_from, h.deleted_at
+      FROM object_versions h
+     WHERE h.tenant = p_tenant AND h.type = p_type AND h.id = p_id
+     ORDER BY 1 DESC;
+$$;
+
+-- GRANT SELECT ON object_versions TO brother_ro;
+
+-- End of migration
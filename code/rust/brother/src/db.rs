@@ -1,61 +1,468 @@
 //! src/db.rs
 //! Simple Postgres helper for the Brother service.
 
-use std::time::Duration;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
 
 use sqlx::{
-    migrate::Migrator,
-    postgres::PgPoolOptions,
-    Pool, Postgres
+    postgres::{PgDatabaseError, PgPoolOptions},
+    Executor, Pool, Postgres
 };
-use tonic::Status;
-use tracing::info;
+use rand::Rng;
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+use tracing::{debug, info};
+
+use crate::{config, deadline, migrate};
 
 /// Alias that the rest of the code uses.
 pub type PgPool = Pool<Postgres>;
 
-/// Compile-time embedded migration directory.
-///
-/// `sqlx::migrate!` expands to a `static MIGRATOR` that embeds every
-/// `*.sql` file under the given path and applies them in lexicographic
-/// order.
-///
-/// The path is **relative to the crate root** *at compile time* – adjust
-/// if you move the `database/` folder elsewhere.
-static MIGRATOR: Migrator = sqlx::migrate!("./src/database");
+/// The primary, and the pool bounded-stale reads go to.
+pub struct Pools {
+    pub primary: PgPool,
+    /// Set by `[database.read]`.
+    pub read: Option<PgPool>,
+}
 
-/// Build a pool, apply migrations, and hand it back.
+/// Build the pools, bring the schema up to date, and hand them back.
 ///
-/// * Reads `DATABASE_URL` from the environment.  
-/// * Uses a small, sensible pool size for dev – tune in prod.  
+/// * Sized and timed by `[database]` in the config (see `config.rs`).  
 /// * Runs the embedded migrations **exactly once**, even in concurrent
-///   start-ups.
-pub async fn init_pool() -> Result<PgPool, sqlx::Error> {
-    let url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL env-var must be set");
+///   start-ups – or, without `migrate`, only checks that nothing is
+///   pending (see `migrate.rs`).  
+/// * Opens the read pool, if configured, only once they are in.
+pub async fn init_pool(cfg: &config::Database, migrate: bool) -> anyhow::Result<Pools> {
+    let pool = connect(cfg).await?;
+
+    if migrate {
+        migrate::up(&pool, None).await?;
+        info!("✅ database ready – migrations are up-to-date");
+    } else {
+        migrate::check(&pool).await?;
+        info!("✅ database ready – migrations checked, none applied");
+    }
+
+    let read = match &cfg.read {
+        Some(read) => Some(init_read_pool(cfg, read).await?),
+        None => None,
+    };
+    Ok(Pools { primary: pool, read })
+}
+
+/// The primary pool alone, schema untouched.
+///
+/// Stamps new object ids with `id_shard`; give each database cluster
+/// that may merge data its own.
+pub async fn connect(cfg: &config::Database) -> Result<PgPool, sqlx::Error> {
+    let url = cfg.url.as_deref().unwrap_or_default();   // checked by `Config::load`
+    let shard = cfg.id_shard;
 
     let pool = PgPoolOptions::new()
-        .max_connections(8)
-        .acquire_timeout(Duration::from_secs(5))
-        .connect(&url)
+        .max_connections(cfg.max_connections)
+        .min_connections(cfg.min_connections)
+        .acquire_timeout(Duration::from_secs(cfg.acquire_timeout_secs))
+        .idle_timeout(config::secs(cfg.idle_timeout_secs))
+        // The `tao_*` functions use unqualified table names.  Migrations
+        // run on a connection of their own, without this (see migrate.rs).
+        .after_connect(move |conn, _meta| Box::pin(async move {
+            conn.execute("SET search_path TO tao, public").await?;
+            conn.execute(format!("SET tao.id_shard = {shard}").as_str()).await?;
+            Ok(())
+        }))
+        .connect(url)
         .await?;
+    Ok(pool)
+}
 
-    // Apply migrations (idempotent).
-    MIGRATOR.run(&pool).await?;
-    info!("✅ database ready – migrations are up-to-date");
+/// Read-only sessions on a replica, or on the primary's cluster with
+/// YugabyteDB follower reads.
+async fn init_read_pool(
+    cfg: &config::Database,
+    read: &config::ReadPool,
+) -> Result<PgPool, sqlx::Error> {
+    let url = read.url.as_deref().or(cfg.url.as_deref()).unwrap_or_default();
+    let follower_staleness = read.follower_reads.then_some(read.staleness_ms);
 
+    let pool = PgPoolOptions::new()
+        .max_connections(read.max_connections)
+        .acquire_timeout(Duration::from_secs(cfg.acquire_timeout_secs))
+        .idle_timeout(config::secs(cfg.idle_timeout_secs))
+        .after_connect(move |conn, _meta| Box::pin(async move {
+            conn.execute("SET search_path TO tao, public").await?;
+            // Follower reads only apply to read-only transactions.
+            conn.execute("SET default_transaction_read_only = on").await?;
+            if let Some(ms) = follower_staleness {
+                conn.execute("SET yb_read_from_followers = on").await?;
+                conn.execute(format!("SET yb_follower_read_staleness_ms = {ms}").as_str())
+                    .await?;
+            }
+            Ok(())
+        }))
+        .connect(url)
+        .await?;
+
+    match follower_staleness {
+        Some(ms) => info!("read pool ready – follower reads, at most {ms} ms stale"),
+        None => info!("read pool ready – replica"),
+    }
     Ok(pool)
 }
 
+/// SQLSTATE raised by `tao_upsert_object` when the expected version does
+/// not match; DETAIL carries the current stored version.
+pub const VERSION_CLASH: &str = "TAOVC";
+
+/// SQLSTATE raised when writing to a tombstoned object.
+pub const DELETED: &str = "TAODL";
+
+/// SQLSTATE raised when a unique attribute key is held by another object;
+/// DETAIL is `{"path": [...], "id": <holder>}`.
+pub const UNIQUE_VIOLATION: &str = "TAOUQ";
+
 /// Map a database error to a tonic `Status`, so handlers can simply
 /// `...? .await .map_err(db_err)?`.
+///
+/// Version clashes become `ABORTED` with an `ErrorInfo` detail
+/// (`reason = "VERSION_CLASH"`, `metadata.current_version`) so clients can
+/// re-read and retry.  Taken unique keys become `ALREADY_EXISTS`, see
+/// [`unique_violation`].
 pub fn db_err(e: sqlx::Error) -> Status {
     match e {
         sqlx::Error::RowNotFound => Status::not_found("record not found"),
-        other => {
-            tracing::error!("database error: {:?}", other);
-            Status::internal("database error")
+        sqlx::Error::Database(ref d) if d.code().as_deref() == Some(VERSION_CLASH) => {
+            let current = d
+                .try_downcast_ref::<PgDatabaseError>()
+                .and_then(PgDatabaseError::detail);
+            version_clash(current.unwrap_or_default())
+        }
+        sqlx::Error::Database(ref d) if d.code().as_deref() == Some(DELETED) => {
+            Status::failed_precondition("object is deleted")
+        }
+        sqlx::Error::Database(ref d) if d.code().as_deref() == Some(UNIQUE_VIOLATION) => {
+            let detail = d
+                .try_downcast_ref::<PgDatabaseError>()
+                .and_then(PgDatabaseError::detail);
+            unique_violation(d.message(), detail.unwrap_or_default())
+        }
+        ref other => match transient(other) {
+            // Out of retries (see `Backoff`): the caller may try again.
+            Some(Transient::Conflict) => {
+                tracing::warn!("database conflict: {other}");
+                Status::aborted("transaction conflict, retry")
+            }
+            Some(Transient::Connection) => {
+                tracing::warn!("database unreachable: {other}");
+                Status::unavailable("database unavailable")
+            }
+            None => {
+                tracing::error!("database error: {:?}", other);
+                Status::internal("database error")
+            }
+        },
+    }
+}
+
+/// An error that is likely gone on the next attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transient {
+    /// The transaction was rolled back: serialization failure (YugabyteDB's
+    /// restart-read and conflict errors included) or deadlock.
+    Conflict,
+    /// The connection broke or the server is restarting; a statement in
+    /// flight may or may not have taken effect.
+    Connection,
+}
+
+pub fn transient(e: &sqlx::Error) -> Option<Transient> {
+    match e {
+        sqlx::Error::Database(d) => match d.code().as_deref()? {
+            "40001" | "40P01" => Some(Transient::Conflict),
+            c if c.starts_with("08") => Some(Transient::Connection),
+            "57P01" | "57P02" | "57P03" => Some(Transient::Connection),
+            _ => None,
+        },
+        sqlx::Error::Io(_) => Some(Transient::Connection),
+        _ => None,
+    }
+}
+
+/// Which transient errors a call may be repeated after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retry {
+    /// Reads and other calls that land the same way twice: any of them.
+    Idempotent,
+    /// Writes: only [`Transient::Conflict`], after which nothing happened.
+    Rollback,
+}
+
+/// Attempts per call, the first included.
+const RETRY_ATTEMPTS: u32 = 6;
+/// Backoff before the second attempt; doubles from there.
+const RETRY_BASE: Duration = Duration::from_millis(20);
+const RETRY_CAP: Duration = Duration::from_secs(1);
+/// Total time spent retrying when the call has no deadline.
+const RETRY_BUDGET: Duration = Duration::from_secs(10);
+
+/// Errors [`Backoff`] can look into.
+pub trait Cause {
+    fn cause(&self) -> &sqlx::Error;
+}
+
+impl Cause for sqlx::Error {
+    fn cause(&self) -> &sqlx::Error {
+        self
+    }
+}
+
+impl<T> Cause for (T, sqlx::Error) {
+    fn cause(&self) -> &sqlx::Error {
+        &self.1
+    }
+}
+
+/// Exponential backoff with jitter, stopping at the call's deadline
+/// (see `deadline.rs`).  Use through [`retry!`].
+pub struct Backoff {
+    retry: Retry,
+    attempt: u32,
+    until: Instant,
+}
+
+impl Backoff {
+    pub fn new(retry: Retry) -> Self {
+        let budget = Instant::now() + RETRY_BUDGET;
+        Self {
+            retry,
+            attempt: 1,
+            until: deadline::current().map_or(budget, |d| d.min(budget)),
+        }
+    }
+
+    /// After a failed attempt: wait and return `true` if another is due.
+    pub async fn again(&mut self, e: &impl Cause) -> bool {
+        let e = e.cause();
+        let retryable = matches!(
+            (transient(e), self.retry),
+            (Some(Transient::Conflict), _) | (Some(Transient::Connection), Retry::Idempotent)
+        );
+        if !retryable || self.attempt >= RETRY_ATTEMPTS {
+            return false;
+        }
+
+        // Half fixed, half random, so clients that clashed spread out.
+        let step = RETRY_BASE.saturating_mul(1 << (self.attempt - 1)).min(RETRY_CAP);
+        let delay = step / 2 + rand::thread_rng().gen_range(Duration::ZERO..=step / 2);
+        if Instant::now() + delay >= self.until {
+            return false;
+        }
+        debug!(attempt = self.attempt, ?delay, "transient database error, retrying: {e}");
+        tokio::time::sleep(delay).await;
+        self.attempt += 1;
+        true
+    }
+}
+
+/// Evaluate and await `$attempt` until it succeeds or [`Backoff`] gives
+/// up; the expression is re-evaluated each time, so it may borrow freely.
+///
+/// ```ignore
+/// let row = retry!(Retry::Idempotent, sqlx::query(SQL).fetch_one(db))?;
+/// ```
+macro_rules! retry {
+    ($retry:expr, $attempt:expr) => {{
+        let mut backoff = $crate::db::Backoff::new($retry);
+        loop {
+            match $attempt.await {
+                Err(e) if backoff.again(&e).await => continue,
+                done => break done,
+            }
         }
+    }};
+}
+pub(crate) use retry;
+
+/// `ABORTED` carrying the current stored version.
+pub fn version_clash(current_version: &str) -> Status {
+    Status::with_error_details(
+        Code::Aborted,
+        "version clash",
+        ErrorDetails::with_error_info(
+            "VERSION_CLASH",
+            "brother",
+            HashMap::from([("current_version".to_owned(), current_version.to_owned())]),
+        ),
+    )
+}
+
+/// `ALREADY_EXISTS` with an `ErrorInfo` detail (`reason =
+/// "UNIQUE_VIOLATION"`, `metadata.path` dotted, `metadata.id` of the holder).
+pub fn unique_violation(message: &str, detail: &str) -> Status {
+    let detail: serde_json::Value = serde_json::from_str(detail).unwrap_or_default();
+    let path = detail["path"]
+        .as_array()
+        .map(|p| p.iter().filter_map(|s| s.as_str()).collect::<Vec<_>>().join("."))
+        .unwrap_or_default();
+    let id = detail["id"].as_i64().map(|i| i.to_string()).unwrap_or_default();
+
+    Status::with_error_details(
+        Code::AlreadyExists,
+        message,
+        ErrorDetails::with_error_info(
+            "UNIQUE_VIOLATION",
+            "brother",
+            HashMap::from([("path".to_owned(), path), ("id".to_owned(), id)]),
+        ),
+    )
+}
+
+/// Map an error that a batch function caught for a single item (see
+/// `tao_upsert_objects`) – only the SQLSTATE classes it catches occur here.
+pub fn item_err(state: &str, message: &str, detail: Option<&str>) -> Status {
+    match state {
+        VERSION_CLASH => version_clash(detail.unwrap_or_default()),
+        DELETED => Status::failed_precondition("object is deleted"),
+        UNIQUE_VIOLATION => unique_violation(message, detail.unwrap_or_default()),
+        s if s.starts_with("22") => Status::invalid_argument(message),
+        _ => Status::failed_precondition(message),
     }
 }
 
+/// `BROTHER_TEST_DATABASE_URL`, migrated.  Tests that need it are
+/// `#[ignore]`d; run them with `cargo test -- --ignored`.
+#[cfg(test)]
+pub async fn test_pool() -> PgPool {
+    let url = std::env::var("BROTHER_TEST_DATABASE_URL")
+        .expect("BROTHER_TEST_DATABASE_URL names the test database");
+    let cfg = config::Database {
+        url: Some(url),
+        max_connections: 4,
+        ..Default::default()
+    };
+    init_pool(&cfg, true).await.expect("test database").primary
+}
+
+/// A tenant no earlier test run has written to, in all likelihood.
+#[cfg(test)]
+pub fn test_tenant() -> crate::auth::Tenant {
+    use std::sync::atomic::{AtomicU32, Ordering::Relaxed};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    static NEXT: AtomicU32 = AtomicU32::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .subsec_nanos();
+    crate::auth::Tenant(
+        1_000_000 + (nanos ^ NEXT.fetch_add(1, Relaxed).wrapping_mul(2_654_435_761)) % 1_000_000_000,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{borrow::Cow, error::Error as StdError, fmt};
+
+    use sqlx::error::{DatabaseError, ErrorKind};
+
+    use super::*;
+
+    /// A server error with nothing but its SQLSTATE.
+    #[derive(Debug)]
+    struct State(&'static str);
+
+    impl fmt::Display for State {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl StdError for State {}
+
+    impl DatabaseError for State {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn state(code: &'static str) -> sqlx::Error {
+        sqlx::Error::Database(Box::new(State(code)))
+    }
+
+    fn io() -> sqlx::Error {
+        sqlx::Error::Io(std::io::ErrorKind::ConnectionReset.into())
+    }
+
+    #[test]
+    fn sqlstates_are_classified() {
+        for code in ["40001", "40P01"] {
+            assert_eq!(transient(&state(code)), Some(Transient::Conflict), "{code}");
+        }
+        for code in ["08000", "08003", "08006", "57P01", "57P02", "57P03"] {
+            assert_eq!(transient(&state(code)), Some(Transient::Connection), "{code}");
+        }
+        assert_eq!(transient(&io()), Some(Transient::Connection));
+
+        // Constraint and data errors, our own codes, and a cancelled
+        // statement will fail the same way again.
+        for code in ["23505", "22P02", "40002", "57014", VERSION_CLASH, DELETED, UNIQUE_VIOLATION] {
+            assert_eq!(transient(&state(code)), None, "{code}");
+        }
+        assert_eq!(transient(&sqlx::Error::RowNotFound), None);
+        assert_eq!(transient(&sqlx::Error::PoolTimedOut), None);
+    }
+
+    #[tokio::test]
+    async fn rollback_retries_conflicts_only() {
+        assert!(Backoff::new(Retry::Rollback).again(&state("40001")).await);
+        assert!(Backoff::new(Retry::Rollback).again(&state("40P01")).await);
+        // The write may have committed before the connection went.
+        assert!(!Backoff::new(Retry::Rollback).again(&state("08006")).await);
+        assert!(!Backoff::new(Retry::Rollback).again(&state("57P01")).await);
+        assert!(!Backoff::new(Retry::Rollback).again(&io()).await);
+        assert!(!Backoff::new(Retry::Rollback).again(&state("23505")).await);
+    }
+
+    #[tokio::test]
+    async fn idempotent_calls_retry_connection_errors_too() {
+        for e in [state("40001"), state("08006"), state("57P03"), io()] {
+            assert!(Backoff::new(Retry::Idempotent).again(&e).await, "{e}");
+        }
+        assert!(!Backoff::new(Retry::Idempotent).again(&state("23505")).await);
+        assert!(!Backoff::new(Retry::Idempotent).again(&sqlx::Error::RowNotFound).await);
+    }
+
+    #[tokio::test]
+    async fn backoff_gives_up() {
+        let mut backoff = Backoff::new(Retry::Idempotent);
+        for _ in 1..RETRY_ATTEMPTS {
+            assert!(backoff.again(&state("40001")).await);
+        }
+        assert!(!backoff.again(&state("40001")).await, "attempts are capped");
+
+        let mut backoff = Backoff::new(Retry::Idempotent);
+        backoff.until = Instant::now() + RETRY_BASE / 4;
+        assert!(!backoff.again(&state("40001")).await, "no retry past the deadline");
+    }
+}
@@ -0,0 +1,136 @@
+//! src/deadline.rs
+//! How long the caller is still waiting.
+//!
+//! The `grpc-timeout` a client sends, capped by the server's own request
+//! timeout, becomes a deadline that the handler's task can read through
+//! [`current`].  Database retries (see `db::Backoff`) stop before it.
+
+use std::{
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use tonic::codegen::{http, BoxFuture, Service};
+use tonic::server::NamedService;
+
+tokio::task_local! {
+    static DEADLINE: Option<Instant>;
+}
+
+/// The deadline of the call being served, if it has one.
+pub fn current() -> Option<Instant> {
+    DEADLINE.try_with(|d| *d).ok().flatten()
+}
+
+/// `grpc-timeout`: up to 8 digits and a unit (`H M S m u n`).
+fn parse_timeout(value: &str) -> Option<Duration> {
+    if !(2..=9).contains(&value.len()) || !value.is_ascii() {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    // `parse` alone would take a leading `+`.
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let n: u64 = digits.parse().ok()?;
+    Some(match unit {
+        "H" => Duration::from_secs(n * 3600),
+        "M" => Duration::from_secs(n * 60),
+        "S" => Duration::from_secs(n),
+        "m" => Duration::from_millis(n),
+        "u" => Duration::from_micros(n),
+        "n" => Duration::from_nanos(n),
+        _ => return None,
+    })
+}
+
+/// Wraps a gRPC service so every call runs with its deadline in scope.
+#[derive(Clone)]
+pub struct WithDeadline<S> {
+    inner: S,
+    /// The server-wide request timeout, if configured.
+    limit: Option<Duration>,
+}
+
+impl<S> WithDeadline<S> {
+    pub fn new(inner: S, limit: Option<Duration>) -> Self {
+        Self { inner, limit }
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for WithDeadline<S>
+where
+    S: Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let asked = req
+            .headers()
+            .get("grpc-timeout")
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_timeout);
+        let timeout = match (asked, self.limit) {
+            (Some(a), Some(l)) => Some(a.min(l)),
+            (a, l) => a.or(l),
+        };
+        let deadline = timeout.map(|t| Instant::now() + t);
+        Box::pin(DEADLINE.scope(deadline, self.inner.call(req)))
+    }
+}
+
+impl<S: NamedService> NamedService for WithDeadline<S> {
+    const NAME: &'static str = S::NAME;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_unit() {
+        let cases = [
+            ("2H", Duration::from_secs(7200)),
+            ("3M", Duration::from_secs(180)),
+            ("5S", Duration::from_secs(5)),
+            ("250m", Duration::from_millis(250)),
+            ("750u", Duration::from_micros(750)),
+            ("900n", Duration::from_nanos(900)),
+            ("0S", Duration::ZERO),
+            ("00000010m", Duration::from_millis(10)),
+            ("99999999H", Duration::from_secs(99_999_999 * 3600)),
+        ];
+        for (value, want) in cases {
+            assert_eq!(parse_timeout(value), Some(want), "{value}");
+        }
+    }
+
+    #[test]
+    fn malformed_headers() {
+        for value in [
+            "",
+            "S",
+            "5",
+            "100000000S", // nine digits
+            "5s",
+            "5ms",
+            "5 S",
+            " 5S",
+            "-5S",
+            "+5S",
+            "5.0S",
+            "0x5S",
+            "５S",
+            "5Ｓ",
+        ] {
+            assert_eq!(parse_timeout(value), None, "{value:?}");
+        }
+    }
+}
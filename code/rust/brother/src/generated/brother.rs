@@ -1,4 +1,55 @@
 // This file is @generated by prost-build.
+/// A typed attribute value, stored as JSONB.
+///
+/// Plain JSON kinds (null, bool, string, numbers, lists, maps) are stored as
+/// themselves, so data written by other tools reads back unchanged.  An
+/// integer reads back as `int_value` when it fits in int64 and as
+/// `uint_value` otherwise.  Kinds JSON cannot express are stored as
+/// single-key tagged objects: `{"$bytes": "<base64>"}`,
+/// `{"$timestamp": "<RFC 3339>"}` and `{"$double": "<text>"}` (non-finite
+/// or integral-and-huge doubles).  A map that would look like a tag is
+/// itself wrapped as `{"$map": {...}}`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Value {
+    #[prost(oneof = "value::Kind", tags = "1, 2, 3, 4, 5, 6, 7, 8, 9, 10")]
+    pub kind: ::core::option::Option<value::Kind>,
+}
+/// Nested message and enum types in `Value`.
+pub mod value {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Kind {
+        #[prost(enumeration = "::prost_types::NullValue", tag = "1")]
+        NullValue(i32),
+        #[prost(int64, tag = "2")]
+        IntValue(i64),
+        #[prost(uint64, tag = "3")]
+        UintValue(u64),
+        #[prost(double, tag = "4")]
+        DoubleValue(f64),
+        #[prost(bool, tag = "5")]
+        BoolValue(bool),
+        #[prost(string, tag = "6")]
+        StringValue(::prost::alloc::string::String),
+        #[prost(bytes, tag = "7")]
+        BytesValue(::prost::alloc::vec::Vec<u8>),
+        #[prost(message, tag = "8")]
+        ListValue(super::ListValue),
+        #[prost(message, tag = "9")]
+        MapValue(super::MapValue),
+        #[prost(message, tag = "10")]
+        TimestampValue(::prost_types::Timestamp),
+    }
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListValue {
+    #[prost(message, repeated, tag = "1")]
+    pub values: ::prost::alloc::vec::Vec<Value>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MapValue {
+    #[prost(map = "string, message", tag = "1")]
+    pub fields: ::std::collections::HashMap<::prost::alloc::string::String, Value>,
+}
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Object {
     #[prost(uint32, tag = "1")]
@@ -9,11 +60,11 @@ pub struct Object {
     pub id: u64,
     #[prost(uint32, tag = "4")]
     pub version: u32,
-    #[prost(map = "string, string", tag = "5")]
-    pub attributes: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
+    #[prost(map = "string, message", tag = "6")]
+    pub attributes: ::std::collections::HashMap<::prost::alloc::string::String, Value>,
+    /// Epoch milliseconds; unset = never.  Past it the object reads as absent.
+    #[prost(uint64, optional, tag = "7")]
+    pub expires_at: ::core::option::Option<u64>,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Association {
@@ -29,18 +80,30 @@ pub struct Association {
     pub time: u64,
     #[prost(uint64, tag = "6")]
     pub position: u64,
-    #[prost(map = "string, string", tag = "7")]
-    pub attributes: ::std::collections::HashMap<
-        ::prost::alloc::string::String,
-        ::prost::alloc::string::String,
-    >,
+    #[prost(map = "string, message", tag = "8")]
+    pub attributes: ::std::collections::HashMap<::prost::alloc::string::String, Value>,
+    /// Epoch milliseconds; unset = never.  Past it the edge reads as absent
+    /// and stops counting.
+    #[prost(uint64, optional, tag = "9")]
+    pub expires_at: ::core::option::Option<u64>,
 }
+/// Point-in-time reads: set at most one of `as_of_version` / `as_of_time`.
+/// An id created again after its tombstone was purged starts over at
+/// version 0; `as_of_version` reads that current incarnation only.
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct GetObjectRequest {
     #[prost(uint32, tag = "1")]
     pub otype: u32,
     #[prost(uint64, tag = "2")]
     pub id: u64,
+    #[prost(uint32, optional, tag = "3")]
+    pub as_of_version: ::core::option::Option<u32>,
+    /// epoch-ms
+    #[prost(uint64, optional, tag = "4")]
+    pub as_of_time: ::core::option::Option<u64>,
+    /// as_of reads are always STRONG
+    #[prost(enumeration = "Consistency", tag = "5")]
+    pub consistency: i32,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetObjectResponse {
@@ -52,12 +115,19 @@ pub struct PutObjectRequest {
     #[prost(message, optional, tag = "1")]
     pub object: ::core::option::Option<Object>,
 }
+/// A version clash fails with ABORTED; its ErrorInfo detail carries
+/// `current_version`.
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct PutObjectResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
     #[prost(uint64, tag = "2")]
     pub id: u64,
+    #[prost(bool, tag = "3")]
+    pub created: bool,
+    /// version after the write
+    #[prost(uint32, tag = "4")]
+    pub version: u32,
 }
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct RemoveObjectRequest {
@@ -72,6 +142,54 @@ pub struct RemoveObjectResponse {
     pub success: bool,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ObjectVersion {
+    #[prost(uint32, tag = "1")]
+    pub version: u32,
+    /// epoch-ms the version became current
+    #[prost(uint64, tag = "3")]
+    pub valid_from: u64,
+    /// tombstone version
+    #[prost(bool, tag = "4")]
+    pub deleted: bool,
+    #[prost(map = "string, message", tag = "5")]
+    pub attributes: ::std::collections::HashMap<::prost::alloc::string::String, Value>,
+}
+/// Newest first, of the id's current incarnation (see GetObjectRequest).
+/// `before_version` pages backwards through the history.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct GetObjectHistoryRequest {
+    #[prost(uint32, tag = "1")]
+    pub otype: u32,
+    #[prost(uint64, tag = "2")]
+    pub id: u64,
+    #[prost(int32, tag = "3")]
+    pub limit: i32,
+    #[prost(uint32, optional, tag = "4")]
+    pub before_version: ::core::option::Option<u32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetObjectHistoryResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub versions: ::prost::alloc::vec::Vec<ObjectVersion>,
+}
+/// Undeletes an object removed within the server's tombstone retention.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct RestoreObjectRequest {
+    #[prost(uint32, tag = "1")]
+    pub otype: u32,
+    #[prost(uint64, tag = "2")]
+    pub id: u64,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct RestoreObjectResponse {
+    /// false: not deleted, unknown, or already purged
+    #[prost(bool, tag = "1")]
+    pub success: bool,
+    /// version after the restore
+    #[prost(uint32, tag = "2")]
+    pub version: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CreateAssociationRequest {
     #[prost(message, optional, tag = "1")]
     pub association: ::core::option::Option<Association>,
@@ -83,160 +201,889 @@ pub struct CreateAssociationResponse {
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct RemoveAssociationRequest {
-    #[prost(uint32, tag = "1")]
-    pub otype: u32,
-    #[prost(uint64, tag = "2")]
-    pub src_id: u64,
-    #[prost(string, tag = "3")]
-    pub atype: ::prost::alloc::string::String,
-    #[prost(uint64, tag = "4")]
-    pub tar_tid: u64,
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub source_id: i64,
+    #[prost(int64, tag = "3")]
+    pub target_id: i64,
 }
 #[derive(Clone, Copy, PartialEq, ::prost::Message)]
 pub struct RemoveAssociationResponse {
     #[prost(bool, tag = "1")]
     pub success: bool,
 }
+/// assoc_range: every edge of (type, source_id), ordered by position.
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetAssociationsRequest {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub source_id: i64,
+    #[prost(int32, tag = "4")]
+    pub limit: i32,
+    /// `next_cursor` of the previous page; empty = start
+    #[prost(bytes = "vec", tag = "5")]
+    pub cursor: ::prost::alloc::vec::Vec<u8>,
+    #[prost(enumeration = "Order", tag = "6")]
+    pub order: i32,
+    /// inclusive, epoch-ms
+    #[prost(uint64, optional, tag = "7")]
+    pub time_from: ::core::option::Option<u64>,
+    /// exclusive, epoch-ms
+    #[prost(uint64, optional, tag = "8")]
+    pub time_to: ::core::option::Option<u64>,
+    #[prost(enumeration = "Consistency", tag = "9")]
+    pub consistency: i32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetAssociationsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub associations: ::prost::alloc::vec::Vec<Association>,
+    /// empty when there are no more pages
+    #[prost(bytes = "vec", tag = "2")]
+    pub next_cursor: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AssocCountRequest {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub source_id: i64,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct AssocCountResponse {
+    #[prost(uint64, tag = "1")]
+    pub count: u64,
+}
+/// Per-item outcome inside a batch response; mirrors google.rpc.Status.
+/// Absent on success.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ItemStatus {
+    /// grpc status code
+    #[prost(int32, tag = "1")]
+    pub code: i32,
+    #[prost(string, tag = "2")]
+    pub message: ::prost::alloc::string::String,
+    /// same encoding as the `grpc-status-details-bin` trailer
+    #[prost(bytes = "vec", tag = "3")]
+    pub details: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ObjectKey {
     #[prost(uint32, tag = "1")]
     pub otype: u32,
     #[prost(uint64, tag = "2")]
-    pub src_id: u64,
-    #[prost(string, tag = "3")]
-    pub atype: ::prost::alloc::string::String,
-    #[prost(uint64, tag = "4")]
-    pub tar_id: u64,
-    /// For pagination
-    #[prost(uint64, tag = "5")]
-    pub above: u64,
-    #[prost(uint32, tag = "6")]
-    pub limit: u32,
+    pub id: u64,
 }
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct GetAssociationsResponse {
+pub struct BatchGetObjectsRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub keys: ::prost::alloc::vec::Vec<ObjectKey>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ObjectResult {
+    #[prost(message, optional, tag = "1")]
+    pub object: ::core::option::Option<Object>,
+    #[prost(message, optional, tag = "2")]
+    pub error: ::core::option::Option<ItemStatus>,
+}
+/// `results\[i\]` answers `keys\[i\]`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchGetObjectsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<ObjectResult>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchPutObjectsRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub objects: ::prost::alloc::vec::Vec<Object>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutObjectResult {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(bool, tag = "2")]
+    pub created: bool,
+    #[prost(uint32, tag = "3")]
+    pub version: u32,
+    #[prost(message, optional, tag = "4")]
+    pub error: ::core::option::Option<ItemStatus>,
+}
+/// `results\[i\]` answers `objects\[i\]`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchPutObjectsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<PutObjectResult>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchCreateAssociationsRequest {
     #[prost(message, repeated, tag = "1")]
     pub associations: ::prost::alloc::vec::Vec<Association>,
 }
-/// Generated client implementations.
-pub mod brother_client {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    use tonic::codegen::http::Uri;
-    #[derive(Debug, Clone)]
-    pub struct BrotherClient<T> {
-        inner: tonic::client::Grpc<T>,
+/// `results\[i\]` answers `associations\[i\]`; an unset `error` means success.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AssociationResult {
+    #[prost(message, optional, tag = "1")]
+    pub error: ::core::option::Option<ItemStatus>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchCreateAssociationsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<AssociationResult>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct DeleteObjectOp {
+    #[prost(uint32, tag = "1")]
+    pub otype: u32,
+    #[prost(uint64, tag = "2")]
+    pub id: u64,
+    /// unset = unconditional
+    #[prost(uint32, optional, tag = "3")]
+    pub expected_version: ::core::option::Option<u32>,
+}
+/// `source_from` / `target_from` name an earlier `put_object` op whose
+/// resulting id replaces `source_id` / `target_id` – so an object and its
+/// edges can be created in the same Write.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpsertAssociationOp {
+    #[prost(message, optional, tag = "1")]
+    pub association: ::core::option::Option<Association>,
+    #[prost(uint32, optional, tag = "2")]
+    pub source_from: ::core::option::Option<u32>,
+    #[prost(uint32, optional, tag = "3")]
+    pub target_from: ::core::option::Option<u32>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteAssociationOp {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(int64, tag = "2")]
+    pub source_id: i64,
+    #[prost(int64, tag = "3")]
+    pub target_id: i64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteOp {
+    #[prost(oneof = "write_op::Op", tags = "1, 2, 3, 4")]
+    pub op: ::core::option::Option<write_op::Op>,
+}
+/// Nested message and enum types in `WriteOp`.
+pub mod write_op {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Op {
+        /// `version` is the expected version, as in PutObject
+        #[prost(message, tag = "1")]
+        PutObject(super::Object),
+        #[prost(message, tag = "2")]
+        DeleteObject(super::DeleteObjectOp),
+        #[prost(message, tag = "3")]
+        UpsertAssociation(super::UpsertAssociationOp),
+        #[prost(message, tag = "4")]
+        DeleteAssociation(super::DeleteAssociationOp),
     }
-    impl BrotherClient<tonic::transport::Channel> {
-        /// Attempt to create a new client by connecting to a given endpoint.
-        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
-        where
-            D: TryInto<tonic::transport::Endpoint>,
-            D::Error: Into<StdError>,
-        {
-            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
-            Ok(Self::new(conn))
-        }
+}
+/// All ops commit together or not at all.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub ops: ::prost::alloc::vec::Vec<WriteOp>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct WriteOpResult {
+    /// put_object: id written
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    /// put_object
+    #[prost(bool, tag = "2")]
+    pub created: bool,
+    /// put_object: version after the write
+    #[prost(uint32, tag = "3")]
+    pub version: u32,
+    /// delete_*: whether the row existed
+    #[prost(bool, tag = "4")]
+    pub found: bool,
+}
+/// `results\[i\]` answers `ops\[i\]`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub results: ::prost::alloc::vec::Vec<WriteOpResult>,
+}
+/// `otypes` / `types` empty = every type.  `after_lsn` resumes after the
+/// last event seen; unset = only changes from now on.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchObjectsRequest {
+    #[prost(uint32, repeated, tag = "1")]
+    pub otypes: ::prost::alloc::vec::Vec<u32>,
+    #[prost(uint64, optional, tag = "2")]
+    pub after_lsn: ::core::option::Option<u64>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchAssociationsRequest {
+    #[prost(string, repeated, tag = "1")]
+    pub types: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(uint64, optional, tag = "2")]
+    pub after_lsn: ::core::option::Option<u64>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ObjectChange {
+    #[prost(uint64, tag = "1")]
+    pub lsn: u64,
+    #[prost(enumeration = "ChangeOp", tag = "2")]
+    pub op: i32,
+    #[prost(uint32, tag = "3")]
+    pub otype: u32,
+    #[prost(uint64, tag = "4")]
+    pub id: u64,
+    /// unset on create
+    #[prost(uint32, optional, tag = "5")]
+    pub old_version: ::core::option::Option<u32>,
+    /// unset on delete
+    #[prost(uint32, optional, tag = "6")]
+    pub new_version: ::core::option::Option<u32>,
+    /// epoch-ms
+    #[prost(uint64, tag = "7")]
+    pub time: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AssociationChange {
+    #[prost(uint64, tag = "1")]
+    pub lsn: u64,
+    #[prost(enumeration = "ChangeOp", tag = "2")]
+    pub op: i32,
+    #[prost(string, tag = "3")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(int64, tag = "4")]
+    pub source_id: i64,
+    #[prost(int64, tag = "5")]
+    pub target_id: i64,
+    /// epoch-ms
+    #[prost(uint64, tag = "6")]
+    pub time: u64,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldFilter {
+    /// top-level attribute name
+    #[prost(string, tag = "1")]
+    pub field: ::prost::alloc::string::String,
+    #[prost(enumeration = "FilterOp", tag = "2")]
+    pub op: i32,
+    #[prost(message, optional, tag = "3")]
+    pub value: ::core::option::Option<Value>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FilterList {
+    #[prost(message, repeated, tag = "1")]
+    pub filters: ::prost::alloc::vec::Vec<Filter>,
+}
+/// `all` of an empty list matches everything, `any` of one nothing.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Filter {
+    #[prost(oneof = "filter::Kind", tags = "1, 2, 3")]
+    pub kind: ::core::option::Option<filter::Kind>,
+}
+/// Nested message and enum types in `Filter`.
+pub mod filter {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        Field(super::FieldFilter),
+        #[prost(message, tag = "2")]
+        All(super::FilterList),
+        #[prost(message, tag = "3")]
+        Any(super::FilterList),
     }
-    impl<T> BrotherClient<T>
-    where
-        T: tonic::client::GrpcService<tonic::body::Body>,
-        T::Error: Into<StdError>,
-        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
-        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
-    {
-        pub fn new(inner: T) -> Self {
-            let inner = tonic::client::Grpc::new(inner);
-            Self { inner }
-        }
-        pub fn with_origin(inner: T, origin: Uri) -> Self {
-            let inner = tonic::client::Grpc::with_origin(inner, origin);
-            Self { inner }
-        }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> BrotherClient<InterceptedService<T, F>>
-        where
-            F: tonic::service::Interceptor,
-            T::ResponseBody: Default,
-            T: tonic::codegen::Service<
-                http::Request<tonic::body::Body>,
-                Response = http::Response<
-                    <T as tonic::client::GrpcService<tonic::body::Body>>::ResponseBody,
-                >,
-            >,
-            <T as tonic::codegen::Service<
-                http::Request<tonic::body::Body>,
-            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
-        {
-            BrotherClient::new(InterceptedService::new(inner, interceptor))
-        }
-        /// Compress requests with the given encoding.
-        ///
-        /// This requires the server to support it otherwise it might respond with an
-        /// error.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.send_compressed(encoding);
-            self
-        }
-        /// Enable decompressing responses.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.inner = self.inner.accept_compressed(encoding);
-            self
-        }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_decoding_message_size(limit);
-            self
-        }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.inner = self.inner.max_encoding_message_size(limit);
-            self
-        }
-        pub async fn get_object(
-            &mut self,
-            request: impl tonic::IntoRequest<super::GetObjectRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::GetObjectResponse>,
-            tonic::Status,
-        > {
-            self.inner
-                .ready()
-                .await
-                .map_err(|e| {
-                    tonic::Status::unknown(
-                        format!("Service was not ready: {}", e.into()),
-                    )
-                })?;
-            let codec = tonic::codec::ProstCodec::default();
-            let path = http::uri::PathAndQuery::from_static(
-                "/brother.Brother/GetObject",
-            );
-            let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("brother.Brother", "GetObject"));
-            self.inner.unary(req, path, codec).await
-        }
-        pub async fn put_object(
-            &mut self,
-            request: impl tonic::IntoRequest<super::PutObjectRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::PutObjectResponse>,
+}
+/// Unset `filter` = every object of the type.  `order_by` names an
+/// attribute to sort on (missing = lowest); empty sorts by id.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryObjectsRequest {
+    #[prost(uint32, tag = "1")]
+    pub otype: u32,
+    #[prost(message, optional, tag = "2")]
+    pub filter: ::core::option::Option<Filter>,
+    #[prost(string, tag = "3")]
+    pub order_by: ::prost::alloc::string::String,
+    #[prost(enumeration = "Order", tag = "4")]
+    pub order: i32,
+    #[prost(int32, tag = "5")]
+    pub limit: i32,
+    /// next_cursor of the previous page
+    #[prost(bytes = "vec", tag = "6")]
+    pub cursor: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryObjectsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub objects: ::prost::alloc::vec::Vec<Object>,
+    /// empty = no more
+    #[prost(bytes = "vec", tag = "2")]
+    pub next_cursor: ::prost::alloc::vec::Vec<u8>,
+}
+/// `source_id` unset = every source.  Empty `order_by` sorts by position.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryAssociationsRequest {
+    #[prost(string, tag = "1")]
+    pub r#type: ::prost::alloc::string::String,
+    #[prost(uint64, optional, tag = "2")]
+    pub source_id: ::core::option::Option<u64>,
+    #[prost(message, optional, tag = "3")]
+    pub filter: ::core::option::Option<Filter>,
+    #[prost(string, tag = "4")]
+    pub order_by: ::prost::alloc::string::String,
+    #[prost(enumeration = "Order", tag = "5")]
+    pub order: i32,
+    #[prost(int32, tag = "6")]
+    pub limit: i32,
+    #[prost(bytes = "vec", tag = "7")]
+    pub cursor: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryAssociationsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub associations: ::prost::alloc::vec::Vec<Association>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub next_cursor: ::prost::alloc::vec::Vec<u8>,
+}
+/// `min` / `max` bound a number's value, or the length of a string, bytes,
+/// list or map; a bounded double must be finite.  `one_of` restricts the
+/// field to the listed values.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FieldSchema {
+    #[prost(enumeration = "FieldType", tag = "1")]
+    pub r#type: i32,
+    #[prost(bool, tag = "2")]
+    pub required: bool,
+    #[prost(double, optional, tag = "3")]
+    pub min: ::core::option::Option<f64>,
+    #[prost(double, optional, tag = "4")]
+    pub max: ::core::option::Option<f64>,
+    #[prost(message, repeated, tag = "5")]
+    pub one_of: ::prost::alloc::vec::Vec<Value>,
+}
+/// `closed` rejects attributes that are not listed in `fields`.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TypeSchema {
+    #[prost(map = "string, message", tag = "1")]
+    pub fields: ::std::collections::HashMap<::prost::alloc::string::String, FieldSchema>,
+    #[prost(bool, tag = "2")]
+    pub closed: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SchemaTarget {
+    #[prost(oneof = "schema_target::Target", tags = "1, 2")]
+    pub target: ::core::option::Option<schema_target::Target>,
+}
+/// Nested message and enum types in `SchemaTarget`.
+pub mod schema_target {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Target {
+        #[prost(uint32, tag = "1")]
+        Otype(u32),
+        #[prost(string, tag = "2")]
+        Atype(::prost::alloc::string::String),
+    }
+}
+/// Writes of a type that has a schema fail with INVALID_ARGUMENT and a
+/// BadRequest detail listing every violating field.  The inverse of a
+/// registered association pair is checked against the forward type only.
+/// Put / Delete need an admin caller.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PutSchemaRequest {
+    #[prost(message, optional, tag = "1")]
+    pub target: ::core::option::Option<SchemaTarget>,
+    #[prost(message, optional, tag = "2")]
+    pub schema: ::core::option::Option<TypeSchema>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct PutSchemaResponse {
+    #[prost(bool, tag = "1")]
+    pub created: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSchemaRequest {
+    #[prost(message, optional, tag = "1")]
+    pub target: ::core::option::Option<SchemaTarget>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetSchemaResponse {
+    /// unset = no schema, anything goes
+    #[prost(message, optional, tag = "1")]
+    pub schema: ::core::option::Option<TypeSchema>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteSchemaRequest {
+    #[prost(message, optional, tag = "1")]
+    pub target: ::core::option::Option<SchemaTarget>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct DeleteSchemaResponse {
+    #[prost(bool, tag = "1")]
+    pub found: bool,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListSchemasRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SchemaEntry {
+    #[prost(message, optional, tag = "1")]
+    pub target: ::core::option::Option<SchemaTarget>,
+    #[prost(message, optional, tag = "2")]
+    pub schema: ::core::option::Option<TypeSchema>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSchemasResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub schemas: ::prost::alloc::vec::Vec<SchemaEntry>,
+}
+/// Attribute `path` of object type `otype` is unique within the calling
+/// tenant, which declares, drops and lists its own indexes.  A put that
+/// would share a live object's value fails with ALREADY_EXISTS; its
+/// ErrorInfo (reason UNIQUE_VIOLATION) carries the dotted `path` and the
+/// holder's `id`.  Missing / null values are never claimed.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UniqueIndex {
+    #[prost(uint32, tag = "1")]
+    pub otype: u32,
+    #[prost(string, repeated, tag = "2")]
+    pub path: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Fails with ALREADY_EXISTS if existing objects already share a value.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeclareUniqueIndexRequest {
+    #[prost(message, optional, tag = "1")]
+    pub index: ::core::option::Option<UniqueIndex>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct DeclareUniqueIndexResponse {
+    #[prost(bool, tag = "1")]
+    pub created: bool,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DropUniqueIndexRequest {
+    #[prost(message, optional, tag = "1")]
+    pub index: ::core::option::Option<UniqueIndex>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct DropUniqueIndexResponse {
+    #[prost(bool, tag = "1")]
+    pub found: bool,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ListUniqueIndexesRequest {}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListUniqueIndexesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub indexes: ::prost::alloc::vec::Vec<UniqueIndex>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LookupByUniqueKeyRequest {
+    #[prost(uint32, tag = "1")]
+    pub otype: u32,
+    #[prost(string, repeated, tag = "2")]
+    pub path: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(message, optional, tag = "3")]
+    pub value: ::core::option::Option<Value>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct LookupByUniqueKeyResponse {
+    /// unset = no live object holds the value
+    #[prost(message, optional, tag = "1")]
+    pub object: ::core::option::Option<Object>,
+}
+/// Ids a later `PutObject` of type `otype` can create at, e.g. to link
+/// objects before they exist.  Same generator as `PutObject` with `id = 0`:
+/// no id is handed out twice, across types and tenants, and ids sort by
+/// creation time.  Ids that ImportTenant copies into another tenant are
+/// the exception: they keep the archive's.  Bits 10..16 carry the low 7
+/// bits of `otype` (types 1 and 129 share them), a hint only; bits 17..21
+/// carry the server's 5-bit `id_shard`.  `count` is 1..=1000.
+///
+/// An id is the caller's to create once: a `PutObject` at an id that
+/// already exists is an update, and at `version = 0` it overwrites a
+/// version-0 object.  `created = false` in the response tells.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct AllocateIdsRequest {
+    #[prost(uint32, tag = "1")]
+    pub otype: u32,
+    #[prost(uint32, tag = "2")]
+    pub count: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AllocateIdsResponse {
+    #[prost(uint64, repeated, tag = "1")]
+    pub ids: ::prost::alloc::vec::Vec<u64>,
+}
+/// An archive is the bytes "BROTHER-ARCHIVE\n" followed by length-delimited
+/// (varint-prefixed) ArchiveRecords: one header, the tenant's live objects,
+/// its live associations (inverse edges as stored), then one trailer.
+/// Ids, versions, `time` and `position` are kept; history, tombstones and
+/// expired rows are not.  Schemas are global and must already exist where
+/// it is imported.  The tenant's unique indexes travel in the header and
+/// are declared again by the import.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ArchiveHeader {
+    /// 1
+    #[prost(uint32, tag = "1")]
+    pub format: u32,
+    /// the exported tenant
+    #[prost(uint32, tag = "2")]
+    pub tenant: u32,
+    /// epoch-ms
+    #[prost(uint64, tag = "3")]
+    pub created_at: u64,
+    #[prost(message, repeated, tag = "4")]
+    pub unique_indexes: ::prost::alloc::vec::Vec<UniqueIndex>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ArchiveTrailer {
+    #[prost(uint64, tag = "1")]
+    pub objects: u64,
+    #[prost(uint64, tag = "2")]
+    pub associations: u64,
+    /// of every archive byte before this record
+    #[prost(bytes = "vec", tag = "3")]
+    pub sha256: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ArchiveRecord {
+    #[prost(oneof = "archive_record::Record", tags = "1, 2, 3, 4")]
+    pub record: ::core::option::Option<archive_record::Record>,
+}
+/// Nested message and enum types in `ArchiveRecord`.
+pub mod archive_record {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Record {
+        #[prost(message, tag = "1")]
+        Header(super::ArchiveHeader),
+        #[prost(message, tag = "2")]
+        Object(super::Object),
+        #[prost(message, tag = "3")]
+        Association(super::Association),
+        #[prost(message, tag = "4")]
+        Trailer(super::ArchiveTrailer),
+    }
+}
+/// Admin only.  Streams the archive of `tenant`, read from one snapshot,
+/// in chunks cut at arbitrary byte offsets.
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ExportTenantRequest {
+    #[prost(uint32, tag = "1")]
+    pub tenant: u32,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportTenantResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub chunk: ::prost::alloc::vec::Vec<u8>,
+}
+/// Admin only.  The archive in chunks, in order.  `tenant` (read from the
+/// first message) restores into another tenant than the archive's.  The
+/// target must hold no objects or associations, tombstones included.
+/// All or nothing: a damaged or truncated archive fails with DATA_LOSS.
+///
+/// Imported objects keep their ids.  Restored into another tenant of the
+/// cluster it was exported from, the archive's ids then exist twice, in
+/// both tenants – the one exception to "no id is handed out twice" (see
+/// AllocateIdsRequest).
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportTenantRequest {
+    #[prost(bytes = "vec", tag = "1")]
+    pub chunk: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint32, optional, tag = "2")]
+    pub tenant: ::core::option::Option<u32>,
+}
+#[derive(Clone, Copy, PartialEq, ::prost::Message)]
+pub struct ImportTenantResponse {
+    #[prost(uint32, tag = "1")]
+    pub tenant: u32,
+    #[prost(uint64, tag = "2")]
+    pub objects: u64,
+    #[prost(uint64, tag = "3")]
+    pub associations: u64,
+    /// declared for the tenant
+    #[prost(uint32, tag = "4")]
+    pub unique_indexes: u32,
+}
+/// Where a read may be served from.  BOUNDED_STALE goes to the read pool
+/// (a replica, or YugabyteDB follower reads) when the server has one and
+/// may miss writes newer than its staleness bound; without a read pool it
+/// is the same as STRONG.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Consistency {
+    Strong = 0,
+    BoundedStale = 1,
+}
+impl Consistency {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Strong => "CONSISTENCY_STRONG",
+            Self::BoundedStale => "CONSISTENCY_BOUNDED_STALE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CONSISTENCY_STRONG" => Some(Self::Strong),
+            "CONSISTENCY_BOUNDED_STALE" => Some(Self::BoundedStale),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Order {
+    /// newest (highest position) first
+    Desc = 0,
+    Asc = 1,
+}
+impl Order {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Desc => "ORDER_DESC",
+            Self::Asc => "ORDER_ASC",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ORDER_DESC" => Some(Self::Desc),
+            "ORDER_ASC" => Some(Self::Asc),
+            _ => None,
+        }
+    }
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ChangeOp {
+    ChangePut = 0,
+    ChangeDelete = 1,
+    /// removed by the reaper after `expires_at`
+    ChangeExpire = 2,
+}
+impl ChangeOp {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::ChangePut => "CHANGE_PUT",
+            Self::ChangeDelete => "CHANGE_DELETE",
+            Self::ChangeExpire => "CHANGE_EXPIRE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "CHANGE_PUT" => Some(Self::ChangePut),
+            "CHANGE_DELETE" => Some(Self::ChangeDelete),
+            "CHANGE_EXPIRE" => Some(Self::ChangeExpire),
+            _ => None,
+        }
+    }
+}
+/// EQ: the attribute equals `value`.  CONTAINS: JSONB containment – a list
+/// holds the listed elements, a map the given entries.  EXISTS: the
+/// attribute is set (`value` ignored).  LT/LE/GT/GE: numeric comparison,
+/// `value` must be a number; non-numeric attributes never match.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FilterOp {
+    FilterEq = 0,
+    FilterContains = 1,
+    FilterExists = 2,
+    FilterLt = 3,
+    FilterLe = 4,
+    FilterGt = 5,
+    FilterGe = 6,
+}
+impl FilterOp {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::FilterEq => "FILTER_EQ",
+            Self::FilterContains => "FILTER_CONTAINS",
+            Self::FilterExists => "FILTER_EXISTS",
+            Self::FilterLt => "FILTER_LT",
+            Self::FilterLe => "FILTER_LE",
+            Self::FilterGt => "FILTER_GT",
+            Self::FilterGe => "FILTER_GE",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FILTER_EQ" => Some(Self::FilterEq),
+            "FILTER_CONTAINS" => Some(Self::FilterContains),
+            "FILTER_EXISTS" => Some(Self::FilterExists),
+            "FILTER_LT" => Some(Self::FilterLt),
+            "FILTER_LE" => Some(Self::FilterLe),
+            "FILTER_GT" => Some(Self::FilterGt),
+            "FILTER_GE" => Some(Self::FilterGe),
+            _ => None,
+        }
+    }
+}
+/// Numeric kinds are checked by value: INT accepts a uint that fits,
+/// UINT a non-negative int, DOUBLE any number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum FieldType {
+    FieldAny = 0,
+    FieldInt = 1,
+    FieldUint = 2,
+    FieldDouble = 3,
+    FieldBool = 4,
+    FieldString = 5,
+    FieldBytes = 6,
+    FieldList = 7,
+    FieldMap = 8,
+    FieldTimestamp = 9,
+}
+impl FieldType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::FieldAny => "FIELD_ANY",
+            Self::FieldInt => "FIELD_INT",
+            Self::FieldUint => "FIELD_UINT",
+            Self::FieldDouble => "FIELD_DOUBLE",
+            Self::FieldBool => "FIELD_BOOL",
+            Self::FieldString => "FIELD_STRING",
+            Self::FieldBytes => "FIELD_BYTES",
+            Self::FieldList => "FIELD_LIST",
+            Self::FieldMap => "FIELD_MAP",
+            Self::FieldTimestamp => "FIELD_TIMESTAMP",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FIELD_ANY" => Some(Self::FieldAny),
+            "FIELD_INT" => Some(Self::FieldInt),
+            "FIELD_UINT" => Some(Self::FieldUint),
+            "FIELD_DOUBLE" => Some(Self::FieldDouble),
+            "FIELD_BOOL" => Some(Self::FieldBool),
+            "FIELD_STRING" => Some(Self::FieldString),
+            "FIELD_BYTES" => Some(Self::FieldBytes),
+            "FIELD_LIST" => Some(Self::FieldList),
+            "FIELD_MAP" => Some(Self::FieldMap),
+            "FIELD_TIMESTAMP" => Some(Self::FieldTimestamp),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+pub mod brother_client {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    use tonic::codegen::http::Uri;
+    #[derive(Debug, Clone)]
+    pub struct BrotherClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl BrotherClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> BrotherClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::Body>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> BrotherClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::Body>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::Body>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<
+                http::Request<tonic::body::Body>,
+            >>::Error: Into<StdError> + std::marker::Send + std::marker::Sync,
+        {
+            BrotherClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_decoding_message_size(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.inner = self.inner.max_encoding_message_size(limit);
+            self
+        }
+        pub async fn get_object(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetObjectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetObjectResponse>,
             tonic::Status,
         > {
             self.inner
@@ -249,17 +1096,17 @@ pub mod brother_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/brother.Brother/PutObject",
+                "/brother.Brother/GetObject",
             );
             let mut req = request.into_request();
-            req.extensions_mut().insert(GrpcMethod::new("brother.Brother", "PutObject"));
+            req.extensions_mut().insert(GrpcMethod::new("brother.Brother", "GetObject"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn remove_object(
+        pub async fn put_object(
             &mut self,
-            request: impl tonic::IntoRequest<super::RemoveObjectRequest>,
+            request: impl tonic::IntoRequest<super::PutObjectRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RemoveObjectResponse>,
+            tonic::Response<super::PutObjectResponse>,
             tonic::Status,
         > {
             self.inner
@@ -272,16 +1119,135 @@ pub mod brother_client {
                 })?;
             let codec = tonic::codec::ProstCodec::default();
             let path = http::uri::PathAndQuery::from_static(
-                "/brother.Brother/RemoveObject",
+                "/brother.Brother/PutObject",
             );
             let mut req = request.into_request();
-            req.extensions_mut()
-                .insert(GrpcMethod::new("brother.Brother", "RemoveObject"));
+            req.extensions_mut().insert(GrpcMethod::new("brother.Brother", "PutObject"));
             self.inner.unary(req, path, codec).await
         }
-        pub async fn create_association(
+        pub async fn remove_object(
             &mut self,
-            request: impl tonic::IntoRequest<super::CreateAssociationRequest>,
+            request: impl tonic::IntoRequest<super::RemoveObjectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RemoveObjectResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/RemoveObject",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "RemoveObject"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn restore_object(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RestoreObjectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RestoreObjectResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/RestoreObject",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "RestoreObject"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_object_history(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetObjectHistoryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetObjectHistoryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/GetObjectHistory",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "GetObjectHistory"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn batch_get_objects(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BatchGetObjectsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchGetObjectsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/BatchGetObjects",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "BatchGetObjects"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn batch_put_objects(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BatchPutObjectsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchPutObjectsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/BatchPutObjects",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "BatchPutObjects"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn create_association(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateAssociationRequest>,
         ) -> std::result::Result<
             tonic::Response<super::CreateAssociationResponse>,
             tonic::Status,
@@ -303,6 +1269,30 @@ pub mod brother_client {
                 .insert(GrpcMethod::new("brother.Brother", "CreateAssociation"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn batch_create_associations(
+            &mut self,
+            request: impl tonic::IntoRequest<super::BatchCreateAssociationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchCreateAssociationsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/BatchCreateAssociations",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "BatchCreateAssociations"));
+            self.inner.unary(req, path, codec).await
+        }
         pub async fn remove_association(
             &mut self,
             request: impl tonic::IntoRequest<super::RemoveAssociationRequest>,
@@ -351,157 +1341,1493 @@ pub mod brother_client {
                 .insert(GrpcMethod::new("brother.Brother", "GetAssociations"));
             self.inner.unary(req, path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod brother_server {
-    #![allow(
-        unused_variables,
-        dead_code,
-        missing_docs,
-        clippy::wildcard_imports,
-        clippy::let_unit_value,
-    )]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with BrotherServer.
-    #[async_trait]
-    pub trait Brother: std::marker::Send + std::marker::Sync + 'static {
-        async fn get_object(
-            &self,
-            request: tonic::Request<super::GetObjectRequest>,
-        ) -> std::result::Result<
-            tonic::Response<super::GetObjectResponse>,
-            tonic::Status,
-        >;
-        async fn put_object(
-            &self,
-            request: tonic::Request<super::PutObjectRequest>,
+        pub async fn assoc_count(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AssocCountRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::PutObjectResponse>,
+            tonic::Response<super::AssocCountResponse>,
             tonic::Status,
-        >;
-        async fn remove_object(
-            &self,
-            request: tonic::Request<super::RemoveObjectRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/AssocCount",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "AssocCount"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn query_objects(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QueryObjectsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RemoveObjectResponse>,
+            tonic::Response<super::QueryObjectsResponse>,
             tonic::Status,
-        >;
-        async fn create_association(
-            &self,
-            request: tonic::Request<super::CreateAssociationRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/QueryObjects",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "QueryObjects"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn query_associations(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QueryAssociationsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::CreateAssociationResponse>,
+            tonic::Response<super::QueryAssociationsResponse>,
             tonic::Status,
-        >;
-        async fn remove_association(
-            &self,
-            request: tonic::Request<super::RemoveAssociationRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/QueryAssociations",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "QueryAssociations"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn write(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WriteRequest>,
+        ) -> std::result::Result<tonic::Response<super::WriteResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/brother.Brother/Write");
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("brother.Brother", "Write"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn watch_objects(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchObjectsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::RemoveAssociationResponse>,
+            tonic::Response<tonic::codec::Streaming<super::ObjectChange>>,
             tonic::Status,
-        >;
-        async fn get_associations(
-            &self,
-            request: tonic::Request<super::GetAssociationsRequest>,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/WatchObjects",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "WatchObjects"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn watch_associations(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchAssociationsRequest>,
         ) -> std::result::Result<
-            tonic::Response<super::GetAssociationsResponse>,
+            tonic::Response<tonic::codec::Streaming<super::AssociationChange>>,
             tonic::Status,
-        >;
-    }
-    #[derive(Debug)]
-    pub struct BrotherServer<T> {
-        inner: Arc<T>,
-        accept_compression_encodings: EnabledCompressionEncodings,
-        send_compression_encodings: EnabledCompressionEncodings,
-        max_decoding_message_size: Option<usize>,
-        max_encoding_message_size: Option<usize>,
-    }
-    impl<T> BrotherServer<T> {
-        pub fn new(inner: T) -> Self {
-            Self::from_arc(Arc::new(inner))
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/WatchAssociations",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "WatchAssociations"));
+            self.inner.server_streaming(req, path, codec).await
         }
-        pub fn from_arc(inner: Arc<T>) -> Self {
-            Self {
-                inner,
-                accept_compression_encodings: Default::default(),
-                send_compression_encodings: Default::default(),
-                max_decoding_message_size: None,
-                max_encoding_message_size: None,
-            }
+        pub async fn put_schema(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PutSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PutSchemaResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/PutSchema",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("brother.Brother", "PutSchema"));
+            self.inner.unary(req, path, codec).await
         }
-        pub fn with_interceptor<F>(
-            inner: T,
-            interceptor: F,
-        ) -> InterceptedService<Self, F>
-        where
-            F: tonic::service::Interceptor,
-        {
-            InterceptedService::new(Self::new(inner), interceptor)
+        pub async fn get_schema(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSchemaResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/GetSchema",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut().insert(GrpcMethod::new("brother.Brother", "GetSchema"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Enable decompressing requests with the given encoding.
-        #[must_use]
-        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.accept_compression_encodings.enable(encoding);
-            self
+        pub async fn delete_schema(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeleteSchemaResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/DeleteSchema",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "DeleteSchema"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Compress responses with the given encoding, if the client supports it.
-        #[must_use]
-        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
-            self.send_compression_encodings.enable(encoding);
-            self
+        pub async fn list_schemas(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListSchemasRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSchemasResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/ListSchemas",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "ListSchemas"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of a decoded message.
-        ///
-        /// Default: `4MB`
-        #[must_use]
-        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
-            self.max_decoding_message_size = Some(limit);
-            self
+        pub async fn declare_unique_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeclareUniqueIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeclareUniqueIndexResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/DeclareUniqueIndex",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "DeclareUniqueIndex"));
+            self.inner.unary(req, path, codec).await
         }
-        /// Limits the maximum size of an encoded message.
-        ///
-        /// Default: `usize::MAX`
-        #[must_use]
-        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
-            self.max_encoding_message_size = Some(limit);
-            self
+        pub async fn drop_unique_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DropUniqueIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DropUniqueIndexResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/DropUniqueIndex",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "DropUniqueIndex"));
+            self.inner.unary(req, path, codec).await
         }
-    }
-    impl<T, B> tonic::codegen::Service<http::Request<B>> for BrotherServer<T>
-    where
-        T: Brother,
-        B: Body + std::marker::Send + 'static,
-        B::Error: Into<StdError> + std::marker::Send + 'static,
-    {
-        type Response = http::Response<tonic::body::Body>;
-        type Error = std::convert::Infallible;
-        type Future = BoxFuture<Self::Response, Self::Error>;
-        fn poll_ready(
+        pub async fn list_unique_indexes(
             &mut self,
-            _cx: &mut Context<'_>,
-        ) -> Poll<std::result::Result<(), Self::Error>> {
-            Poll::Ready(Ok(()))
+            request: impl tonic::IntoRequest<super::ListUniqueIndexesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListUniqueIndexesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/ListUniqueIndexes",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "ListUniqueIndexes"));
+            self.inner.unary(req, path, codec).await
         }
-        fn call(&mut self, req: http::Request<B>) -> Self::Future {
-            match req.uri().path() {
-                "/brother.Brother/GetObject" => {
+        pub async fn lookup_by_unique_key(
+            &mut self,
+            request: impl tonic::IntoRequest<super::LookupByUniqueKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::LookupByUniqueKeyResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/LookupByUniqueKey",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "LookupByUniqueKey"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn allocate_ids(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AllocateIdsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AllocateIdsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/AllocateIds",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "AllocateIds"));
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn export_tenant(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExportTenantRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ExportTenantResponse>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/ExportTenant",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "ExportTenant"));
+            self.inner.server_streaming(req, path, codec).await
+        }
+        pub async fn import_tenant(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<
+                Message = super::ImportTenantRequest,
+            >,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportTenantResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::unknown(
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/brother.Brother/ImportTenant",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("brother.Brother", "ImportTenant"));
+            self.inner.client_streaming(req, path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod brother_server {
+    #![allow(
+        unused_variables,
+        dead_code,
+        missing_docs,
+        clippy::wildcard_imports,
+        clippy::let_unit_value,
+    )]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with BrotherServer.
+    #[async_trait]
+    pub trait Brother: std::marker::Send + std::marker::Sync + 'static {
+        async fn get_object(
+            &self,
+            request: tonic::Request<super::GetObjectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetObjectResponse>,
+            tonic::Status,
+        >;
+        async fn put_object(
+            &self,
+            request: tonic::Request<super::PutObjectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PutObjectResponse>,
+            tonic::Status,
+        >;
+        async fn remove_object(
+            &self,
+            request: tonic::Request<super::RemoveObjectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RemoveObjectResponse>,
+            tonic::Status,
+        >;
+        async fn restore_object(
+            &self,
+            request: tonic::Request<super::RestoreObjectRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RestoreObjectResponse>,
+            tonic::Status,
+        >;
+        async fn get_object_history(
+            &self,
+            request: tonic::Request<super::GetObjectHistoryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetObjectHistoryResponse>,
+            tonic::Status,
+        >;
+        async fn batch_get_objects(
+            &self,
+            request: tonic::Request<super::BatchGetObjectsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchGetObjectsResponse>,
+            tonic::Status,
+        >;
+        async fn batch_put_objects(
+            &self,
+            request: tonic::Request<super::BatchPutObjectsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchPutObjectsResponse>,
+            tonic::Status,
+        >;
+        async fn create_association(
+            &self,
+            request: tonic::Request<super::CreateAssociationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateAssociationResponse>,
+            tonic::Status,
+        >;
+        async fn batch_create_associations(
+            &self,
+            request: tonic::Request<super::BatchCreateAssociationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::BatchCreateAssociationsResponse>,
+            tonic::Status,
+        >;
+        async fn remove_association(
+            &self,
+            request: tonic::Request<super::RemoveAssociationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RemoveAssociationResponse>,
+            tonic::Status,
+        >;
+        async fn get_associations(
+            &self,
+            request: tonic::Request<super::GetAssociationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetAssociationsResponse>,
+            tonic::Status,
+        >;
+        async fn assoc_count(
+            &self,
+            request: tonic::Request<super::AssocCountRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AssocCountResponse>,
+            tonic::Status,
+        >;
+        async fn query_objects(
+            &self,
+            request: tonic::Request<super::QueryObjectsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::QueryObjectsResponse>,
+            tonic::Status,
+        >;
+        async fn query_associations(
+            &self,
+            request: tonic::Request<super::QueryAssociationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::QueryAssociationsResponse>,
+            tonic::Status,
+        >;
+        async fn write(
+            &self,
+            request: tonic::Request<super::WriteRequest>,
+        ) -> std::result::Result<tonic::Response<super::WriteResponse>, tonic::Status>;
+        /// Server streaming response type for the WatchObjects method.
+        type WatchObjectsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::ObjectChange, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        async fn watch_objects(
+            &self,
+            request: tonic::Request<super::WatchObjectsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::WatchObjectsStream>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the WatchAssociations method.
+        type WatchAssociationsStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::AssociationChange, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        async fn watch_associations(
+            &self,
+            request: tonic::Request<super::WatchAssociationsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::WatchAssociationsStream>,
+            tonic::Status,
+        >;
+        async fn put_schema(
+            &self,
+            request: tonic::Request<super::PutSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PutSchemaResponse>,
+            tonic::Status,
+        >;
+        async fn get_schema(
+            &self,
+            request: tonic::Request<super::GetSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetSchemaResponse>,
+            tonic::Status,
+        >;
+        async fn delete_schema(
+            &self,
+            request: tonic::Request<super::DeleteSchemaRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeleteSchemaResponse>,
+            tonic::Status,
+        >;
+        async fn list_schemas(
+            &self,
+            request: tonic::Request<super::ListSchemasRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListSchemasResponse>,
+            tonic::Status,
+        >;
+        async fn declare_unique_index(
+            &self,
+            request: tonic::Request<super::DeclareUniqueIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DeclareUniqueIndexResponse>,
+            tonic::Status,
+        >;
+        async fn drop_unique_index(
+            &self,
+            request: tonic::Request<super::DropUniqueIndexRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::DropUniqueIndexResponse>,
+            tonic::Status,
+        >;
+        async fn list_unique_indexes(
+            &self,
+            request: tonic::Request<super::ListUniqueIndexesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListUniqueIndexesResponse>,
+            tonic::Status,
+        >;
+        async fn lookup_by_unique_key(
+            &self,
+            request: tonic::Request<super::LookupByUniqueKeyRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::LookupByUniqueKeyResponse>,
+            tonic::Status,
+        >;
+        async fn allocate_ids(
+            &self,
+            request: tonic::Request<super::AllocateIdsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::AllocateIdsResponse>,
+            tonic::Status,
+        >;
+        /// Server streaming response type for the ExportTenant method.
+        type ExportTenantStream: tonic::codegen::tokio_stream::Stream<
+                Item = std::result::Result<super::ExportTenantResponse, tonic::Status>,
+            >
+            + std::marker::Send
+            + 'static;
+        async fn export_tenant(
+            &self,
+            request: tonic::Request<super::ExportTenantRequest>,
+        ) -> std::result::Result<
+            tonic::Response<Self::ExportTenantStream>,
+            tonic::Status,
+        >;
+        async fn import_tenant(
+            &self,
+            request: tonic::Request<tonic::Streaming<super::ImportTenantRequest>>,
+        ) -> std::result::Result<
+            tonic::Response<super::ImportTenantResponse>,
+            tonic::Status,
+        >;
+    }
+    #[derive(Debug)]
+    pub struct BrotherServer<T> {
+        inner: Arc<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+        max_decoding_message_size: Option<usize>,
+        max_encoding_message_size: Option<usize>,
+    }
+    impl<T> BrotherServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+                max_decoding_message_size: None,
+                max_encoding_message_size: None,
+            }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+        /// Limits the maximum size of a decoded message.
+        ///
+        /// Default: `4MB`
+        #[must_use]
+        pub fn max_decoding_message_size(mut self, limit: usize) -> Self {
+            self.max_decoding_message_size = Some(limit);
+            self
+        }
+        /// Limits the maximum size of an encoded message.
+        ///
+        /// Default: `usize::MAX`
+        #[must_use]
+        pub fn max_encoding_message_size(mut self, limit: usize) -> Self {
+            self.max_encoding_message_size = Some(limit);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for BrotherServer<T>
+    where
+        T: Brother,
+        B: Body + std::marker::Send + 'static,
+        B::Error: Into<StdError> + std::marker::Send + 'static,
+    {
+        type Response = http::Response<tonic::body::Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(
+            &mut self,
+            _cx: &mut Context<'_>,
+        ) -> Poll<std::result::Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            match req.uri().path() {
+                "/brother.Brother/GetObject" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetObjectSvc<T: Brother>(pub Arc<T>);
+                    impl<T: Brother> tonic::server::UnaryService<super::GetObjectRequest>
+                    for GetObjectSvc<T> {
+                        type Response = super::GetObjectResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetObjectRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::get_object(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetObjectSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/PutObject" => {
+                    #[allow(non_camel_case_types)]
+                    struct PutObjectSvc<T: Brother>(pub Arc<T>);
+                    impl<T: Brother> tonic::server::UnaryService<super::PutObjectRequest>
+                    for PutObjectSvc<T> {
+                        type Response = super::PutObjectResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PutObjectRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::put_object(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = PutObjectSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/RemoveObject" => {
+                    #[allow(non_camel_case_types)]
+                    struct RemoveObjectSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::RemoveObjectRequest>
+                    for RemoveObjectSvc<T> {
+                        type Response = super::RemoveObjectResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RemoveObjectRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::remove_object(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RemoveObjectSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/RestoreObject" => {
+                    #[allow(non_camel_case_types)]
+                    struct RestoreObjectSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::RestoreObjectRequest>
+                    for RestoreObjectSvc<T> {
+                        type Response = super::RestoreObjectResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RestoreObjectRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::restore_object(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RestoreObjectSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/GetObjectHistory" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetObjectHistorySvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::GetObjectHistoryRequest>
+                    for GetObjectHistorySvc<T> {
+                        type Response = super::GetObjectHistoryResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetObjectHistoryRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::get_object_history(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetObjectHistorySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/BatchGetObjects" => {
+                    #[allow(non_camel_case_types)]
+                    struct BatchGetObjectsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::BatchGetObjectsRequest>
+                    for BatchGetObjectsSvc<T> {
+                        type Response = super::BatchGetObjectsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BatchGetObjectsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::batch_get_objects(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BatchGetObjectsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/BatchPutObjects" => {
+                    #[allow(non_camel_case_types)]
+                    struct BatchPutObjectsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::BatchPutObjectsRequest>
+                    for BatchPutObjectsSvc<T> {
+                        type Response = super::BatchPutObjectsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::BatchPutObjectsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::batch_put_objects(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BatchPutObjectsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/CreateAssociation" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateAssociationSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::CreateAssociationRequest>
+                    for CreateAssociationSvc<T> {
+                        type Response = super::CreateAssociationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateAssociationRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::create_association(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = CreateAssociationSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/BatchCreateAssociations" => {
+                    #[allow(non_camel_case_types)]
+                    struct BatchCreateAssociationsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::BatchCreateAssociationsRequest>
+                    for BatchCreateAssociationsSvc<T> {
+                        type Response = super::BatchCreateAssociationsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                super::BatchCreateAssociationsRequest,
+                            >,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::batch_create_associations(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = BatchCreateAssociationsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/RemoveAssociation" => {
+                    #[allow(non_camel_case_types)]
+                    struct RemoveAssociationSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::RemoveAssociationRequest>
+                    for RemoveAssociationSvc<T> {
+                        type Response = super::RemoveAssociationResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RemoveAssociationRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::remove_association(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = RemoveAssociationSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/GetAssociations" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetAssociationsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::GetAssociationsRequest>
+                    for GetAssociationsSvc<T> {
+                        type Response = super::GetAssociationsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetAssociationsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::get_associations(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = GetAssociationsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/AssocCount" => {
+                    #[allow(non_camel_case_types)]
+                    struct AssocCountSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::AssocCountRequest>
+                    for AssocCountSvc<T> {
+                        type Response = super::AssocCountResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AssocCountRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::assoc_count(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = AssocCountSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/QueryObjects" => {
+                    #[allow(non_camel_case_types)]
+                    struct QueryObjectsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::QueryObjectsRequest>
+                    for QueryObjectsSvc<T> {
+                        type Response = super::QueryObjectsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QueryObjectsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::query_objects(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = QueryObjectsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/QueryAssociations" => {
+                    #[allow(non_camel_case_types)]
+                    struct QueryAssociationsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::QueryAssociationsRequest>
+                    for QueryAssociationsSvc<T> {
+                        type Response = super::QueryAssociationsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::QueryAssociationsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::query_associations(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = QueryAssociationsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/Write" => {
+                    #[allow(non_camel_case_types)]
+                    struct WriteSvc<T: Brother>(pub Arc<T>);
+                    impl<T: Brother> tonic::server::UnaryService<super::WriteRequest>
+                    for WriteSvc<T> {
+                        type Response = super::WriteResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WriteRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::write(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WriteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/WatchObjects" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchObjectsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::ServerStreamingService<super::WatchObjectsRequest>
+                    for WatchObjectsSvc<T> {
+                        type Response = super::ObjectChange;
+                        type ResponseStream = T::WatchObjectsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchObjectsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::watch_objects(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WatchObjectsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/WatchAssociations" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchAssociationsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::ServerStreamingService<
+                        super::WatchAssociationsRequest,
+                    > for WatchAssociationsSvc<T> {
+                        type Response = super::AssociationChange;
+                        type ResponseStream = T::WatchAssociationsStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchAssociationsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::watch_associations(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = WatchAssociationsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/PutSchema" => {
                     #[allow(non_camel_case_types)]
-                    struct GetObjectSvc<T: Brother>(pub Arc<T>);
-                    impl<T: Brother> tonic::server::UnaryService<super::GetObjectRequest>
-                    for GetObjectSvc<T> {
-                        type Response = super::GetObjectResponse;
+                    struct PutSchemaSvc<T: Brother>(pub Arc<T>);
+                    impl<T: Brother> tonic::server::UnaryService<super::PutSchemaRequest>
+                    for PutSchemaSvc<T> {
+                        type Response = super::PutSchemaResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetObjectRequest>,
+                            request: tonic::Request<super::PutSchemaRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Brother>::get_object(&inner, request).await
+                                <T as Brother>::put_schema(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -512,7 +2838,7 @@ pub mod brother_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetObjectSvc(inner);
+                        let method = PutSchemaSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -528,23 +2854,23 @@ pub mod brother_server {
                     };
                     Box::pin(fut)
                 }
-                "/brother.Brother/PutObject" => {
+                "/brother.Brother/GetSchema" => {
                     #[allow(non_camel_case_types)]
-                    struct PutObjectSvc<T: Brother>(pub Arc<T>);
-                    impl<T: Brother> tonic::server::UnaryService<super::PutObjectRequest>
-                    for PutObjectSvc<T> {
-                        type Response = super::PutObjectResponse;
+                    struct GetSchemaSvc<T: Brother>(pub Arc<T>);
+                    impl<T: Brother> tonic::server::UnaryService<super::GetSchemaRequest>
+                    for GetSchemaSvc<T> {
+                        type Response = super::GetSchemaResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::PutObjectRequest>,
+                            request: tonic::Request<super::GetSchemaRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Brother>::put_object(&inner, request).await
+                                <T as Brother>::get_schema(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -555,7 +2881,7 @@ pub mod brother_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = PutObjectSvc(inner);
+                        let method = GetSchemaSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -571,25 +2897,25 @@ pub mod brother_server {
                     };
                     Box::pin(fut)
                 }
-                "/brother.Brother/RemoveObject" => {
+                "/brother.Brother/DeleteSchema" => {
                     #[allow(non_camel_case_types)]
-                    struct RemoveObjectSvc<T: Brother>(pub Arc<T>);
+                    struct DeleteSchemaSvc<T: Brother>(pub Arc<T>);
                     impl<
                         T: Brother,
-                    > tonic::server::UnaryService<super::RemoveObjectRequest>
-                    for RemoveObjectSvc<T> {
-                        type Response = super::RemoveObjectResponse;
+                    > tonic::server::UnaryService<super::DeleteSchemaRequest>
+                    for DeleteSchemaSvc<T> {
+                        type Response = super::DeleteSchemaResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::RemoveObjectRequest>,
+                            request: tonic::Request<super::DeleteSchemaRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Brother>::remove_object(&inner, request).await
+                                <T as Brother>::delete_schema(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -600,7 +2926,7 @@ pub mod brother_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = RemoveObjectSvc(inner);
+                        let method = DeleteSchemaSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -616,25 +2942,25 @@ pub mod brother_server {
                     };
                     Box::pin(fut)
                 }
-                "/brother.Brother/CreateAssociation" => {
+                "/brother.Brother/ListSchemas" => {
                     #[allow(non_camel_case_types)]
-                    struct CreateAssociationSvc<T: Brother>(pub Arc<T>);
+                    struct ListSchemasSvc<T: Brother>(pub Arc<T>);
                     impl<
                         T: Brother,
-                    > tonic::server::UnaryService<super::CreateAssociationRequest>
-                    for CreateAssociationSvc<T> {
-                        type Response = super::CreateAssociationResponse;
+                    > tonic::server::UnaryService<super::ListSchemasRequest>
+                    for ListSchemasSvc<T> {
+                        type Response = super::ListSchemasResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::CreateAssociationRequest>,
+                            request: tonic::Request<super::ListSchemasRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Brother>::create_association(&inner, request).await
+                                <T as Brother>::list_schemas(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -645,7 +2971,7 @@ pub mod brother_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = CreateAssociationSvc(inner);
+                        let method = ListSchemasSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -661,25 +2987,25 @@ pub mod brother_server {
                     };
                     Box::pin(fut)
                 }
-                "/brother.Brother/RemoveAssociation" => {
+                "/brother.Brother/DeclareUniqueIndex" => {
                     #[allow(non_camel_case_types)]
-                    struct RemoveAssociationSvc<T: Brother>(pub Arc<T>);
+                    struct DeclareUniqueIndexSvc<T: Brother>(pub Arc<T>);
                     impl<
                         T: Brother,
-                    > tonic::server::UnaryService<super::RemoveAssociationRequest>
-                    for RemoveAssociationSvc<T> {
-                        type Response = super::RemoveAssociationResponse;
+                    > tonic::server::UnaryService<super::DeclareUniqueIndexRequest>
+                    for DeclareUniqueIndexSvc<T> {
+                        type Response = super::DeclareUniqueIndexResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::RemoveAssociationRequest>,
+                            request: tonic::Request<super::DeclareUniqueIndexRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Brother>::remove_association(&inner, request).await
+                                <T as Brother>::declare_unique_index(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -690,7 +3016,7 @@ pub mod brother_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = RemoveAssociationSvc(inner);
+                        let method = DeclareUniqueIndexSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -706,25 +3032,25 @@ pub mod brother_server {
                     };
                     Box::pin(fut)
                 }
-                "/brother.Brother/GetAssociations" => {
+                "/brother.Brother/DropUniqueIndex" => {
                     #[allow(non_camel_case_types)]
-                    struct GetAssociationsSvc<T: Brother>(pub Arc<T>);
+                    struct DropUniqueIndexSvc<T: Brother>(pub Arc<T>);
                     impl<
                         T: Brother,
-                    > tonic::server::UnaryService<super::GetAssociationsRequest>
-                    for GetAssociationsSvc<T> {
-                        type Response = super::GetAssociationsResponse;
+                    > tonic::server::UnaryService<super::DropUniqueIndexRequest>
+                    for DropUniqueIndexSvc<T> {
+                        type Response = super::DropUniqueIndexResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::GetAssociationsRequest>,
+                            request: tonic::Request<super::DropUniqueIndexRequest>,
                         ) -> Self::Future {
                             let inner = Arc::clone(&self.0);
                             let fut = async move {
-                                <T as Brother>::get_associations(&inner, request).await
+                                <T as Brother>::drop_unique_index(&inner, request).await
                             };
                             Box::pin(fut)
                         }
@@ -735,7 +3061,142 @@ pub mod brother_server {
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
                     let fut = async move {
-                        let method = GetAssociationsSvc(inner);
+                        let method = DropUniqueIndexSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/ListUniqueIndexes" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListUniqueIndexesSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::ListUniqueIndexesRequest>
+                    for ListUniqueIndexesSvc<T> {
+                        type Response = super::ListUniqueIndexesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListUniqueIndexesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::list_unique_indexes(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ListUniqueIndexesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/LookupByUniqueKey" => {
+                    #[allow(non_camel_case_types)]
+                    struct LookupByUniqueKeySvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::LookupByUniqueKeyRequest>
+                    for LookupByUniqueKeySvc<T> {
+                        type Response = super::LookupByUniqueKeyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LookupByUniqueKeyRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::lookup_by_unique_key(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = LookupByUniqueKeySvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/AllocateIds" => {
+                    #[allow(non_camel_case_types)]
+                    struct AllocateIdsSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::UnaryService<super::AllocateIdsRequest>
+                    for AllocateIdsSvc<T> {
+                        type Response = super::AllocateIdsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AllocateIdsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::allocate_ids(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = AllocateIdsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -751,6 +3212,99 @@ pub mod brother_server {
                     };
                     Box::pin(fut)
                 }
+                "/brother.Brother/ExportTenant" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExportTenantSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::ServerStreamingService<super::ExportTenantRequest>
+                    for ExportTenantSvc<T> {
+                        type Response = super::ExportTenantResponse;
+                        type ResponseStream = T::ExportTenantStream;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::ResponseStream>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExportTenantRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::export_tenant(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ExportTenantSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/brother.Brother/ImportTenant" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportTenantSvc<T: Brother>(pub Arc<T>);
+                    impl<
+                        T: Brother,
+                    > tonic::server::ClientStreamingService<super::ImportTenantRequest>
+                    for ImportTenantSvc<T> {
+                        type Response = super::ImportTenantResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<
+                                tonic::Streaming<super::ImportTenantRequest>,
+                            >,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as Brother>::import_tenant(&inner, request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let method = ImportTenantSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.client_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         let mut response = http::Response::new(
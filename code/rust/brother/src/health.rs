@@ -0,0 +1,65 @@
+//! src/health.rs
+//! `grpc.health.v1` status of the brother binary.
+//!
+//! The overall status (`""`) and `brother.Brother` follow the database: a
+//! probe runs `SELECT 1` on the pool every interval and reports
+//! NOT_SERVING while that fails or takes longer than the interval.  Once
+//! shutdown starts both stay NOT_SERVING, so load balancers drain first.
+
+use std::time::Duration;
+
+use brother::pb::brother_server::BrotherServer;
+use tokio::task::JoinHandle;
+use tonic::server::NamedService;
+use tonic_health::{server::HealthReporter, ServingStatus};
+use tracing::{info, warn};
+
+use crate::db::PgPool;
+use crate::service::BrotherService;
+use crate::watch::{self, Shutdown};
+
+/// Probe the pool every `every` until `stop`.
+pub fn spawn_probe(
+    db: PgPool,
+    reporter: HealthReporter,
+    every: Duration,
+    mut stop: Shutdown,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(every);
+        let mut last = None;
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {}
+                _ = watch::stopping(&mut stop) => {
+                    set(&reporter, ServingStatus::NotServing).await;
+                    return;
+                }
+            }
+
+            let status = match tokio::time::timeout(every, sqlx::query("SELECT 1").execute(&db)).await {
+                Ok(Ok(_)) => ServingStatus::Serving,
+                Ok(Err(e)) => {
+                    warn!("health probe failed: {e}");
+                    ServingStatus::NotServing
+                }
+                Err(_) => {
+                    warn!("health probe timed out after {every:?}");
+                    ServingStatus::NotServing
+                }
+            };
+            if last != Some(status) {
+                info!(?status, "health");
+                set(&reporter, status).await;
+                last = Some(status);
+            }
+        }
+    })
+}
+
+async fn set(reporter: &HealthReporter, status: ServingStatus) {
+    reporter.set_service_status("", status).await;
+    reporter
+        .set_service_status(BrotherServer::<BrotherService>::NAME, status)
+        .await;
+}
@@ -0,0 +1,711 @@
+//! src/idempotency.rs
+//! `idempotency-key` metadata on the mutating RPCs.
+//!
+//! The first call with a key claims it in `tao.idempotency_keys` (or in
+//! process, for a service without Postgres), runs and stores its
+//! response; a later call with the same key and the same request gets
+//! that response back verbatim, flagged by `idempotent-replay: true`
+//! metadata.  Reusing a key for another request fails with
+//! INVALID_ARGUMENT.  Keys are per tenant and live for the window.
+//!
+//! The response is stored right after the write, not with it, so a key
+//! is only ever given up while its write cannot have happened.  The
+//! holder marks the key `writing` just before [`Claim::write`] runs the
+//! write; until then a failed call releases the key and one that died is
+//! taken over after [`ABANDONED`], and the retry runs for real.  After,
+//! only a write that definitely failed releases it.  A key left writing
+//! without a response – the call died, was cancelled or lost the database
+//! mid-write – fails its retries with FAILED_PRECONDITION: the outcome is
+//! unknown and running again could repeat the write.  Each claim carries
+//! a random holder token, so a call that was taken over cannot mark,
+//! finish or release the key of the call that took it.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use prost::Message;
+use sqlx::Row;
+use tonic::{metadata::MetadataValue, Code, Request, Response, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+use tracing::warn;
+
+use crate::auth::{self, Tenant};
+use crate::db::{db_err, PgPool};
+use crate::maintenance;
+
+/// Request metadata carrying the key.
+pub const HEADER: &str = "idempotency-key";
+/// Response metadata set on a replay.
+const REPLAY_HEADER: &str = "idempotent-replay";
+const MAX_KEY_LEN: usize = 255;
+/// How long a key is remembered unless configured otherwise.
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(24 * 3600);
+/// A claim this old that has not begun its write belongs to a call that
+/// died, or that will find it taken over when it gets there.
+const ABANDONED: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+pub struct Idempotency {
+    keys: Keys,
+    window: Duration,
+}
+
+/// Where claims live.
+#[derive(Clone)]
+enum Keys {
+    Pg(Arc<PgPool>),
+    /// In process, for a service without Postgres.
+    Memory(Arc<Mutex<HashMap<(Tenant, String), Kept>>>),
+}
+
+/// A claim kept in process: a row of `tao.idempotency_keys`.
+struct Kept {
+    method: String,
+    request: Vec<u8>,
+    response: Option<Vec<u8>>,
+    holder: i64,
+    writing: bool,
+    created: Instant,
+}
+
+/// A claim found under a key that was not free.
+struct Found {
+    method: String,
+    request: Vec<u8>,
+    response: Option<Vec<u8>>,
+    writing: bool,
+    abandoned: bool,
+}
+
+/// Outcome of [`Idempotency::claim`].
+pub enum Claimed<T> {
+    /// A duplicate: hand this back as is.
+    Replay(Response<T>),
+    /// Run the call, then pass its response through [`Claim::finish`].
+    Run(Claim),
+}
+
+/// A key held by a running call.  Dropped without `finish` (the call
+/// failed), it is released unless its write may have happened.
+pub struct Claim {
+    keys: Keys,
+    /// Unset when the caller sent no key.
+    key: Option<(Tenant, String)>,
+    /// This call's token in the row's `holder`.
+    holder: i64,
+    /// The write has started and not definitely failed.
+    writing: bool,
+}
+
+impl Idempotency {
+    pub fn new(db: Arc<PgPool>) -> Self {
+        Self {
+            keys: Keys::Pg(db),
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    /// Keys held in process, lost on restart.
+    pub fn in_memory() -> Self {
+        Self {
+            keys: Keys::Memory(Arc::default()),
+            window: DEFAULT_WINDOW,
+        }
+    }
+
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Claim the request's key for `method`, or find the call it repeats.
+    pub async fn claim<Req, Resp>(
+        &self,
+        method: &str,
+        req: &Request<Req>,
+    ) -> Result<Claimed<Resp>, Status>
+    where
+        Req: Message + Default + PartialEq,
+        Resp: Message + Default,
+    {
+        let Some(key) = req.metadata().get(HEADER) else {
+            return Ok(Claimed::Run(self.held(None, 0)));
+        };
+        let key = key
+            .to_str()
+            .ok()
+            .filter(|k| !k.is_empty() && k.len() <= MAX_KEY_LEN)
+            .ok_or_else(|| {
+                Status::invalid_argument(format!(
+                    "{HEADER} must be 1..={MAX_KEY_LEN} visible ASCII characters"
+                ))
+            })?
+            .to_owned();
+        let tenant = auth::tenant(req)?;
+        let request = req.get_ref().encode_to_vec();
+        let holder: i64 = rand::random();
+
+        // The first call may give the key up between our two statements;
+        // then it is free again.
+        for _ in 0..3 {
+            // New, or outlived its window: ours.
+            if self.keys.insert(tenant, &key, method, &request, holder, self.window).await? {
+                return Ok(Claimed::Run(self.held(Some((tenant, key)), holder)));
+            }
+            let Some(found) = self.keys.find(tenant, &key).await? else {
+                continue;
+            };
+
+            // Compared decoded: map fields encode in no fixed order.
+            let same = found.method == method
+                && Req::decode(&*found.request).ok().as_ref() == Some(req.get_ref());
+            if !same {
+                return Err(reused(&key));
+            }
+
+            if let Some(bytes) = found.response {
+                let body = Resp::decode(&*bytes).map_err(|e| {
+                    tracing::error!("stored response for {method} does not decode: {e}");
+                    Status::internal("stored response is unreadable")
+                })?;
+                let mut resp = Response::new(body);
+                resp.metadata_mut()
+                    .insert(REPLAY_HEADER, MetadataValue::from_static("true"));
+                return Ok(Claimed::Replay(resp));
+            }
+
+            if found.abandoned && found.writing {
+                return Err(outcome_unknown(&key));
+            }
+            // Died before its write: nothing to repeat.
+            if found.abandoned && self.keys.take_over(tenant, &key, holder).await? {
+                return Ok(Claimed::Run(self.held(Some((tenant, key)), holder)));
+            }
+            return Err(in_progress(&key));
+        }
+        Err(in_progress(&key))
+    }
+
+    fn held(&self, key: Option<(Tenant, String)>, holder: i64) -> Claim {
+        Claim {
+            keys: self.keys.clone(),
+            key,
+            holder,
+            writing: false,
+        }
+    }
+}
+
+impl Keys {
+    fn memory(
+        keys: &Mutex<HashMap<(Tenant, String), Kept>>,
+    ) -> MutexGuard<'_, HashMap<(Tenant, String), Kept>> {
+        keys.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Claim `key` if it is free or has outlived `window`.
+    async fn insert(
+        &self,
+        tenant: Tenant,
+        key: &str,
+        method: &str,
+        request: &[u8],
+        holder: i64,
+        window: Duration,
+    ) -> Result<bool, Status> {
+        match self {
+            Keys::Pg(db) => {
+                let claimed = sqlx::query(
+                    r#"
+                    INSERT INTO tao.idempotency_keys (tenant, key, method, request, holder)
+                         VALUES ($1, $2, $3, $4, $6)
+                    ON CONFLICT ON CONSTRAINT idempotency_keys_pk DO UPDATE
+                            SET method     = EXCLUDED.method,
+                                request    = EXCLUDED.request,
+                                response   = NULL,
+                                holder     = EXCLUDED.holder,
+                                writing    = FALSE,
+                                created_at = now()
+                          WHERE idempotency_keys.created_at < now() - $5
+                    RETURNING 1
+                    "#,
+                )
+                .bind(tenant.db())
+                .bind(key)
+                .bind(method)
+                .bind(request)
+                .bind(maintenance::interval(window))
+                .bind(holder)
+                .fetch_optional(&**db)
+                .await
+                .map_err(db_err)?;
+                Ok(claimed.is_some())
+            }
+            Keys::Memory(keys) => {
+                let mut keys = Self::memory(keys);
+                let slot = (tenant, key.to_owned());
+                if keys.get(&slot).is_some_and(|k| k.created.elapsed() < window) {
+                    return Ok(false);
+                }
+                let kept = Kept {
+                    method: method.to_owned(),
+                    request: request.to_vec(),
+                    response: None,
+                    holder,
+                    writing: false,
+                    created: Instant::now(),
+                };
+                keys.insert(slot, kept);
+                Ok(true)
+            }
+        }
+    }
+
+    /// The claim on `key`, if any.
+    async fn find(&self, tenant: Tenant, key: &str) -> Result<Option<Found>, Status> {
+        match self {
+            Keys::Pg(db) => {
+                let row = sqlx::query(
+                    r#"
+                    SELECT method, request, response, writing,
+                           created_at < now() - $3 AS abandoned
+                      FROM tao.idempotency_keys
+                     WHERE tenant = $1 AND key = $2
+                    "#,
+                )
+                .bind(tenant.db())
+                .bind(key)
+                .bind(maintenance::interval(ABANDONED))
+                .fetch_optional(&**db)
+                .await
+                .map_err(db_err)?;
+                Ok(row.map(|row| Found {
+                    method: row.get("method"),
+                    request: row.get("request"),
+                    response: row.get("response"),
+                    writing: row.get("writing"),
+                    abandoned: row.get("abandoned"),
+                }))
+            }
+            Keys::Memory(keys) => {
+                let keys = Self::memory(keys);
+                Ok(keys.get(&(tenant, key.to_owned())).map(|k| Found {
+                    method: k.method.clone(),
+                    request: k.request.clone(),
+                    response: k.response.clone(),
+                    writing: k.writing,
+                    abandoned: k.created.elapsed() >= ABANDONED,
+                }))
+            }
+        }
+    }
+
+    /// Move an abandoned claim that never began its write to `holder`.
+    async fn take_over(&self, tenant: Tenant, key: &str, holder: i64) -> Result<bool, Status> {
+        match self {
+            Keys::Pg(db) => {
+                let taken = sqlx::query(
+                    r#"
+                    UPDATE tao.idempotency_keys
+                       SET holder = $4, created_at = now()
+                     WHERE tenant = $1 AND key = $2
+                       AND response IS NULL AND NOT writing
+                       AND created_at < now() - $3
+                    "#,
+                )
+                .bind(tenant.db())
+                .bind(key)
+                .bind(maintenance::interval(ABANDONED))
+                .bind(holder)
+                .execute(&**db)
+                .await
+                .map_err(db_err)?;
+                Ok(taken.rows_affected() == 1)
+            }
+            Keys::Memory(keys) => {
+                let mut keys = Self::memory(keys);
+                let Some(k) = keys.get_mut(&(tenant, key.to_owned())) else {
+                    return Ok(false);
+                };
+                if k.response.is_some() || k.writing || k.created.elapsed() < ABANDONED {
+                    return Ok(false);
+                }
+                k.holder = holder;
+                k.created = Instant::now();
+                Ok(true)
+            }
+        }
+    }
+
+    /// Mark `holder`'s claim writing; `false` if it is no longer theirs.
+    async fn mark(&self, tenant: Tenant, key: &str, holder: i64) -> Result<bool, Status> {
+        match self {
+            Keys::Pg(db) => {
+                let marked = sqlx::query(
+                    r#"
+                    UPDATE tao.idempotency_keys SET writing = TRUE
+                     WHERE tenant = $1 AND key = $2 AND holder = $3 AND response IS NULL
+                    "#,
+                )
+                .bind(tenant.db())
+                .bind(key)
+                .bind(holder)
+                .execute(&**db)
+                .await
+                .map_err(db_err)?;
+                Ok(marked.rows_affected() == 1)
+            }
+            Keys::Memory(keys) => {
+                let mut keys = Self::memory(keys);
+                match keys.get_mut(&(tenant, key.to_owned())) {
+                    Some(k) if k.holder == holder && k.response.is_none() => {
+                        k.writing = true;
+                        Ok(true)
+                    }
+                    _ => Ok(false),
+                }
+            }
+        }
+    }
+
+    /// Store the response on `holder`'s claim.
+    async fn store(&self, tenant: Tenant, key: &str, holder: i64, response: Vec<u8>) -> Result<(), sqlx::Error> {
+        match self {
+            Keys::Pg(db) => {
+                sqlx::query(
+                    r#"
+                    UPDATE tao.idempotency_keys SET response = $4
+                     WHERE tenant = $1 AND key = $2 AND holder = $3
+                    "#,
+                )
+                .bind(tenant.db())
+                .bind(key)
+                .bind(holder)
+                .bind(response)
+                .execute(&**db)
+                .await?;
+            }
+            Keys::Memory(keys) => {
+                let mut keys = Self::memory(keys);
+                if let Some(k) = keys.get_mut(&(tenant, key.to_owned())) {
+                    if k.holder == holder {
+                        k.response = Some(response);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Give up `holder`'s claim unless it has a response.  `writing` is
+    /// only set by the holder; unset, nothing was written.
+    async fn release(&self, tenant: Tenant, key: &str, holder: i64) -> Result<(), sqlx::Error> {
+        match self {
+            Keys::Pg(db) => {
+                sqlx::query(
+                    r#"DELETE FROM tao.idempotency_keys
+                        WHERE tenant = $1 AND key = $2 AND holder = $3 AND response IS NULL"#,
+                )
+                .bind(tenant.db())
+                .bind(key)
+                .bind(holder)
+                .execute(&**db)
+                .await?;
+            }
+            Keys::Memory(keys) => {
+                let mut keys = Self::memory(keys);
+                let slot = (tenant, key.to_owned());
+                if keys.get(&slot).is_some_and(|k| k.holder == holder && k.response.is_none()) {
+                    keys.remove(&slot);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Claim {
+    /// Run the call's write.  The key is marked first, so from here on it
+    /// is never given up unless `write` definitely did not happen.  Fails
+    /// with ABORTED, without running `write`, if the key was taken over.
+    pub async fn write<T>(
+        &mut self,
+        write: impl Future<Output = Result<T, Status>>,
+    ) -> Result<T, Status> {
+        if let Some((tenant, key)) = &self.key {
+            if !self.keys.mark(*tenant, key, self.holder).await? {
+                let key = self.key.take().map(|(_, key)| key).unwrap_or_default();
+                return Err(in_progress(&key));
+            }
+            self.writing = true;
+        }
+        let result = write.await;
+        if let Err(e) = &result {
+            self.writing = !failed_for_sure(e.code());
+        }
+        result
+    }
+
+    /// Store `resp` under the key and hand it on.
+    pub async fn finish<T: Message>(mut self, resp: Response<T>) -> Result<Response<T>, Status> {
+        let Some((tenant, key)) = self.key.take() else {
+            return Ok(resp);
+        };
+        // The write has happened: failing here would invite a retry that
+        // repeats it.  Unstored, the key stays writing and is never rerun.
+        let response = resp.get_ref().encode_to_vec();
+        if let Err(e) = self.keys.store(tenant, &key, self.holder, response).await {
+            warn!(key, "storing idempotent response failed: {e}");
+        }
+        Ok(resp)
+    }
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        let Some((tenant, key)) = self.key.take() else {
+            return;
+        };
+        if self.writing {
+            warn!(key, "call ended mid-write; its idempotency key is kept");
+            return;
+        }
+        let (keys, holder) = (self.keys.clone(), self.holder);
+        tokio::spawn(async move {
+            if let Err(e) = keys.release(tenant, &key, holder).await {
+                warn!(key, "releasing idempotency key failed: {e}");
+            }
+        });
+    }
+}
+
+fn reused(key: &str) -> Status {
+    Status::with_error_details(
+        Code::InvalidArgument,
+        "idempotency key was used for a different request",
+        ErrorDetails::with_error_info(
+            "IDEMPOTENCY_KEY_REUSED",
+            "brother",
+            HashMap::from([("key".to_owned(), key.to_owned())]),
+        ),
+    )
+}
+
+/// Whether a write that failed with `code` certainly did not commit: the
+/// codes a rejected statement or a failed check comes back with.  Any
+/// other – a lost connection, a cancelled call, a blown deadline, an
+/// internal error – may have left it committed.
+fn failed_for_sure(code: Code) -> bool {
+    matches!(
+        code,
+        Code::Aborted
+            | Code::FailedPrecondition
+            | Code::InvalidArgument
+            | Code::AlreadyExists
+            | Code::NotFound
+            | Code::PermissionDenied
+            | Code::OutOfRange
+    )
+}
+
+/// `FAILED_PRECONDITION`: an earlier call with the key started its write
+/// and never stored a response.  Retrying would not tell either.
+fn outcome_unknown(key: &str) -> Status {
+    Status::with_error_details(
+        Code::FailedPrecondition,
+        "an earlier request with this idempotency key may have been applied; \
+         check before retrying under a new key",
+        ErrorDetails::with_error_info(
+            "IDEMPOTENCY_OUTCOME_UNKNOWN",
+            "brother",
+            HashMap::from([("key".to_owned(), key.to_owned())]),
+        ),
+    )
+}
+
+/// `ABORTED`: retrying later gets the first call's response.
+fn in_progress(key: &str) -> Status {
+    Status::with_error_details(
+        Code::Aborted,
+        "a request with this idempotency key is still running",
+        ErrorDetails::with_error_info(
+            "IDEMPOTENCY_KEY_IN_USE",
+            "brother",
+            HashMap::from([("key".to_owned(), key.to_owned())]),
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering::SeqCst};
+
+    use brother::pb::{RemoveObjectRequest, RemoveObjectResponse};
+
+    use super::*;
+    use crate::db;
+
+    fn req(tenant: Tenant, key: &str) -> Request<RemoveObjectRequest> {
+        let mut req = Request::new(RemoveObjectRequest { otype: 1, id: 7 });
+        req.extensions_mut().insert(tenant);
+        req.metadata_mut().insert(HEADER, key.parse().unwrap());
+        req
+    }
+
+    async fn claim(
+        idem: &Idempotency,
+        tenant: Tenant,
+        key: &str,
+    ) -> Result<Claimed<RemoveObjectResponse>, Status> {
+        idem.claim("RemoveObject", &req(tenant, key)).await
+    }
+
+    fn run(claimed: Result<Claimed<RemoveObjectResponse>, Status>) -> Claim {
+        match claimed {
+            Ok(Claimed::Run(claim)) => claim,
+            Ok(Claimed::Replay(_)) => panic!("replayed"),
+            Err(e) => panic!("{e:?}"),
+        }
+    }
+
+    /// Pretend the claim's call went quiet long ago.
+    async fn age(pool: &PgPool, tenant: Tenant, key: &str) {
+        sqlx::query(
+            r#"UPDATE tao.idempotency_keys SET created_at = now() - interval '1 hour'
+                WHERE tenant = $1 AND key = $2"#,
+        )
+        .bind(tenant.db())
+        .bind(key)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    /// Drop releases in the background.
+    async fn released(idem: &Idempotency, tenant: Tenant, key: &str) -> Claim {
+        for _ in 0..50 {
+            if let Ok(Claimed::Run(claim)) = claim(idem, tenant, key).await {
+                return claim;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("key was not released");
+    }
+
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn replays_the_stored_response() {
+        let pool = db::test_pool().await;
+        let idem = Idempotency::new(Arc::new(pool));
+        let tenant = db::test_tenant();
+
+        let mut first = run(claim(&idem, tenant, "k").await);
+        let success = first.write(async { Ok(true) }).await.unwrap();
+        first.finish(Response::new(RemoveObjectResponse { success })).await.unwrap();
+
+        match claim(&idem, tenant, "k").await.unwrap() {
+            Claimed::Replay(resp) => {
+                assert!(resp.get_ref().success);
+                assert_eq!(resp.metadata().get(REPLAY_HEADER).unwrap(), "true");
+            }
+            Claimed::Run(_) => panic!("ran twice"),
+        }
+        let mut other = req(tenant, "k");
+        other.get_mut().id = 8;
+        let status = idem
+            .claim::<_, RemoveObjectResponse>("RemoveObject", &other)
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(status.code(), Code::InvalidArgument);
+    }
+
+    #[test]
+    fn only_rejections_fail_for_sure() {
+        for code in [Code::Aborted, Code::FailedPrecondition, Code::InvalidArgument, Code::NotFound] {
+            assert!(failed_for_sure(code), "{code:?}");
+        }
+        for code in [Code::Unavailable, Code::Cancelled, Code::DeadlineExceeded, Code::Unknown, Code::Internal, Code::ResourceExhausted] {
+            assert!(!failed_for_sure(code), "{code:?}");
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn a_write_that_failed_for_sure_releases_the_key() {
+        let pool = db::test_pool().await;
+        let idem = Idempotency::new(Arc::new(pool));
+        let tenant = db::test_tenant();
+
+        let mut first = run(claim(&idem, tenant, "k").await);
+        let failed = first.write(async { Err::<(), _>(Status::aborted("version clash")) });
+        assert_eq!(failed.await.unwrap_err().code(), Code::Aborted);
+        drop(first);
+
+        released(&idem, tenant, "k").await;
+    }
+
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn a_call_that_ends_mid_write_keeps_the_key() {
+        let pool = db::test_pool().await;
+        let idem = Idempotency::new(Arc::new(pool.clone()));
+        let tenant = db::test_tenant();
+
+        // The connection went away: the write may have committed.
+        let mut lost = run(claim(&idem, tenant, "lost").await);
+        let failed = lost.write(async { Err::<(), _>(Status::unavailable("database unavailable")) });
+        assert_eq!(failed.await.unwrap_err().code(), Code::Unavailable);
+        drop(lost);
+
+        // Cancelled (client gone, deadline) between the write and finish.
+        let mut cancelled = run(claim(&idem, tenant, "cancelled").await);
+        let write = cancelled.write(std::future::pending::<Result<(), Status>>());
+        assert!(tokio::time::timeout(Duration::from_millis(50), write).await.is_err());
+        drop(cancelled);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        for key in ["lost", "cancelled"] {
+            let status = claim(&idem, tenant, key).await.err().unwrap();
+            assert_eq!(status.code(), Code::Aborted, "{key} is still running");
+            age(&pool, tenant, key).await;
+            let status = claim(&idem, tenant, key).await.err().unwrap();
+            assert_eq!(status.code(), Code::FailedPrecondition, "{key} must not run again");
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn a_taken_over_call_does_not_write() {
+        let pool = db::test_pool().await;
+        let idem = Idempotency::new(Arc::new(pool.clone()));
+        let tenant = db::test_tenant();
+
+        let mut slow = run(claim(&idem, tenant, "k").await);
+        let status = claim(&idem, tenant, "k").await.err().unwrap();
+        assert_eq!(status.code(), Code::Aborted);
+
+        age(&pool, tenant, "k").await;
+        let mut retry = run(claim(&idem, tenant, "k").await);
+
+        let wrote = AtomicBool::new(false);
+        let status = slow
+            .write(async {
+                wrote.store(true, SeqCst);
+                Ok(true)
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Aborted);
+        assert!(!wrote.load(SeqCst));
+        drop(slow);
+
+        let success = retry.write(async { Ok(true) }).await.unwrap();
+        retry.finish(Response::new(RemoveObjectResponse { success })).await.unwrap();
+        assert!(matches!(claim(&idem, tenant, "k").await, Ok(Claimed::Replay(_))));
+    }
+}
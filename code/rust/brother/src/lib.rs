@@ -3,4 +3,8 @@
 pub mod pb {
     // Path is relative to *this* file.
     include!("generated/brother.rs");
+
+    /// Encoded `FileDescriptorSet` of `brother.proto` and its imports.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        include_bytes!(concat!(env!("OUT_DIR"), "/brother_descriptor.bin"));
 }
\ No newline at end of file
@@ -1,15 +1,63 @@
+// `tonic::Status` is large, but it is what every handler returns.
+#![allow(clippy::result_large_err)]
+
+mod archive;
+mod auth;
+mod cache;
+mod config;
 mod service;
 mod db;
+mod deadline;
+mod health;
+mod idempotency;
+mod maintenance;
+mod migrate;
+mod query;
+mod schema;
+mod store;
+mod value;
+mod watch;
 
+use auth::Authenticator;
+use clap::{Parser, Subcommand};
+use cache::Cache;
+use config::Config;
+use deadline::WithDeadline;
 use service::BrotherService;
 use brother::pb::brother_server::BrotherServer;
-use tonic::transport::Server;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+use tracing::{info, warn};
 use tracing_subscriber::{EnvFilter};
-use std::net::SocketAddr;
+use std::{sync::Arc, time::Duration};
 use dotenvy::dotenv;
 
+/// Settings come from `BROTHER_CONFIG` and the environment (see
+/// `config.rs`); the flags only pick what to do.
+#[derive(Debug, Parser)]
+#[command(version, about = "Brother: TAO-style objects and associations over gRPC")]
+struct Cli {
+    /// Serve without applying migrations; refuse to start if any are
+    /// pending or have drifted.
+    #[arg(long)]
+    no_migrate: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Inspect, apply or revert the schema migrations, then exit.
+    #[command(subcommand)]
+    Migrate(migrate::Command),
+    /// Export a tenant to an archive file or import one, then exit.
+    #[command(subcommand)]
+    Tenant(archive::Command),
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
     dotenv().ok();
     
     // ---------- logging ----------
@@ -20,18 +68,159 @@ async fn main() -> anyhow::Result<()> {
         )
         .init();
 
+    // ---------- config ----------
+    let cfg = Config::load()?;
+
+    if let Some(command) = cli.command {
+        let pool = db::connect(&cfg.database).await?;
+        let done = match command {
+            Command::Migrate(command) => migrate::run(command, &pool).await,
+            Command::Tenant(command) => archive::run(command, &pool).await,
+        };
+        pool.close().await;
+        return done;
+    }
+
     // ---------- DB connection ----------
-    let pool = db::init_pool().await?;        // implement this in db.rs
+    let db::Pools { primary: pool, read: read_pool } =
+        db::init_pool(&cfg.database, !cli.no_migrate).await?;
+
+    // ---------- housekeeping ----------
+    let m = &cfg.maintenance;
+    let retention = Duration::from_secs(m.tombstone_retention_secs);
+    let purge_every = Duration::from_secs(m.purge_interval_secs);
+    let key_window = Duration::from_secs(m.idempotency_window_secs);
+    maintenance::spawn_purge(pool.clone(), retention, purge_every);
+    maintenance::spawn_reaper(pool.clone(), Duration::from_secs(m.reap_interval_secs));
+    maintenance::spawn_key_purge(pool.clone(), key_window, purge_every);
+    maintenance::spawn_id_purge(pool.clone(), maintenance::ID_WINDOW, purge_every);
+
+    // ---------- read cache (entries = 0 turns it off) ----------
+    let cache = Arc::new(Cache::new(
+        cfg.cache.entries,
+        cfg.cache.tenant_entries.unwrap_or(cfg.cache.entries),
+    )
+    .with_max_age(config::secs(cfg.cache.max_age_secs)));
+    if cache.enabled() {
+        cache.clone().spawn_report(Duration::from_secs(60));
+    }
+
+    // ---------- health (follows the pool) ----------
+    let (stop_tx, stop) = tokio::sync::watch::channel(false);
+    let (reporter, health) = tonic_health::server::health_reporter();
+    let probe_every = Duration::from_secs(cfg.lifecycle.health_probe_secs);
+    health::spawn_probe(pool.clone(), reporter, probe_every, stop.clone());
+
+    // ---------- reflection ----------
+    let reflection = || {
+        tonic_reflection::server::Builder::configure()
+            .register_encoded_file_descriptor_set(brother::pb::FILE_DESCRIPTOR_SET)
+            .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+    };
+    let reflection_v1 = reflection().build_v1()?;
+    let reflection_v1alpha = reflection().build_v1alpha()?;   // older grpcurl
+
+    let mut svc  = BrotherService::new(pool.clone())
+        .with_retention(retention)
+        .with_idempotency_window(key_window)
+        .with_cache(cache)
+        .with_shutdown(stop.clone());
+    if let Some(read_pool) = &read_pool {
+        svc = svc.with_read_pool(read_pool.clone());
+    }
+    let auth = Authenticator::from_env()?;
 
     // ---------- gRPC server ----------
-    let addr: SocketAddr = "[::1]:42069".parse()?;
-    let svc  = BrotherService::new(pool);
-    tracing::info!("Brother gRPC server listening on {}", addr);
+    let limits = &cfg.limits;
+    let keepalive = &cfg.keepalive;
+    let mut server = Server::builder()
+        .max_concurrent_streams((limits.max_concurrent_streams > 0).then_some(limits.max_concurrent_streams))
+        .http2_keepalive_interval(config::secs(keepalive.http2_interval_secs))
+        .http2_keepalive_timeout(config::secs(keepalive.http2_timeout_secs))
+        .tcp_keepalive(config::secs(keepalive.tcp_secs));
+    if limits.concurrency_per_connection > 0 {
+        server = server.concurrency_limit_per_connection(limits.concurrency_per_connection);
+    }
+    if let Some(timeout) = config::secs(limits.request_timeout_secs) {
+        server = server.timeout(timeout);
+    }
+    if let Some(tls) = &cfg.tls {
+        server = server.tls_config(tls_config(tls)?)?;
+    }
+
+    let brother = BrotherServer::new(svc)
+        .max_decoding_message_size(limits.max_message_bytes)
+        .max_encoding_message_size(limits.max_message_bytes);
+
+    // Health and reflection answer without credentials.
+    info!("Brother gRPC server listening on {}", cfg.listen);
+    let serve = server
+        .add_service(health)
+        .add_service(reflection_v1)
+        .add_service(reflection_v1alpha)
+        .add_service(WithDeadline::new(
+            InterceptedService::new(brother, auth),
+            config::secs(limits.request_timeout_secs),
+        ))
+        .serve_with_shutdown(cfg.listen, async move {
+            shutdown_signal().await;
+            info!("shutting down: no new connections, draining in-flight calls");
+            let _ = stop_tx.send(true);
+        });
 
-    Server::builder()
-        .add_service(BrotherServer::new(svc))
-        .serve(addr)
-        .await?;
+    // ---------- graceful shutdown ----------
+    let grace = Duration::from_secs(cfg.lifecycle.shutdown_grace_secs);
+    let mut stopping = stop;
+    tokio::select! {
+        served = serve => served?,
+        _ = async {
+            watch::stopping(&mut stopping).await;
+            tokio::time::sleep(grace).await;
+        } => warn!("calls still running after {grace:?}; exiting anyway"),
+    }
+    pool.close().await;
+    if let Some(read_pool) = read_pool {
+        read_pool.close().await;
+    }
+    info!("database pools closed; bye");
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// SIGTERM (what orchestrators send) or Ctrl-C.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                term.recv().await;
+            }
+            Err(e) => {
+                warn!("cannot listen for SIGTERM: {e}");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate => {}
+    }
+}
+
+/// Server identity plus, with `client_ca`, optional client certificates –
+/// what [`Authenticator`] maps to tenants.
+fn tls_config(tls: &config::Tls) -> anyhow::Result<ServerTlsConfig> {
+    let identity = Identity::from_pem(std::fs::read(&tls.cert)?, std::fs::read(&tls.key)?);
+    let mut out = ServerTlsConfig::new().identity(identity);
+
+    if let Some(ca) = &tls.client_ca {
+        out = out
+            .client_ca_root(Certificate::from_pem(std::fs::read(ca)?))
+            .client_auth_optional(true);   // bearer tokens still allowed
+    }
+    Ok(out)
+}
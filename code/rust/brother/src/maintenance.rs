@@ -0,0 +1,103 @@
+//! src/maintenance.rs
+//! Background housekeeping run by the brother binary.
+
+use std::{future::Future, time::Duration};
+
+use sqlx::postgres::types::PgInterval;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::db::PgPool;
+
+/// Rows removed per statement, so a job never holds long locks.
+const BATCH: i32 = 1000;
+
+/// How long a generated id stays recorded: far beyond any clock skew.
+pub const ID_WINDOW: Duration = Duration::from_secs(3600);
+
+/// `INTERVAL` for a std `Duration` (microsecond precision).
+pub fn interval(d: Duration) -> PgInterval {
+    PgInterval {
+        months: 0,
+        days: 0,
+        microseconds: d.as_micros() as i64,
+    }
+}
+
+/// Every `every`, physically remove tombstones older than `retention`.
+pub fn spawn_purge(db: PgPool, retention: Duration, every: Duration) -> JoinHandle<()> {
+    spawn_batched("tombstone purge", db, every, move |db, batch| async move {
+        sqlx::query_scalar(r#"SELECT tao.tao_purge_tombstones($1,$2)"#)
+            .bind(interval(retention))
+            .bind(batch)
+            .fetch_one(&db)
+            .await
+    })
+}
+
+/// Every `every`, physically remove rows past their `expires_at`.  A
+/// batch is up to [`BATCH`] objects and as many edges.
+pub fn spawn_reaper(db: PgPool, every: Duration) -> JoinHandle<()> {
+    spawn_batched("expiry reaper", db, every, |db, batch| async move {
+        sqlx::query_scalar(r#"SELECT tao.tao_reap_expired($1)"#)
+            .bind(batch)
+            .fetch_one(&db)
+            .await
+    })
+}
+
+/// Every `every`, forget idempotency keys older than `window`.
+pub fn spawn_key_purge(db: PgPool, window: Duration, every: Duration) -> JoinHandle<()> {
+    spawn_batched("idempotency key purge", db, every, move |db, batch| async move {
+        sqlx::query_scalar(r#"SELECT tao.tao_purge_idempotency_keys($1,$2)"#)
+            .bind(interval(window))
+            .bind(batch)
+            .fetch_one(&db)
+            .await
+    })
+}
+
+/// Every `every`, forget generated ids older than `window`; only ids of
+/// the current millisecond can be drawn again.
+pub fn spawn_id_purge(db: PgPool, window: Duration, every: Duration) -> JoinHandle<()> {
+    spawn_batched("object id purge", db, every, move |db, batch| async move {
+        sqlx::query_scalar(r#"SELECT tao.tao_purge_object_ids($1,$2)"#)
+            .bind(interval(window))
+            .bind(batch)
+            .fetch_one(&db)
+            .await
+    })
+}
+
+/// Every `every`, run `step` with [`BATCH`] until it removes fewer rows
+/// than that.  `step` returns the rows it removed.
+fn spawn_batched<F, Fut>(name: &'static str, db: PgPool, every: Duration, step: F) -> JoinHandle<()>
+where
+    F: Fn(PgPool, i32) -> Fut + Send + 'static,
+    Fut: Future<Output = sqlx::Result<i64>> + Send,
+{
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(every);
+        loop {
+            tick.tick().await;
+            let mut total = 0;
+            let done = loop {
+                match step(db.clone(), BATCH).await {
+                    Ok(removed) => {
+                        total += removed as u64;
+                        if removed < BATCH as i64 {
+                            break Ok(());
+                        }
+                    }
+                    Err(e) => break Err(e),
+                }
+            };
+            if total > 0 {
+                info!(rows = total, "{name} removed rows");
+            }
+            if let Err(e) = done {
+                warn!("{name} failed: {e}");
+            }
+        }
+    })
+}
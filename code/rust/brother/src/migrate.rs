@@ -0,0 +1,462 @@
+//! src/migrate.rs
+//! The embedded schema migrations, and `brother migrate`.
+//!
+//! `src/database/N_name.sql` apply in order of `N`.  One that can be
+//! undone comes as `N_name.up.sql` plus `N_name.down.sql` – every one
+//! from 13 on does; `down` stops at the first applied migration without
+//! a down script.  sqlx records
+//! every applied migration in `_sqlx_migrations` with a checksum of its
+//! SQL, which is how an edited file (drift) shows.
+//!
+//! The server applies what is pending on start unless `--no-migrate`.
+//! With many replicas, run `brother migrate up` once per deploy and
+//! start them with `--no-migrate`: they then refuse to serve a database
+//! that is behind or has drifted.
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+use clap::Subcommand;
+use sqlx::migrate::{Migrate, Migration, Migrator};
+use sqlx::{Connection, Executor, PgConnection, Row};
+use tracing::{info, warn};
+
+use crate::db::PgPool;
+
+/// Compile-time embedded migration directory.
+///
+/// `sqlx::migrate!` expands to a `static MIGRATOR` that embeds every
+/// `*.sql` file under the given path and applies them in lexicographic
+/// order.
+///
+/// The path is **relative to the crate root** *at compile time* – adjust
+/// if you move the `database/` folder elsewhere.
+static MIGRATOR: Migrator = sqlx::migrate!("./src/database");
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List every migration and where the database stands on it.
+    Status,
+    /// Apply pending migrations.
+    Up {
+        /// Apply none above this version.
+        #[arg(long)]
+        to: Option<i64>,
+    },
+    /// Revert applied migrations, newest first.
+    Down {
+        /// Revert everything above this version; default: the newest only.
+        #[arg(long)]
+        to: Option<i64>,
+    },
+    /// Fail unless the database is at exactly this binary's migrations.
+    Verify,
+}
+
+/// Where the database stands on one migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Applied,
+    Pending,
+    /// Applied, but the embedded SQL has changed since.
+    Drifted,
+    /// Started and never finished; needs fixing by hand.
+    Failed,
+    /// Applied by a binary that embeds it – a newer one.
+    Unknown,
+}
+
+impl State {
+    fn label(self) -> &'static str {
+        match self {
+            State::Applied => "applied",
+            State::Pending => "pending",
+            State::Drifted => "DRIFTED",
+            State::Failed => "FAILED",
+            State::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub version: i64,
+    pub description: String,
+    pub state: State,
+    /// Has a down script.
+    pub reversible: bool,
+}
+
+/// `brother migrate <command>`.
+pub async fn run(command: Command, pool: &PgPool) -> anyhow::Result<()> {
+    match command {
+        Command::Status => {
+            println!("{:>8}  {:<8} {:<5} description", "version", "state", "down");
+            for e in status(pool).await? {
+                println!(
+                    "{:>8}  {:<8} {:<5} {}",
+                    e.version,
+                    e.state.label(),
+                    if e.reversible { "yes" } else { "-" },
+                    e.description
+                );
+            }
+        }
+        Command::Up { to } => match up(pool, to).await?.as_slice() {
+            [] => println!("nothing to apply"),
+            done => println!("applied {done:?}"),
+        },
+        Command::Down { to } => match down(pool, to).await?.as_slice() {
+            [] => println!("nothing to revert"),
+            done => println!("reverted {done:?}"),
+        },
+        Command::Verify => {
+            let off: Vec<_> = status(pool)
+                .await?
+                .into_iter()
+                .filter(|e| e.state != State::Applied)
+                .collect();
+            if !off.is_empty() {
+                for e in &off {
+                    println!("{:>8}  {:<8} {}", e.version, e.state.label(), e.description);
+                }
+                bail!("database does not match this binary's migrations");
+            }
+            println!("database matches this binary's migrations");
+        }
+    }
+    Ok(())
+}
+
+/// Every migration embedded here or recorded in the database, by version.
+pub async fn status(pool: &PgPool) -> anyhow::Result<Vec<Entry>> {
+    let mut conn = session(pool).await?;
+    let applied = applied(&mut conn).await;
+    end(conn).await?;
+    let mut applied = applied?;
+
+    let mut entries: Vec<Entry> = ups()
+        .map(|m| {
+            let state = match applied.remove(&m.version) {
+                None => State::Pending,
+                Some(a) if !a.success => State::Failed,
+                Some(a) if *a.checksum != *m.checksum => State::Drifted,
+                Some(_) => State::Applied,
+            };
+            Entry {
+                version: m.version,
+                description: m.description.to_string(),
+                state,
+                reversible: down_of(m.version).is_some(),
+            }
+        })
+        .collect();
+    entries.extend(applied.into_iter().map(|(version, a)| Entry {
+        version,
+        description: a.description,
+        state: if a.success { State::Unknown } else { State::Failed },
+        reversible: false,
+    }));
+    entries.sort_by_key(|e| e.version);
+    Ok(entries)
+}
+
+/// On start with `--no-migrate`: refuse a database this binary cannot
+/// run on.  Migrations of a newer binary are fine (a rolling deploy).
+pub async fn check(pool: &PgPool) -> anyhow::Result<()> {
+    let entries = status(pool).await?;
+    let of = |state| -> Vec<i64> {
+        entries.iter().filter(|e| e.state == state).map(|e| e.version).collect()
+    };
+    let (failed, drifted, pending) = (of(State::Failed), of(State::Drifted), of(State::Pending));
+    if !failed.is_empty() {
+        bail!("migrations {failed:?} failed part-way; fix the database by hand");
+    }
+    if !drifted.is_empty() {
+        bail!("migrations {drifted:?} differ from what was applied (see `brother migrate status`)");
+    }
+    if !pending.is_empty() {
+        bail!("migrations {pending:?} are pending; run `brother migrate up` first");
+    }
+    let unknown = of(State::Unknown);
+    if !unknown.is_empty() {
+        warn!("database has migrations {unknown:?} from a newer brother");
+    }
+    Ok(())
+}
+
+/// Apply pending migrations up to `to` (all if `None`).  The versions
+/// applied, in order.
+pub async fn up(pool: &PgPool, to: Option<i64>) -> anyhow::Result<Vec<i64>> {
+    let mut conn = session(pool).await?;
+    let done = up_locked(&mut conn, to).await;
+    end(conn).await?;
+    done
+}
+
+async fn up_locked(conn: &mut PgConnection, to: Option<i64>) -> anyhow::Result<Vec<i64>> {
+    let applied = checked(conn).await?;
+    if let Some(v) = applied.keys().find(|&&v| !ups().any(|m| m.version == v)) {
+        bail!("database has migration {v}, unknown to this binary; use a newer brother");
+    }
+
+    let mut done = Vec::new();
+    for m in ups().filter(|m| !applied.contains_key(&m.version)) {
+        if to.is_some_and(|to| m.version > to) {
+            break;
+        }
+        let took = conn.apply(m).await?;
+        conn.execute(PIN_SEARCH_PATH).await?;      // the migration sets its own
+        info!(version = m.version, ?took, "applied migration {}", m.description);
+        done.push(m.version);
+    }
+    Ok(done)
+}
+
+/// Revert applied migrations above `to` (default: the newest), newest
+/// first.  Nothing is reverted unless every one of them can be.
+pub async fn down(pool: &PgPool, to: Option<i64>) -> anyhow::Result<Vec<i64>> {
+    let mut conn = session(pool).await?;
+    let done = down_locked(&mut conn, to).await;
+    end(conn).await?;
+    done
+}
+
+async fn down_locked(conn: &mut PgConnection, to: Option<i64>) -> anyhow::Result<Vec<i64>> {
+    let mut versions: Vec<i64> = checked(conn).await?.into_keys().collect();
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+    let Some(&newest) = versions.first() else {
+        return Ok(Vec::new());
+    };
+    let to = to.unwrap_or(newest - 1);
+
+    let mut scripts = Vec::new();
+    for &v in versions.iter().take_while(|&&v| v > to) {
+        match down_of(v) {
+            Some(m) => scripts.push(m),
+            None => bail!("migration {v} has no down script; cannot revert below it"),
+        }
+    }
+
+    let mut done = Vec::new();
+    for m in scripts {
+        let took = conn.revert(m).await?;
+        conn.execute(PIN_SEARCH_PATH).await?;
+        info!(version = m.version, ?took, "reverted migration {}", m.description);
+        done.push(m.version);
+    }
+    Ok(done)
+}
+
+/// One row of `_sqlx_migrations`.
+struct Applied {
+    description: String,
+    checksum: Vec<u8>,
+    success: bool,
+}
+
+async fn applied(conn: &mut PgConnection) -> Result<HashMap<i64, Applied>, sqlx::Error> {
+    let rows = sqlx::query(r#"SELECT version, description, checksum, success FROM _sqlx_migrations"#)
+        .fetch_all(conn)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| {
+            let applied = Applied {
+                description: r.get("description"),
+                checksum: r.get("checksum"),
+                success: r.get("success"),
+            };
+            (r.get("version"), applied)
+        })
+        .collect())
+}
+
+/// Where sqlx keeps `_sqlx_migrations`: the first schema that exists on
+/// the path, which was `public` when it first ran – before `tao` did.
+const PIN_SEARCH_PATH: &str = "SET search_path TO public";
+
+/// A connection of its own, holding the migration lock (the one sqlx's
+/// runner takes).  It is opened with the pool's options but not through
+/// the pool, so it skips the `after_connect` that puts `tao` first on
+/// the path; migrations change `search_path` anyway, so it never goes
+/// back to a pool.
+async fn session(pool: &PgPool) -> anyhow::Result<PgConnection> {
+    let mut conn = PgConnection::connect_with(&pool.connect_options()).await?;
+    conn.execute(PIN_SEARCH_PATH).await?;
+    conn.lock().await?;
+    conn.ensure_migrations_table().await?;
+    repair_stray_table(&mut conn).await?;
+    Ok(conn)
+}
+
+/// One-off repair.  Builds that ran sqlx's migrator through pool
+/// sessions, with `tao` first on the path, created an empty
+/// `tao._sqlx_migrations` on the restart after the first and re-ran
+/// every migration into it.  Its rows are folded back into `public`
+/// and it is dropped; on a database without it this does nothing.
+async fn repair_stray_table(conn: &mut PgConnection) -> anyhow::Result<()> {
+    let stray: bool = sqlx::query_scalar(r#"SELECT to_regclass('tao._sqlx_migrations') IS NOT NULL"#)
+        .fetch_one(&mut *conn)
+        .await?;
+    if !stray {
+        return Ok(());
+    }
+    warn!("folding tao._sqlx_migrations back into public._sqlx_migrations");
+    let mut tx = conn.begin().await?;
+    tx.execute(
+        r#"INSERT INTO public._sqlx_migrations
+           SELECT * FROM tao._sqlx_migrations
+           ON CONFLICT (version) DO NOTHING"#,
+    )
+    .await?;
+    tx.execute(r#"DROP TABLE tao._sqlx_migrations"#).await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn end(mut conn: PgConnection) -> anyhow::Result<()> {
+    conn.unlock().await?;
+    conn.close().await?;
+    Ok(())
+}
+
+/// The applied versions and their checksums, once nothing failed or
+/// drifted.
+async fn checked(conn: &mut PgConnection) -> anyhow::Result<HashMap<i64, Vec<u8>>> {
+    if let Some(v) = conn.dirty_version().await? {
+        bail!("migration {v} failed part-way; fix the database by hand");
+    }
+    let applied: HashMap<i64, Vec<u8>> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m.checksum.into_owned()))
+        .collect();
+    for m in ups() {
+        if applied.get(&m.version).is_some_and(|c| **c != *m.checksum) {
+            bail!(
+                "migration {} ({}) differs from what was applied",
+                m.version,
+                m.description
+            );
+        }
+    }
+    Ok(applied)
+}
+
+fn ups() -> impl Iterator<Item = &'static Migration> {
+    MIGRATOR.iter().filter(|m| m.migration_type.is_up_migration())
+}
+
+fn down_of(version: i64) -> Option<&'static Migration> {
+    MIGRATOR
+        .iter()
+        .find(|m| m.version == version && m.migration_type.is_down_migration())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config, db};
+
+    /// A database of its own: reverting under the other tests' feet
+    /// would break them.
+    async fn scratch(name: &str) -> (PgConnection, PgPool) {
+        let url = std::env::var("BROTHER_TEST_DATABASE_URL")
+            .expect("BROTHER_TEST_DATABASE_URL names the test database");
+        let mut admin = PgConnection::connect(&url).await.unwrap();
+        admin.execute(format!("DROP DATABASE IF EXISTS {name}").as_str()).await.unwrap();
+        admin.execute(format!("CREATE DATABASE {name}").as_str()).await.unwrap();
+
+        let (base, query) = url.split_once('?').map_or((url.as_str(), None), |(b, q)| (b, Some(q)));
+        let (server, _) = base.rsplit_once('/').unwrap();
+        let cfg = config::Database {
+            url: Some(format!("{server}/{name}{}", query.map_or(String::new(), |q| format!("?{q}")))),
+            max_connections: 2,
+            ..Default::default()
+        };
+        (admin, db::connect(&cfg).await.unwrap())
+    }
+
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn down_to_12_and_up_again() {
+        let name = format!("brother_migrate_{}", std::process::id());
+        let (mut admin, pool) = scratch(&name).await;
+        let all: Vec<i64> = ups().map(|m| m.version).collect();
+
+        assert_eq!(up(&pool, None).await.unwrap(), all);
+        let id: i64 = sqlx::query_scalar(r#"SELECT id FROM tao_upsert_object(7, 5, 0, 0, '{"name":"ada"}')"#)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"SELECT tao_declare_unique(7, 5, ARRAY['name'])"#)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"SELECT tao_upsert_object(7, 5, $1, 0, '{"name":"bea"}')"#)
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query(r#"SELECT tao_upsert_association(7, 'follows', $1, 2, 0, 0, '{}', NULL)"#)
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let above: Vec<i64> = all.iter().copied().filter(|&v| v > 12).collect();
+        let mut reverted = above.clone();
+        reverted.reverse();
+        assert_eq!(down(&pool, Some(12)).await.unwrap(), reverted);
+        assert!(status(&pool).await.unwrap().iter().all(|e| (e.version > 12) == (e.state == State::Pending)));
+
+        assert_eq!(up(&pool, None).await.unwrap(), above);
+        assert!(status(&pool).await.unwrap().iter().all(|e| e.state == State::Applied));
+
+        // The data made it there and back.
+        let taken = sqlx::query(r#"SELECT tao_upsert_object(7, 5, 0, 0, '{"name":"bea"}')"#)
+            .execute(&pool)
+            .await
+            .unwrap_err();
+        assert_eq!(taken.as_database_error().and_then(|e| e.code()).as_deref(), Some(db::UNIQUE_VIOLATION));
+        let versions: i64 = sqlx::query_scalar(r#"SELECT count(*) FROM tao_object_versions(7, 5, $1)"#)
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(versions, 2);
+
+        pool.close().await;
+        admin.execute(format!("DROP DATABASE {name} WITH (FORCE)").as_str()).await.unwrap();
+    }
+
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn a_stray_tao_table_is_folded_back() {
+        let name = format!("brother_migrate_stray_{}", std::process::id());
+        let (mut admin, pool) = scratch(&name).await;
+        up(&pool, None).await.unwrap();
+
+        // What the old builds left: the newest version recorded in tao only.
+        let newest = ups().map(|m| m.version).max().unwrap();
+        for sql in [
+            r#"CREATE TABLE tao._sqlx_migrations (LIKE public._sqlx_migrations INCLUDING ALL)"#,
+            r#"INSERT INTO tao._sqlx_migrations SELECT * FROM public._sqlx_migrations WHERE version = $1"#,
+            r#"DELETE FROM public._sqlx_migrations WHERE version = $1"#,
+        ] {
+            sqlx::query(sql).bind(newest).execute(&pool).await.unwrap();
+        }
+
+        assert!(status(&pool).await.unwrap().iter().all(|e| e.state == State::Applied));
+        let stray: bool = sqlx::query_scalar(r#"SELECT to_regclass('tao._sqlx_migrations') IS NOT NULL"#)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(!stray);
+
+        pool.close().await;
+        admin.execute(format!("DROP DATABASE {name} WITH (FORCE)").as_str()).await.unwrap();
+    }
+}
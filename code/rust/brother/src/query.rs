@@ -0,0 +1,620 @@
+//! src/query.rs
+//! `QueryObjects` / `QueryAssociations`: attribute filters compiled to SQL.
+//!
+//! Every literal is a bind parameter.  Equality and containment become
+//! `attributes @> $n`, which the `*_attrs_gin` indexes serve; ranges are
+//! jsonpath predicates evaluated on the rows the rest of the filter left.
+//! Pages are keyset-based on the sort key plus the row's primary key.
+
+use brother::pb::{
+    filter::Kind, value::Kind as ValueKind, Association, FieldFilter, Filter, FilterOp, Object,
+    Order, QueryAssociationsRequest, QueryAssociationsResponse, QueryObjectsRequest,
+    QueryObjectsResponse,
+};
+use serde_json::{json, Number, Value as Json};
+use sqlx::{postgres::PgRow, Postgres, QueryBuilder, Row};
+use tonic::Status;
+
+use crate::auth::Tenant;
+use crate::db::{db_err, retry, PgPool, Retry};
+use crate::value;
+
+/// Leaves + `all` / `any` nodes a single filter may have.
+const MAX_FILTER_NODES: usize = 64;
+
+/// One component of the ordering.
+enum Key {
+    /// An attribute; rows without it sort lowest.
+    Attr(String),
+    /// A BIGINT column.
+    Col(&'static str),
+}
+
+pub async fn objects(
+    db: &PgPool,
+    tenant: Tenant,
+    req: QueryObjectsRequest,
+    limit: i64,
+) -> Result<QueryObjectsResponse, Status> {
+    let QueryObjectsRequest {
+        otype,
+        filter,
+        order_by,
+        order,
+        cursor,
+        ..
+    } = req;
+
+    let mut keys = Vec::with_capacity(2);
+    if !order_by.is_empty() {
+        keys.push(Key::Attr(order_by));
+    }
+    keys.push(Key::Col("id"));
+    let after = decode_cursor(&cursor, &keys)?;
+
+    let filter = filter.as_ref().map(compile).transpose()?;
+
+    // A built query owns its arguments, so each attempt builds afresh.
+    let select = || {
+        let mut qb = QueryBuilder::<Postgres>::new(
+            "SELECT id, version, attributes, \
+                    (extract(epoch FROM expires_at) * 1000)::BIGINT AS expires_at",
+        );
+        push_sort_column(&mut qb, &keys);
+        qb.push(" FROM tao.objects WHERE tenant = ")
+            .push_bind(tenant.db())
+            .push(" AND type = ")
+            .push_bind(otype as i32)
+            .push(" AND deleted_at IS NULL AND (expires_at IS NULL OR expires_at > now())");
+        if let Some(parts) = &filter {
+            qb.push(" AND ");
+            push_parts(&mut qb, parts);
+        }
+        push_page(&mut qb, &keys, after.as_deref(), order, limit);
+        qb
+    };
+
+    let mut rows = retry!(Retry::Idempotent, select().build().fetch_all(db)).map_err(db_err)?;
+    let next_cursor = next_cursor(&mut rows, &keys, limit);
+
+    let objects = rows
+        .into_iter()
+        .map(|r| Object {
+            tenant: tenant.0,
+            r#type: otype,
+            id: r.get::<i64, _>("id") as u64,
+            version: r.get::<i32, _>("version") as u32,
+            attributes: value::json_to_attrs(r.get("attributes")),
+            expires_at: r.get::<Option<i64>, _>("expires_at").map(|t| t as u64),
+        })
+        .collect();
+
+    Ok(QueryObjectsResponse {
+        objects,
+        next_cursor,
+    })
+}
+
+pub async fn associations(
+    db: &PgPool,
+    tenant: Tenant,
+    req: QueryAssociationsRequest,
+    limit: i64,
+) -> Result<QueryAssociationsResponse, Status> {
+    let QueryAssociationsRequest {
+        r#type: atype,
+        source_id,
+        filter,
+        order_by,
+        order,
+        cursor,
+        ..
+    } = req;
+    if atype.is_empty() {
+        return Err(Status::invalid_argument("type is required"));
+    }
+
+    let keys = [
+        if order_by.is_empty() {
+            Key::Col("position")
+        } else {
+            Key::Attr(order_by)
+        },
+        Key::Col("source_id"),
+        Key::Col("target_id"),
+    ];
+    let after = decode_cursor(&cursor, &keys)?;
+    let filter = filter.as_ref().map(compile).transpose()?;
+
+    let select = || {
+        let mut qb = QueryBuilder::<Postgres>::new(
+            "SELECT source_id, target_id, time, position, attributes, \
+                    (extract(epoch FROM expires_at) * 1000)::BIGINT AS expires_at",
+        );
+        push_sort_column(&mut qb, &keys);
+        qb.push(" FROM tao.associations WHERE tenant = ")
+            .push_bind(tenant.db())
+            .push(" AND type = ")
+            .push_bind(atype.clone())
+            .push(" AND deleted_at IS NULL AND (expires_at IS NULL OR expires_at > now())");
+        if let Some(source_id) = source_id {
+            qb.push(" AND source_id = ").push_bind(source_id as i64);
+        }
+        if let Some(parts) = &filter {
+            qb.push(" AND ");
+            push_parts(&mut qb, parts);
+        }
+        push_page(&mut qb, &keys, after.as_deref(), order, limit);
+        qb
+    };
+
+    let mut rows = retry!(Retry::Idempotent, select().build().fetch_all(db)).map_err(db_err)?;
+    let next_cursor = next_cursor(&mut rows, &keys, limit);
+
+    let associations = rows
+        .into_iter()
+        .map(|r| Association {
+            tenant: tenant.0,
+            r#type: atype.clone(),
+            source_id: r.get::<i64, _>("source_id") as u64,
+            target_id: r.get::<i64, _>("target_id") as u64,
+            time: r.get::<i64, _>("time") as u64,
+            position: r.get::<i64, _>("position") as u64,
+            attributes: value::json_to_attrs(r.get("attributes")),
+            expires_at: r.get::<Option<i64>, _>("expires_at").map(|t| t as u64),
+        })
+        .collect();
+
+    Ok(QueryAssociationsResponse {
+        associations,
+        next_cursor,
+    })
+}
+
+/// A compiled filter: SQL text and the values bound in between.
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Sql(&'static str),
+    Json(Json),
+    Text(String),
+}
+
+/// `filter` as one parenthesised boolean expression.
+fn compile(filter: &Filter) -> Result<Vec<Part>, Status> {
+    let mut out = Vec::new();
+    let mut budget = MAX_FILTER_NODES;
+    compile_node(&mut out, filter, &mut budget)?;
+    Ok(out)
+}
+
+fn push_parts(qb: &mut QueryBuilder<'_, Postgres>, parts: &[Part]) {
+    for part in parts {
+        match part {
+            Part::Sql(s) => qb.push(*s),
+            Part::Json(j) => qb.push_bind(j.clone()),
+            Part::Text(t) => qb.push_bind(t.clone()),
+        };
+    }
+}
+
+fn compile_node(out: &mut Vec<Part>, filter: &Filter, budget: &mut usize) -> Result<(), Status> {
+    if *budget == 0 {
+        return Err(Status::invalid_argument(format!(
+            "filter has more than {MAX_FILTER_NODES} nodes"
+        )));
+    }
+    *budget -= 1;
+
+    let (list, joiner, empty) = match &filter.kind {
+        None => return Err(Status::invalid_argument("filter kind is required")),
+        Some(Kind::Field(f)) => return compile_field(out, f),
+        Some(Kind::All(l)) => (&l.filters, " AND ", "TRUE"),
+        Some(Kind::Any(l)) => (&l.filters, " OR ", "FALSE"),
+    };
+    if list.is_empty() {
+        out.push(Part::Sql(empty));
+        return Ok(());
+    }
+    out.push(Part::Sql("("));
+    for (i, f) in list.iter().enumerate() {
+        if i > 0 {
+            out.push(Part::Sql(joiner));
+        }
+        compile_node(out, f, budget)?;
+    }
+    out.push(Part::Sql(")"));
+    Ok(())
+}
+
+fn compile_field(out: &mut Vec<Part>, f: &FieldFilter) -> Result<(), Status> {
+    if f.field.is_empty() {
+        return Err(Status::invalid_argument("filter field is required"));
+    }
+    let op = FilterOp::try_from(f.op)
+        .map_err(|_| Status::invalid_argument("unknown filter op"))?;
+    let value = || {
+        f.value
+            .as_ref()
+            .map(value::to_json)
+            .ok_or_else(|| Status::invalid_argument(format!("filter on `{}` needs a value", f.field)))
+    };
+
+    let cmp = match op {
+        FilterOp::FilterEq => {
+            // `@>` narrows through the GIN index; `->` makes it exact for
+            // lists and maps, where containment is not equality.
+            let v = value()?;
+            out.extend([
+                Part::Sql("(attributes @> "),
+                Part::Json(json!({ &f.field: v.clone() })),
+                Part::Sql(" AND attributes -> "),
+                Part::Text(f.field.clone()),
+                Part::Sql(" = "),
+                Part::Json(v),
+                Part::Sql(")"),
+            ]);
+            return Ok(());
+        }
+        FilterOp::FilterContains => {
+            out.extend([
+                Part::Sql("attributes @> "),
+                Part::Json(json!({ &f.field: value()? })),
+            ]);
+            return Ok(());
+        }
+        FilterOp::FilterExists => {
+            out.extend([Part::Sql("attributes ? "), Part::Text(f.field.clone())]);
+            return Ok(());
+        }
+        FilterOp::FilterLt => "<",
+        FilterOp::FilterLe => "<=",
+        FilterOp::FilterGt => ">",
+        FilterOp::FilterGe => ">=",
+    };
+
+    let n = match f.value.as_ref().and_then(|v| v.kind.as_ref()) {
+        Some(ValueKind::IntValue(i)) => Number::from(*i),
+        Some(ValueKind::UintValue(u)) => Number::from(*u),
+        Some(ValueKind::DoubleValue(d)) => Number::from_f64(*d).ok_or_else(|| {
+            Status::invalid_argument(format!("range filter on `{}` needs a finite number", f.field))
+        })?,
+        _ => {
+            return Err(Status::invalid_argument(format!(
+                "range filter on `{}` needs a number",
+                f.field
+            )))
+        }
+    };
+    // The field name is quoted as a jsonpath string; JSON escaping is valid there.
+    let path = format!("$.{} ? (@ {cmp} $v)", Json::String(f.field.clone()));
+    out.extend([
+        Part::Sql("jsonb_path_exists(attributes, "),
+        Part::Text(path),
+        Part::Sql("::jsonpath, "),
+        Part::Json(json!({ "v": Json::Number(n) })),
+        Part::Sql(")"),
+    ]);
+    Ok(())
+}
+
+fn push_key(qb: &mut QueryBuilder<'_, Postgres>, key: &Key) {
+    match key {
+        Key::Attr(a) => {
+            qb.push("COALESCE(attributes -> ")
+                .push_bind(a.clone())
+                .push(", 'null'::jsonb)");
+        }
+        Key::Col(c) => {
+            qb.push(*c);
+        }
+    }
+}
+
+/// Select the attribute sort key (if any) so the cursor can carry it.
+fn push_sort_column(qb: &mut QueryBuilder<'_, Postgres>, keys: &[Key]) {
+    if let Some(k @ Key::Attr(_)) = keys.first() {
+        qb.push(", ");
+        push_key(qb, k);
+        qb.push(" AS sort_key");
+    }
+}
+
+/// Keyset condition, ordering and `LIMIT` (one extra row tells us if
+/// there is a next page).
+fn push_page(
+    qb: &mut QueryBuilder<'_, Postgres>,
+    keys: &[Key],
+    after: Option<&[Json]>,
+    order: i32,
+    limit: i64,
+) {
+    let (cmp, dir) = match Order::try_from(order) {
+        Ok(Order::Asc) => (">", "ASC"),
+        _ => ("<", "DESC"),
+    };
+
+    if let Some(after) = after {
+        qb.push(" AND (");
+        for (i, k) in keys.iter().enumerate() {
+            if i > 0 {
+                qb.push(", ");
+            }
+            push_key(qb, k);
+        }
+        qb.push(format!(") {cmp} ("));
+        for (i, (k, v)) in keys.iter().zip(after).enumerate() {
+            if i > 0 {
+                qb.push(", ");
+            }
+            match k {
+                Key::Attr(_) => qb.push_bind(v.clone()).push("::jsonb"),
+                Key::Col(_) => qb.push_bind(v.as_i64()),
+            };
+        }
+        qb.push(")");
+    }
+
+    qb.push(" ORDER BY ");
+    for (i, k) in keys.iter().enumerate() {
+        if i > 0 {
+            qb.push(", ");
+        }
+        push_key(qb, k);
+        qb.push(format!(" {dir}"));
+    }
+    qb.push(" LIMIT ").push_bind(limit + 1);
+}
+
+/// Opaque continuation: the last row's sort values as a JSON array.
+fn next_cursor(rows: &mut Vec<PgRow>, keys: &[Key], limit: i64) -> Vec<u8> {
+    if rows.len() as i64 <= limit {
+        return Vec::new();
+    }
+    rows.truncate(limit as usize);
+    let Some(last) = rows.last() else {
+        return Vec::new();
+    };
+    let values: Vec<Json> = keys
+        .iter()
+        .map(|k| match k {
+            Key::Attr(_) => last.get::<Json, _>("sort_key"),
+            Key::Col(c) => Json::Number(Number::from(last.get::<i64, _>(*c))),
+        })
+        .collect();
+    serde_json::to_vec(&values).unwrap_or_default()
+}
+
+fn decode_cursor(cursor: &[u8], keys: &[Key]) -> Result<Option<Vec<Json>>, Status> {
+    if cursor.is_empty() {
+        return Ok(None);
+    }
+    let bad = || Status::invalid_argument("malformed cursor");
+    let values: Vec<Json> = serde_json::from_slice(cursor).map_err(|_| bad())?;
+    let fits = values.len() == keys.len()
+        && keys
+            .iter()
+            .zip(&values)
+            .all(|(k, v)| matches!(k, Key::Attr(_)) || v.is_i64());
+    if !fits {
+        return Err(bad());
+    }
+    Ok(Some(values))
+}
+
+#[cfg(test)]
+mod tests {
+    use brother::pb::{FilterList, Value};
+    use serde_json::json;
+
+    use super::*;
+
+    fn field(name: &str, op: FilterOp, value: Option<ValueKind>) -> Filter {
+        Filter {
+            kind: Some(Kind::Field(FieldFilter {
+                field: name.to_owned(),
+                op: op as i32,
+                value: value.map(|k| Value { kind: Some(k) }),
+            })),
+        }
+    }
+
+    fn all(filters: Vec<Filter>) -> Filter {
+        Filter {
+            kind: Some(Kind::All(FilterList { filters })),
+        }
+    }
+
+    fn any(filters: Vec<Filter>) -> Filter {
+        Filter {
+            kind: Some(Kind::Any(FilterList { filters })),
+        }
+    }
+
+    /// The SQL a filter compiles to and its bind values, in order.
+    fn sql(filter: &Filter) -> (String, Vec<Json>) {
+        let parts = compile(filter).unwrap();
+        let mut qb = QueryBuilder::<Postgres>::new("");
+        push_parts(&mut qb, &parts);
+        let binds = parts
+            .into_iter()
+            .filter_map(|p| match p {
+                Part::Sql(_) => None,
+                Part::Json(j) => Some(j),
+                Part::Text(t) => Some(Json::String(t)),
+            })
+            .collect();
+        (qb.sql().to_owned(), binds)
+    }
+
+    fn rejected(filter: &Filter) -> String {
+        let status = compile(filter).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        status.message().to_owned()
+    }
+
+    #[test]
+    fn field_ops_compile_to_bound_sql() {
+        let cases = [
+            (
+                field("name", FilterOp::FilterEq, Some(ValueKind::StringValue("ada".into()))),
+                "(attributes @> $1 AND attributes -> $2 = $3)",
+                vec![json!({"name": "ada"}), json!("name"), json!("ada")],
+            ),
+            (
+                field("tags", FilterOp::FilterContains, Some(ValueKind::StringValue("x".into()))),
+                "attributes @> $1",
+                vec![json!({"tags": "x"})],
+            ),
+            (
+                field("zip", FilterOp::FilterExists, None),
+                "attributes ? $1",
+                vec![json!("zip")],
+            ),
+            (
+                field("age", FilterOp::FilterLt, Some(ValueKind::IntValue(-3))),
+                "jsonb_path_exists(attributes, $1::jsonpath, $2)",
+                vec![json!(r#"$."age" ? (@ < $v)"#), json!({"v": -3})],
+            ),
+            (
+                field("age", FilterOp::FilterLe, Some(ValueKind::UintValue(u64::MAX))),
+                "jsonb_path_exists(attributes, $1::jsonpath, $2)",
+                vec![json!(r#"$."age" ? (@ <= $v)"#), json!({"v": u64::MAX})],
+            ),
+            (
+                field("score", FilterOp::FilterGt, Some(ValueKind::DoubleValue(0.5))),
+                "jsonb_path_exists(attributes, $1::jsonpath, $2)",
+                vec![json!(r#"$."score" ? (@ > $v)"#), json!({"v": 0.5})],
+            ),
+            (
+                field(r#"we"ird"#, FilterOp::FilterGe, Some(ValueKind::IntValue(1))),
+                "jsonb_path_exists(attributes, $1::jsonpath, $2)",
+                vec![json!(r#"$."we\"ird" ? (@ >= $v)"#), json!({"v": 1})],
+            ),
+        ];
+        for (filter, want_sql, want_binds) in cases {
+            assert_eq!(sql(&filter), (want_sql.to_owned(), want_binds));
+        }
+    }
+
+    #[test]
+    fn lists_nest_and_empty_lists_are_constants() {
+        let exists = |f: &str| field(f, FilterOp::FilterExists, None);
+        let filter = all(vec![exists("a"), any(vec![exists("b"), exists("c")]), all(vec![])]);
+        assert_eq!(
+            sql(&filter),
+            (
+                "(attributes ? $1 AND (attributes ? $2 OR attributes ? $3) AND TRUE)".to_owned(),
+                vec![json!("a"), json!("b"), json!("c")],
+            )
+        );
+        assert_eq!(sql(&any(vec![])), ("FALSE".to_owned(), vec![]));
+    }
+
+    #[test]
+    fn literals_never_reach_the_sql() {
+        let evil = "x' OR 1=1 --";
+        let (text, binds) = sql(&field(evil, FilterOp::FilterEq, Some(ValueKind::StringValue(evil.into()))));
+        assert!(!text.contains(evil));
+        assert_eq!(binds.len(), 3);
+    }
+
+    #[test]
+    fn malformed_filters_are_rejected() {
+        let cases = [
+            (Filter { kind: None }, "filter kind is required"),
+            (all(vec![Filter { kind: None }]), "filter kind is required"),
+            (field("", FilterOp::FilterExists, None), "filter field is required"),
+            (
+                Filter {
+                    kind: Some(Kind::Field(FieldFilter {
+                        field: "a".into(),
+                        op: 99,
+                        value: None,
+                    })),
+                },
+                "unknown filter op",
+            ),
+            (field("a", FilterOp::FilterEq, None), "filter on `a` needs a value"),
+            (field("a", FilterOp::FilterContains, None), "filter on `a` needs a value"),
+            (field("a", FilterOp::FilterGt, None), "range filter on `a` needs a number"),
+            (
+                field("a", FilterOp::FilterGt, Some(ValueKind::StringValue("1".into()))),
+                "range filter on `a` needs a number",
+            ),
+            (
+                field("a", FilterOp::FilterLt, Some(ValueKind::DoubleValue(f64::NAN))),
+                "range filter on `a` needs a finite number",
+            ),
+        ];
+        for (filter, want) in cases {
+            assert_eq!(rejected(&filter), want);
+        }
+    }
+
+    #[test]
+    fn filters_have_a_node_budget() {
+        let leaf = || field("a", FilterOp::FilterExists, None);
+        // The root plus 63 leaves is exactly the budget.
+        assert!(compile(&all((0..MAX_FILTER_NODES - 1).map(|_| leaf()).collect())).is_ok());
+        let over = all((0..MAX_FILTER_NODES).map(|_| leaf()).collect());
+        assert_eq!(rejected(&over), "filter has more than 64 nodes");
+
+        // Nesting counts every level.
+        let deep = (0..MAX_FILTER_NODES).fold(leaf(), |f, _| any(vec![f]));
+        assert_eq!(rejected(&deep), "filter has more than 64 nodes");
+        let fits = (0..MAX_FILTER_NODES - 1).fold(leaf(), |f, _| any(vec![f]));
+        assert!(compile(&fits).is_ok());
+    }
+
+    #[test]
+    fn cursors_decode_against_their_keys() {
+        let keys = [Key::Attr("name".into()), Key::Col("id")];
+        assert_eq!(decode_cursor(b"", &keys).unwrap(), None);
+        assert_eq!(
+            decode_cursor(br#"[{"a":[1]},42]"#, &keys).unwrap(),
+            Some(vec![json!({"a": [1]}), json!(42)])
+        );
+        assert_eq!(
+            decode_cursor(br#"[null,-1]"#, &keys).unwrap(),
+            Some(vec![Json::Null, json!(-1)])
+        );
+
+        let deep = format!("[{}1{},1]", "[".repeat(200), "]".repeat(200));
+        let too_long = serde_json::to_vec(&vec![json!(1); 1000]).unwrap();
+        let bad: [&[u8]; 10] = [
+            b"not json",
+            b"{\"a\":1}",
+            b"[]",
+            b"[1]",
+            b"[1,2,3]",
+            b"[\"x\",\"1\"]",
+            b"[\"x\",1.5]",
+            b"[\"x\",18446744073709551615]",
+            deep.as_bytes(),
+            &too_long,
+        ];
+        for cursor in bad {
+            let status = decode_cursor(cursor, &keys).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+            assert_eq!(status.message(), "malformed cursor");
+        }
+    }
+
+    #[test]
+    fn pages_resume_after_the_cursor() {
+        let keys = [Key::Attr("name".into()), Key::Col("id")];
+        let after = [json!("ada"), json!(7)];
+
+        let mut qb = QueryBuilder::<Postgres>::new("");
+        push_page(&mut qb, &keys, Some(&after), Order::Asc as i32, 10);
+        assert_eq!(
+            qb.sql(),
+            " AND (COALESCE(attributes -> $1, 'null'::jsonb), id) > ($2::jsonb, $3) \
+             ORDER BY COALESCE(attributes -> $4, 'null'::jsonb) ASC, id ASC LIMIT $5"
+        );
+
+        let mut qb = QueryBuilder::<Postgres>::new("");
+        push_page(&mut qb, &keys[1..], None, Order::Desc as i32, 10);
+        assert_eq!(qb.sql(), " ORDER BY id DESC LIMIT $1");
+    }
+}
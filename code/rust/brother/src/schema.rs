@@ -0,0 +1,711 @@
+//! src/schema.rs
+//! Per-type attribute schemas.
+//!
+//! Schemas live in `tao.object_schemas` / `tao.association_schemas` as the
+//! JSON form of [`Schema`]; the API speaks `TypeSchema`.  Writes load the
+//! schemas of the types they touch and [`check_object`] /
+//! [`check_association`] every item before anything reaches the database.
+
+use std::collections::{BTreeMap, HashMap};
+
+use brother::pb::{
+    schema_target, value::Kind, Association, FieldSchema, FieldType, Object, SchemaTarget,
+    TypeSchema, Value,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as Json;
+use sqlx::{
+    postgres::{PgArguments, PgRow},
+    query::Query,
+    PgConnection, Postgres, Row,
+};
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, FieldViolation, StatusExt};
+
+use crate::value;
+
+/// Stored form of a `TypeSchema`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Schema {
+    #[serde(default)]
+    fields: BTreeMap<String, Field>,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Field {
+    #[serde(rename = "type", default)]
+    kind: FieldKind,
+    #[serde(default)]
+    required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    one_of: Vec<Json>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum FieldKind {
+    #[default]
+    Any,
+    Int,
+    Uint,
+    Double,
+    Bool,
+    String,
+    Bytes,
+    List,
+    Map,
+    Timestamp,
+}
+
+impl FieldKind {
+    fn from_pb(t: FieldType) -> Self {
+        match t {
+            FieldType::FieldAny => Self::Any,
+            FieldType::FieldInt => Self::Int,
+            FieldType::FieldUint => Self::Uint,
+            FieldType::FieldDouble => Self::Double,
+            FieldType::FieldBool => Self::Bool,
+            FieldType::FieldString => Self::String,
+            FieldType::FieldBytes => Self::Bytes,
+            FieldType::FieldList => Self::List,
+            FieldType::FieldMap => Self::Map,
+            FieldType::FieldTimestamp => Self::Timestamp,
+        }
+    }
+
+    fn to_pb(self) -> FieldType {
+        match self {
+            Self::Any => FieldType::FieldAny,
+            Self::Int => FieldType::FieldInt,
+            Self::Uint => FieldType::FieldUint,
+            Self::Double => FieldType::FieldDouble,
+            Self::Bool => FieldType::FieldBool,
+            Self::String => FieldType::FieldString,
+            Self::Bytes => FieldType::FieldBytes,
+            Self::List => FieldType::FieldList,
+            Self::Map => FieldType::FieldMap,
+            Self::Timestamp => FieldType::FieldTimestamp,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Any => "any",
+            Self::Int => "int",
+            Self::Uint => "uint",
+            Self::Double => "double",
+            Self::Bool => "bool",
+            Self::String => "string",
+            Self::Bytes => "bytes",
+            Self::List => "list",
+            Self::Map => "map",
+            Self::Timestamp => "timestamp",
+        }
+    }
+
+    fn accepts(self, kind: &Kind) -> bool {
+        match (self, kind) {
+            (Self::Any, _) => true,
+            (Self::Int, Kind::IntValue(_)) => true,
+            (Self::Int, Kind::UintValue(u)) => i64::try_from(*u).is_ok(),
+            (Self::Uint, Kind::UintValue(_)) => true,
+            (Self::Uint, Kind::IntValue(i)) => *i >= 0,
+            (Self::Double, Kind::IntValue(_) | Kind::UintValue(_) | Kind::DoubleValue(_)) => true,
+            (Self::Bool, Kind::BoolValue(_)) => true,
+            (Self::String, Kind::StringValue(_)) => true,
+            (Self::Bytes, Kind::BytesValue(_)) => true,
+            (Self::List, Kind::ListValue(_)) => true,
+            (Self::Map, Kind::MapValue(_)) => true,
+            (Self::Timestamp, Kind::TimestampValue(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Name of the kind a value carries, for violation messages.
+fn kind_name(kind: &Kind) -> &'static str {
+    match kind {
+        Kind::NullValue(_) => "null",
+        Kind::IntValue(_) => "int",
+        Kind::UintValue(_) => "uint",
+        Kind::DoubleValue(_) => "double",
+        Kind::BoolValue(_) => "bool",
+        Kind::StringValue(_) => "string",
+        Kind::BytesValue(_) => "bytes",
+        Kind::ListValue(_) => "list",
+        Kind::MapValue(_) => "map",
+        Kind::TimestampValue(_) => "timestamp",
+    }
+}
+
+/// What `min` / `max` bound: the number itself or the length.
+fn measure(kind: &Kind) -> Option<(f64, &'static str)> {
+    Some(match kind {
+        Kind::IntValue(i) => (*i as f64, "value"),
+        Kind::UintValue(u) => (*u as f64, "value"),
+        Kind::DoubleValue(d) => (*d, "value"),
+        Kind::StringValue(s) => (s.chars().count() as f64, "length"),
+        Kind::BytesValue(b) => (b.len() as f64, "length"),
+        Kind::ListValue(l) => (l.values.len() as f64, "length"),
+        Kind::MapValue(m) => (m.fields.len() as f64, "length"),
+        _ => return None,
+    })
+}
+
+impl Field {
+    /// Why `value` breaks this field, if it does.
+    fn violation(&self, value: &Value) -> Option<String> {
+        let kind = match &value.kind {
+            None | Some(Kind::NullValue(_)) if self.required => {
+                return Some("is required".to_owned())
+            }
+            None | Some(Kind::NullValue(_)) => return None,
+            Some(kind) => kind,
+        };
+        if !self.kind.accepts(kind) {
+            return Some(format!(
+                "expected {}, got {}",
+                self.kind.name(),
+                kind_name(kind)
+            ));
+        }
+        if let Some((n, what)) = measure(kind) {
+            // NaN compares false both ways; infinities are no better.
+            if !n.is_finite() && (self.min.is_some() || self.max.is_some()) {
+                return Some(format!("{what} must be a finite number"));
+            }
+            if let Some(min) = self.min.filter(|&min| n < min) {
+                return Some(format!("{what} must be at least {min}"));
+            }
+            if let Some(max) = self.max.filter(|&max| n > max) {
+                return Some(format!("{what} must be at most {max}"));
+            }
+        }
+        if !self.one_of.is_empty() && !self.one_of.contains(&value::to_json(value)) {
+            return Some(format!("must be one of {}", Json::from(self.one_of.clone())));
+        }
+        None
+    }
+}
+
+impl Schema {
+    /// Accept a `TypeSchema` from the API, rejecting nonsense.
+    pub fn from_pb(pb: TypeSchema) -> Result<Self, Status> {
+        let mut violations = Vec::new();
+        let mut fields = BTreeMap::new();
+
+        for (name, f) in pb.fields {
+            let at = format!("schema.fields.{name}");
+            let Ok(t) = FieldType::try_from(f.r#type) else {
+                violations.push(FieldViolation::new(at, "unknown field type"));
+                continue;
+            };
+            let bounds = [f.min, f.max];
+            if bounds.iter().flatten().any(|b| !b.is_finite()) {
+                violations.push(FieldViolation::new(at, "min / max must be finite"));
+                continue;
+            }
+            if let [Some(min), Some(max)] = bounds {
+                if min > max {
+                    violations.push(FieldViolation::new(at, "min is greater than max"));
+                    continue;
+                }
+            }
+            fields.insert(
+                name,
+                Field {
+                    kind: FieldKind::from_pb(t),
+                    required: f.required,
+                    min: f.min,
+                    max: f.max,
+                    one_of: f.one_of.iter().map(value::to_json).collect(),
+                },
+            );
+        }
+
+        if !violations.is_empty() {
+            return Err(bad_request("invalid schema", violations));
+        }
+        Ok(Self {
+            fields,
+            closed: pb.closed,
+        })
+    }
+
+    pub fn to_pb(&self) -> TypeSchema {
+        TypeSchema {
+            fields: self
+                .fields
+                .iter()
+                .map(|(name, f)| {
+                    let pb = FieldSchema {
+                        r#type: f.kind.to_pb() as i32,
+                        required: f.required,
+                        min: f.min,
+                        max: f.max,
+                        one_of: f.one_of.iter().cloned().map(value::from_json).collect(),
+                    };
+                    (name.clone(), pb)
+                })
+                .collect(),
+            closed: self.closed,
+        }
+    }
+
+    fn to_json(&self) -> Json {
+        serde_json::to_value(self).expect("schema serialises")
+    }
+
+    fn from_json(json: Json) -> sqlx::Result<Self> {
+        serde_json::from_value(json).map_err(|e| sqlx::Error::Decode(Box::new(e)))
+    }
+
+    /// Every way `attrs` breaks the schema, reported under `path`.
+    fn violations(&self, path: &str, attrs: &HashMap<String, Value>) -> Vec<FieldViolation> {
+        let mut out = Vec::new();
+        for (name, field) in &self.fields {
+            let problem = match attrs.get(name) {
+                Some(v) => field.violation(v),
+                None if field.required => Some("is required".to_owned()),
+                None => None,
+            };
+            if let Some(problem) = problem {
+                out.push(FieldViolation::new(format!("{path}.{name}"), problem));
+            }
+        }
+        if self.closed {
+            let mut unknown: Vec<_> = attrs
+                .keys()
+                .filter(|k| !self.fields.contains_key(*k))
+                .collect();
+            unknown.sort();
+            for name in unknown {
+                out.push(FieldViolation::new(
+                    format!("{path}.{name}"),
+                    "is not in the schema",
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// `INVALID_ARGUMENT` with a `BadRequest` detail.
+fn bad_request(message: &str, violations: Vec<FieldViolation>) -> Status {
+    Status::with_error_details(
+        Code::InvalidArgument,
+        message,
+        ErrorDetails::with_bad_request(violations),
+    )
+}
+
+/// Which type a schema belongs to.
+#[derive(Debug, Clone)]
+pub enum Target {
+    Object(u32),
+    Association(String),
+}
+
+impl Target {
+    pub fn from_pb(pb: Option<SchemaTarget>) -> Result<Self, Status> {
+        match pb.and_then(|t| t.target) {
+            Some(schema_target::Target::Otype(t)) => Ok(Self::Object(t)),
+            Some(schema_target::Target::Atype(t)) if !t.is_empty() => Ok(Self::Association(t)),
+            _ => Err(Status::invalid_argument("target otype or atype is required")),
+        }
+    }
+
+    pub fn to_pb(&self) -> SchemaTarget {
+        let target = match self {
+            Self::Object(t) => schema_target::Target::Otype(*t),
+            Self::Association(t) => schema_target::Target::Atype(t.clone()),
+        };
+        SchemaTarget { target: Some(target) }
+    }
+
+    fn table(&self) -> &'static str {
+        match self {
+            Self::Object(_) => "tao.object_schemas",
+            Self::Association(_) => "tao.association_schemas",
+        }
+    }
+
+    fn bind<'q>(&'q self, q: Query<'q, Postgres, PgArguments>) -> Query<'q, Postgres, PgArguments> {
+        match self {
+            Self::Object(t) => q.bind(*t as i32),
+            Self::Association(t) => q.bind(t.as_str()),
+        }
+    }
+}
+
+/// Create or replace a schema; `true` if it is new.
+pub async fn put(conn: &mut PgConnection, target: &Target, schema: &Schema) -> sqlx::Result<bool> {
+    let sql = format!(
+        r#"INSERT INTO {} (type, schema) VALUES ($1, $2)
+           ON CONFLICT (type) DO UPDATE
+                 SET schema = EXCLUDED.schema, updated_at = now()
+           RETURNING (xmax = 0) AS created"#,
+        target.table()
+    );
+    let row = target.bind(sqlx::query(&sql)).bind(schema.to_json()).fetch_one(conn).await?;
+    Ok(row.get("created"))
+}
+
+pub async fn get(conn: &mut PgConnection, target: &Target) -> sqlx::Result<Option<Schema>> {
+    let sql = format!(r#"SELECT schema FROM {} WHERE type = $1"#, target.table());
+    let row: Option<PgRow> = target.bind(sqlx::query(&sql)).fetch_optional(conn).await?;
+    row.map(|r| Schema::from_json(r.get("schema"))).transpose()
+}
+
+/// Drop a schema; `true` if there was one.
+pub async fn delete(conn: &mut PgConnection, target: &Target) -> sqlx::Result<bool> {
+    let sql = format!(r#"DELETE FROM {} WHERE type = $1"#, target.table());
+    let done = target.bind(sqlx::query(&sql)).execute(conn).await?;
+    Ok(done.rows_affected() > 0)
+}
+
+/// Schemas of the given object types (types without one are absent).
+pub async fn for_objects(
+    conn: &mut PgConnection,
+    types: &[u32],
+) -> sqlx::Result<HashMap<u32, Schema>> {
+    let rows = sqlx::query(r#"SELECT type, schema FROM tao.object_schemas WHERE type = ANY($1)"#)
+        .bind(types.iter().map(|&t| t as i32).collect::<Vec<_>>())
+        .fetch_all(conn)
+        .await?;
+    rows.into_iter()
+        .map(|r| Ok((r.get::<i32, _>("type") as u32, Schema::from_json(r.get("schema"))?)))
+        .collect()
+}
+
+/// Schemas of the given association types (types without one are absent).
+pub async fn for_associations(
+    conn: &mut PgConnection,
+    types: &[&str],
+) -> sqlx::Result<HashMap<String, Schema>> {
+    let rows = sqlx::query(r#"SELECT type, schema FROM tao.association_schemas WHERE type = ANY($1)"#)
+        .bind(types)
+        .fetch_all(conn)
+        .await?;
+    rows.into_iter()
+        .map(|r| Ok((r.get("type"), Schema::from_json(r.get("schema"))?)))
+        .collect()
+}
+
+/// Every stored schema, objects first.
+pub async fn all(conn: &mut PgConnection) -> sqlx::Result<Vec<(Target, Schema)>> {
+    let objects = sqlx::query(r#"SELECT type, schema FROM tao.object_schemas ORDER BY type"#)
+        .fetch_all(&mut *conn)
+        .await?;
+    let associations = sqlx::query(r#"SELECT type, schema FROM tao.association_schemas ORDER BY type"#)
+        .fetch_all(conn)
+        .await?;
+
+    let objects = objects
+        .into_iter()
+        .map(|r| Ok((Target::Object(r.get::<i32, _>("type") as u32), Schema::from_json(r.get("schema"))?)));
+    let associations = associations
+        .into_iter()
+        .map(|r| Ok((Target::Association(r.get("type")), Schema::from_json(r.get("schema"))?)));
+    objects.chain(associations).collect()
+}
+
+/// Check an object against its type's schema, if it has one.
+pub fn check_object(schemas: &HashMap<u32, Schema>, obj: &Object) -> Result<(), Status> {
+    let Some(schema) = schemas.get(&obj.r#type) else {
+        return Ok(());
+    };
+    let violations = schema.violations("attributes", &obj.attributes);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(bad_request(
+        &format!("object does not match the schema of type {}", obj.r#type),
+        violations,
+    ))
+}
+
+/// Check an association against its type's schema, if it has one.
+pub fn check_association(
+    schemas: &HashMap<String, Schema>,
+    a: &Association,
+) -> Result<(), Status> {
+    let Some(schema) = schemas.get(&a.r#type) else {
+        return Ok(());
+    };
+    let violations = schema.violations("attributes", &a.attributes);
+    if violations.is_empty() {
+        return Ok(());
+    }
+    Err(bad_request(
+        &format!("association does not match the schema of type {}", a.r#type),
+        violations,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::Timestamp;
+
+    use super::*;
+
+    fn v(kind: Kind) -> Value {
+        Value { kind: Some(kind) }
+    }
+
+    fn field(t: FieldType, required: bool) -> FieldSchema {
+        FieldSchema {
+            r#type: t as i32,
+            required,
+            ..Default::default()
+        }
+    }
+
+    /// A closed schema: `name` (required string), `age` (int 0..=150),
+    /// `tags` (list), `seen` (timestamp) and `color` (one of red / blue).
+    fn schema() -> Schema {
+        let mut color = field(FieldType::FieldString, false);
+        color.one_of = vec![v(Kind::StringValue("red".into())), v(Kind::StringValue("blue".into()))];
+        let mut age = field(FieldType::FieldInt, false);
+        age.min = Some(0.0);
+        age.max = Some(150.0);
+        let mut score = field(FieldType::FieldDouble, false);
+        score.max = Some(1.0);
+        Schema::from_pb(TypeSchema {
+            fields: HashMap::from([
+                ("name".to_owned(), field(FieldType::FieldString, true)),
+                ("age".to_owned(), age),
+                ("score".to_owned(), score),
+                ("tags".to_owned(), field(FieldType::FieldList, false)),
+                ("seen".to_owned(), field(FieldType::FieldTimestamp, false)),
+                ("color".to_owned(), color),
+            ]),
+            closed: true,
+        })
+        .unwrap()
+    }
+
+    fn attrs(items: &[(&str, Kind)]) -> HashMap<String, Value> {
+        items.iter().map(|(k, kind)| (k.to_string(), v(kind.clone()))).collect()
+    }
+
+    /// The `(field, description)` pairs of a `BadRequest` status.
+    fn violations(status: Status) -> Vec<(String, String)> {
+        assert_eq!(status.code(), Code::InvalidArgument);
+        let details = status.get_error_details();
+        let bad = details.bad_request().expect("BadRequest detail");
+        bad.field_violations
+            .iter()
+            .map(|f| (f.field.clone(), f.description.clone()))
+            .collect()
+    }
+
+    /// Name, attributes, expected `(field, description)` violations.
+    type Case = (&'static str, Vec<(&'static str, Kind)>, Vec<(&'static str, &'static str)>);
+
+    fn name() -> (&'static str, Kind) {
+        ("name", Kind::StringValue("ada".into()))
+    }
+
+    #[test]
+    fn object_violations() {
+        let cases: Vec<Case> = vec![
+            ("valid", vec![name()], vec![]),
+            (
+                "every field valid",
+                vec![
+                    name(),
+                    ("age", Kind::UintValue(40)),
+                    ("tags", Kind::ListValue(Default::default())),
+                    ("seen", Kind::TimestampValue(Timestamp::default())),
+                    ("color", Kind::StringValue("red".into())),
+                    ("score", Kind::DoubleValue(0.5)),
+                ],
+                vec![],
+            ),
+            ("missing required", vec![], vec![("attributes.name", "is required")]),
+            (
+                "null required",
+                vec![("name", Kind::NullValue(0))],
+                vec![("attributes.name", "is required")],
+            ),
+            ("null optional", vec![name(), ("age", Kind::NullValue(0))], vec![]),
+            (
+                "extra",
+                vec![name(), ("zip", Kind::IntValue(1)), ("alias", Kind::BoolValue(true))],
+                vec![
+                    ("attributes.alias", "is not in the schema"),
+                    ("attributes.zip", "is not in the schema"),
+                ],
+            ),
+            (
+                "mistyped",
+                vec![("name", Kind::IntValue(1)), ("tags", Kind::MapValue(Default::default()))],
+                vec![
+                    ("attributes.name", "expected string, got int"),
+                    ("attributes.tags", "expected list, got map"),
+                ],
+            ),
+            (
+                "uint too big for int",
+                vec![name(), ("age", Kind::UintValue(u64::MAX))],
+                vec![("attributes.age", "expected int, got uint")],
+            ),
+            (
+                "below min",
+                vec![name(), ("age", Kind::IntValue(-1))],
+                vec![("attributes.age", "value must be at least 0")],
+            ),
+            (
+                "above max",
+                vec![name(), ("age", Kind::IntValue(151))],
+                vec![("attributes.age", "value must be at most 150")],
+            ),
+            (
+                "not a number",
+                vec![
+                    name(),
+                    ("score", Kind::DoubleValue(f64::NAN)),
+                    ("age", Kind::DoubleValue(f64::NEG_INFINITY)),
+                ],
+                vec![
+                    ("attributes.age", "expected int, got double"),
+                    ("attributes.score", "value must be a finite number"),
+                ],
+            ),
+            (
+                "not one of",
+                vec![name(), ("color", Kind::StringValue("green".into()))],
+                vec![("attributes.color", r#"must be one of ["red","blue"]"#)],
+            ),
+            (
+                "everything at once",
+                vec![("age", Kind::StringValue("old".into())), ("zip", Kind::IntValue(1))],
+                vec![
+                    ("attributes.age", "expected int, got string"),
+                    ("attributes.name", "is required"),
+                    ("attributes.zip", "is not in the schema"),
+                ],
+            ),
+        ];
+
+        let schemas = HashMap::from([(7, schema())]);
+        for (case, items, want) in cases {
+            let obj = Object {
+                r#type: 7,
+                attributes: attrs(&items),
+                ..Default::default()
+            };
+            let got = match check_object(&schemas, &obj) {
+                Ok(()) => vec![],
+                Err(status) => {
+                    assert_eq!(status.message(), "object does not match the schema of type 7");
+                    violations(status)
+                }
+            };
+            let want: Vec<_> = want.into_iter().map(|(f, d)| (f.to_owned(), d.to_owned())).collect();
+            assert_eq!(got, want, "{case}");
+        }
+    }
+
+    #[test]
+    fn association_violations() {
+        let cases: Vec<Case> = vec![
+            ("valid", vec![name()], vec![]),
+            ("missing required", vec![], vec![("attributes.name", "is required")]),
+            (
+                "extra",
+                vec![name(), ("zip", Kind::IntValue(1))],
+                vec![("attributes.zip", "is not in the schema")],
+            ),
+            (
+                "mistyped",
+                vec![name(), ("seen", Kind::IntValue(0))],
+                vec![("attributes.seen", "expected timestamp, got int")],
+            ),
+        ];
+
+        let schemas = HashMap::from([("likes".to_owned(), schema())]);
+        for (case, items, want) in cases {
+            let a = Association {
+                r#type: "likes".into(),
+                attributes: attrs(&items),
+                ..Default::default()
+            };
+            let got = match check_association(&schemas, &a) {
+                Ok(()) => vec![],
+                Err(status) => {
+                    assert_eq!(status.message(), "association does not match the schema of type likes");
+                    violations(status)
+                }
+            };
+            let want: Vec<_> = want.into_iter().map(|(f, d)| (f.to_owned(), d.to_owned())).collect();
+            assert_eq!(got, want, "{case}");
+        }
+    }
+
+    #[test]
+    fn open_schemas_allow_extra_fields() {
+        let mut open = schema();
+        open.closed = false;
+        let obj = Object {
+            r#type: 7,
+            attributes: attrs(&[name(), ("zip", Kind::IntValue(1))]),
+            ..Default::default()
+        };
+        assert!(check_object(&HashMap::from([(7, open)]), &obj).is_ok());
+    }
+
+    #[test]
+    fn types_without_a_schema_pass() {
+        let obj = Object {
+            r#type: 8,
+            attributes: attrs(&[("anything", Kind::BoolValue(true))]),
+            ..Default::default()
+        };
+        assert!(check_object(&HashMap::from([(7, schema())]), &obj).is_ok());
+        let a = Association {
+            r#type: "follows".into(),
+            ..Default::default()
+        };
+        assert!(check_association(&HashMap::new(), &a).is_ok());
+    }
+
+    #[test]
+    fn nonsense_schemas_are_rejected() {
+        let mut inverted = field(FieldType::FieldInt, false);
+        inverted.min = Some(2.0);
+        inverted.max = Some(1.0);
+        let mut infinite = field(FieldType::FieldDouble, false);
+        infinite.max = Some(f64::INFINITY);
+        let unknown = FieldSchema {
+            r#type: 99,
+            ..Default::default()
+        };
+        let status = Schema::from_pb(TypeSchema {
+            fields: HashMap::from([
+                ("a".to_owned(), inverted),
+                ("b".to_owned(), infinite),
+                ("c".to_owned(), unknown),
+            ]),
+            closed: false,
+        })
+        .unwrap_err();
+        let mut got = violations(status);
+        got.sort();
+        assert_eq!(
+            got,
+            [
+                ("schema.fields.a".to_owned(), "min is greater than max".to_owned()),
+                ("schema.fields.b".to_owned(), "min / max must be finite".to_owned()),
+                ("schema.fields.c".to_owned(), "unknown field type".to_owned()),
+            ]
+        );
+    }
+}
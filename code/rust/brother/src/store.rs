@@ -0,0 +1,138 @@
+//! src/store.rs
+//! Objects and associations behind `BrotherService`.
+//!
+//! [`TaoStore`] is the TAO core: optimistic versioning, tombstones and
+//! restore, expiry, inverse edges kept in step, per-source counts and
+//! edge lists ordered by position – all scoped to a tenant.
+//! [`PgStore`] runs it on the `tao_*` functions; `MemoryStore` keeps the
+//! same semantics in process for tests, and `conformance` holds both to
+//! them.
+//!
+//! Schemas, unique keys, version history, attribute queries and change
+//! feeds are built on Postgres itself and stay with the pool.
+
+use std::time::Duration;
+
+use brother::pb::{Association, Object, WriteOp, WriteOpResult};
+use tonic::Status;
+
+use crate::auth::Tenant;
+
+#[cfg(test)]
+mod conformance;
+#[cfg(test)]
+mod memory;
+mod postgres;
+
+#[cfg(test)]
+pub use memory::MemoryStore;
+pub use postgres::PgStore;
+
+/// Outcome of a put: the id written (generated for `id = 0`) and the
+/// version after the write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Written {
+    pub id: u64,
+    pub created: bool,
+    pub version: u32,
+}
+
+/// One page of a source's edges of a type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssocRange {
+    pub atype: String,
+    pub source_id: i64,
+    /// Strictly after this `(position, target_id)` in walking order.
+    pub after: Option<(i64, i64)>,
+    pub ascending: bool,
+    /// `time` window, from inclusive, to exclusive.
+    pub time_from: Option<u64>,
+    pub time_to: Option<u64>,
+    pub limit: i64,
+}
+
+#[tonic::async_trait]
+pub trait TaoStore: Send + Sync {
+    /// The live object: not deleted, not expired.
+    async fn get_object(&self, tenant: Tenant, otype: u32, id: u64) -> Result<Option<Object>, Status>;
+
+    /// `get_object` for each `(otype, id)`, in order.
+    async fn get_objects(
+        &self,
+        tenant: Tenant,
+        keys: &[(u32, u64)],
+    ) -> Result<Vec<Option<Object>>, Status>;
+
+    /// Create (`id = 0` or an id not taken) or update at `object.version`.
+    /// A stale version fails with ABORTED, a tombstone with
+    /// FAILED_PRECONDITION.
+    async fn put_object(&self, tenant: Tenant, object: &Object) -> Result<Written, Status>;
+
+    /// `put_object` for each object; one failing does not stop the others.
+    async fn put_objects(
+        &self,
+        tenant: Tenant,
+        objects: &[Object],
+    ) -> Result<Vec<Result<Written, Status>>, Status>;
+
+    /// Tombstone the object, which bumps its version.  `false` if there
+    /// was nothing live to delete.
+    async fn delete_object(
+        &self,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        expected_version: Option<u32>,
+    ) -> Result<bool, Status>;
+
+    /// Undo a delete less than `window` old.  The version after, or
+    /// `None` if there was nothing to restore.
+    async fn restore_object(
+        &self,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        window: Duration,
+    ) -> Result<Option<u32>, Status>;
+
+    /// Create or overwrite the edge and its registered inverse together;
+    /// a deleted edge comes back.
+    async fn put_association(&self, tenant: Tenant, association: &Association) -> Result<(), Status>;
+
+    /// `put_association` for each edge; one failing does not stop the
+    /// others.
+    async fn put_associations(
+        &self,
+        tenant: Tenant,
+        associations: &[Association],
+    ) -> Result<Vec<Result<(), Status>>, Status>;
+
+    /// Delete the edge and its inverse.  Whether the edge itself existed.
+    async fn delete_association(
+        &self,
+        tenant: Tenant,
+        atype: &str,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<bool, Status>;
+
+    /// Live edges of `range`, ordered by `(position, target_id)`, at most
+    /// `range.limit`.
+    async fn associations(&self, tenant: Tenant, range: &AssocRange) -> Result<Vec<Association>, Status>;
+
+    /// Live edges of `atype` out of `source_id`.
+    async fn count_associations(&self, tenant: Tenant, atype: &str, source_id: i64) -> Result<u64, Status>;
+
+    /// Every op or none.  Ops are checked (see `BrotherService::validate_ops`)
+    /// before they get here; a failing op's error starts with `ops[i]: `.
+    async fn write(&self, tenant: Tenant, ops: &[WriteOp]) -> Result<Vec<WriteOpResult>, Status>;
+}
+
+/// Prefix an error with where it happened.
+pub fn at(prefix: &str, status: Status) -> Status {
+    Status::with_details(
+        status.code(),
+        format!("{prefix}: {}", status.message()),
+        status.details().to_vec().into(),
+    )
+}
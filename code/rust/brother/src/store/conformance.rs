@@ -0,0 +1,395 @@
+//! src/store/conformance.rs
+//! What every [`TaoStore`] must do, run against each of them.
+//!
+//! The Postgres half is `#[ignore]`d: it runs under `--ignored` against
+//! the database `BROTHER_TEST_DATABASE_URL` names, which it may migrate.
+//! Each case works in a fresh tenant, so cases and repeated runs never
+//! see each other.
+
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use brother::pb::{
+    value::Kind, write_op::Op, Association, DeleteObjectOp, Object, UpsertAssociationOp, Value,
+    WriteOp,
+};
+use tonic::Code;
+use tonic_types::StatusExt;
+
+use super::{AssocRange, MemoryStore, PgStore, TaoStore};
+use crate::auth::Tenant;
+use crate::db;
+
+macro_rules! conformance {
+    ($($case:ident),* $(,)?) => {
+        mod memory {
+            $(
+                #[tokio::test]
+                async fn $case() {
+                    super::$case(&super::MemoryStore::new(), crate::db::test_tenant()).await;
+                }
+            )*
+        }
+
+        mod postgres {
+            $(
+                #[tokio::test]
+                #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+                async fn $case() {
+                    super::$case(&super::postgres().await, crate::db::test_tenant()).await;
+                }
+            )*
+        }
+    };
+}
+
+conformance!(
+    create_assigns_id_at_version_zero,
+    create_at_unused_id,
+    update_needs_current_version,
+    delete_tombstones_until_restored,
+    restore_only_within_window,
+    delete_checks_expected_version,
+    tenants_are_isolated,
+    expired_rows_are_hidden,
+    edges_ordered_by_position_then_target,
+    edge_upsert_overwrites_and_counts_once,
+    inverse_edges_follow,
+    batch_put_fails_items_separately,
+    batch_edges_write_inverses,
+    batch_edges_fail_per_item,
+    write_is_all_or_nothing,
+    write_links_earlier_puts,
+);
+
+async fn postgres() -> PgStore {
+    PgStore::new(Arc::new(db::test_pool().await))
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64
+}
+
+fn object(otype: u32, id: u64, version: u32, name: &str) -> Object {
+    Object {
+        r#type: otype,
+        id,
+        version,
+        attributes: HashMap::from([(
+            "name".to_owned(),
+            Value {
+                kind: Some(Kind::StringValue(name.to_owned())),
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+fn edge(atype: &str, source_id: u64, target_id: u64, position: u64, time: u64) -> Association {
+    Association {
+        r#type: atype.to_owned(),
+        source_id,
+        target_id,
+        position,
+        time,
+        ..Default::default()
+    }
+}
+
+fn range(atype: &str, source_id: u64, ascending: bool) -> AssocRange {
+    AssocRange {
+        atype: atype.to_owned(),
+        source_id: source_id as i64,
+        after: None,
+        ascending,
+        time_from: None,
+        time_to: None,
+        limit: 100,
+    }
+}
+
+fn targets(edges: &[Association]) -> Vec<u64> {
+    edges.iter().map(|a| a.target_id).collect()
+}
+
+fn name(o: &Object) -> &str {
+    match o.attributes.get("name").and_then(|v| v.kind.as_ref()) {
+        Some(Kind::StringValue(s)) => s,
+        _ => "",
+    }
+}
+
+// ─────────────────── Objects ───────────────────
+
+async fn create_assigns_id_at_version_zero(store: &dyn TaoStore, t: Tenant) {
+    let w = store.put_object(t, &object(3, 0, 0, "a")).await.unwrap();
+    assert!(w.created);
+    assert_eq!(w.version, 0);
+    assert_ne!(w.id, 0);
+    assert_eq!((w.id >> 10) & 127, 3, "type is encoded in the id");
+
+    let got = store.get_object(t, 3, w.id).await.unwrap().unwrap();
+    assert_eq!((got.tenant, got.r#type, got.id, got.version), (t.0, 3, w.id, 0));
+    assert_eq!(name(&got), "a");
+
+    let other = store.put_object(t, &object(3, 0, 0, "b")).await.unwrap();
+    assert_ne!(other.id, w.id);
+}
+
+async fn create_at_unused_id(store: &dyn TaoStore, t: Tenant) {
+    // The version of a create is not checked.
+    let w = store.put_object(t, &object(1, 42, 7, "a")).await.unwrap();
+    assert_eq!((w.id, w.created, w.version), (42, true, 0));
+    assert!(store.get_object(t, 2, 42).await.unwrap().is_none(), "ids are per type");
+}
+
+async fn update_needs_current_version(store: &dyn TaoStore, t: Tenant) {
+    let id = store.put_object(t, &object(1, 0, 0, "a")).await.unwrap().id;
+
+    let w = store.put_object(t, &object(1, id, 0, "b")).await.unwrap();
+    assert_eq!((w.id, w.created, w.version), (id, false, 1));
+
+    let stale = store.put_object(t, &object(1, id, 0, "c")).await.unwrap_err();
+    assert_eq!(stale.code(), Code::Aborted);
+    let info = stale.get_error_details().error_info().cloned().unwrap();
+    assert_eq!(info.reason, "VERSION_CLASH");
+    assert_eq!(info.metadata["current_version"], "1");
+
+    let got = store.get_object(t, 1, id).await.unwrap().unwrap();
+    assert_eq!((got.version, name(&got)), (1, "b"));
+}
+
+async fn delete_tombstones_until_restored(store: &dyn TaoStore, t: Tenant) {
+    let id = store.put_object(t, &object(1, 0, 0, "a")).await.unwrap().id;
+    let day = Duration::from_secs(86_400);
+
+    assert!(store.delete_object(t, 1, id, None).await.unwrap());
+    assert!(store.get_object(t, 1, id).await.unwrap().is_none());
+    assert!(!store.delete_object(t, 1, id, None).await.unwrap(), "already deleted");
+
+    let err = store.put_object(t, &object(1, id, 1, "b")).await.unwrap_err();
+    assert_eq!(err.code(), Code::FailedPrecondition);
+
+    // Delete and restore each bump the version.
+    assert_eq!(store.restore_object(t, 1, id, day).await.unwrap(), Some(2));
+    assert_eq!(store.restore_object(t, 1, id, day).await.unwrap(), None);
+    let got = store.get_object(t, 1, id).await.unwrap().unwrap();
+    assert_eq!((got.version, name(&got)), (2, "a"));
+
+    assert_eq!(store.restore_object(t, 1, 999, day).await.unwrap(), None);
+}
+
+async fn restore_only_within_window(store: &dyn TaoStore, t: Tenant) {
+    let id = store.put_object(t, &object(1, 0, 0, "a")).await.unwrap().id;
+    assert!(store.delete_object(t, 1, id, None).await.unwrap());
+    assert_eq!(store.restore_object(t, 1, id, Duration::ZERO).await.unwrap(), None);
+    assert!(store.get_object(t, 1, id).await.unwrap().is_none());
+}
+
+async fn delete_checks_expected_version(store: &dyn TaoStore, t: Tenant) {
+    let id = store.put_object(t, &object(1, 0, 0, "a")).await.unwrap().id;
+
+    let err = store.delete_object(t, 1, id, Some(5)).await.unwrap_err();
+    assert_eq!(err.code(), Code::Aborted);
+    assert!(store.get_object(t, 1, id).await.unwrap().is_some());
+
+    assert!(store.delete_object(t, 1, id, Some(0)).await.unwrap());
+    // Nothing live left to compare against.
+    assert!(!store.delete_object(t, 1, id, Some(5)).await.unwrap());
+    assert!(!store.delete_object(t, 1, 999, Some(0)).await.unwrap());
+}
+
+async fn tenants_are_isolated(store: &dyn TaoStore, t: Tenant) {
+    let other = Tenant(t.0 + 1);
+    store.put_object(t, &object(1, 42, 0, "mine")).await.unwrap();
+    store.put_association(t, &edge("follows", 42, 43, 1, 1)).await.unwrap();
+
+    assert!(store.get_object(other, 1, 42).await.unwrap().is_none());
+    assert!(!store.delete_object(other, 1, 42, None).await.unwrap());
+    assert_eq!(store.count_associations(other, "follows", 42).await.unwrap(), 0);
+    assert!(store.associations(other, &range("follows", 42, true)).await.unwrap().is_empty());
+
+    let w = store.put_object(other, &object(1, 42, 0, "theirs")).await.unwrap();
+    assert!(w.created);
+    let mine = store.get_object(t, 1, 42).await.unwrap().unwrap();
+    assert_eq!(name(&mine), "mine");
+}
+
+async fn expired_rows_are_hidden(store: &dyn TaoStore, t: Tenant) {
+    let past = now_ms() - 60_000;
+    let future = now_ms() + 3_600_000;
+
+    let gone = Object { expires_at: Some(past), ..object(1, 0, 0, "gone") };
+    let kept = Object { expires_at: Some(future), ..object(1, 0, 0, "kept") };
+    let gone = store.put_object(t, &gone).await.unwrap().id;
+    let kept = store.put_object(t, &kept).await.unwrap().id;
+
+    assert!(store.get_object(t, 1, gone).await.unwrap().is_none());
+    let got = store.get_objects(t, &[(1, gone), (1, kept)]).await.unwrap();
+    assert!(got[0].is_none());
+    assert_eq!(got[1].as_ref().unwrap().expires_at, Some(future));
+
+    store.put_association(t, &Association { expires_at: Some(past), ..edge("likes", 1, 2, 1, 1) }).await.unwrap();
+    store.put_association(t, &edge("likes", 1, 3, 2, 1)).await.unwrap();
+    assert_eq!(targets(&store.associations(t, &range("likes", 1, true)).await.unwrap()), [3]);
+    assert_eq!(store.count_associations(t, "likes", 1).await.unwrap(), 1);
+
+    // Overwriting an expired edge brings it back without counting it twice.
+    store.put_association(t, &edge("likes", 1, 2, 1, 1)).await.unwrap();
+    assert_eq!(store.count_associations(t, "likes", 1).await.unwrap(), 2);
+}
+
+// ─────────────────── Associations ───────────────────
+
+async fn edges_ordered_by_position_then_target(store: &dyn TaoStore, t: Tenant) {
+    // (target, position, time)
+    for (target, position, time) in [(10, 5, 100), (11, 1, 200), (12, 3, 300), (13, 3, 400)] {
+        store.put_association(t, &edge("follows", 1, target, position, time)).await.unwrap();
+    }
+    store.put_association(t, &edge("blocks", 1, 99, 0, 0)).await.unwrap();
+
+    let asc = store.associations(t, &range("follows", 1, true)).await.unwrap();
+    assert_eq!(targets(&asc), [11, 12, 13, 10]);
+    assert!(asc.iter().all(|a| a.tenant == t.0 && a.r#type == "follows" && a.source_id == 1));
+    let desc = store.associations(t, &range("follows", 1, false)).await.unwrap();
+    assert_eq!(targets(&desc), [10, 13, 12, 11]);
+
+    let page = AssocRange { limit: 2, ..range("follows", 1, true) };
+    assert_eq!(targets(&store.associations(t, &page).await.unwrap()), [11, 12]);
+    let next = AssocRange { after: Some((3, 12)), ..page.clone() };
+    assert_eq!(targets(&store.associations(t, &next).await.unwrap()), [13, 10]);
+    let back = AssocRange { after: Some((3, 13)), ascending: false, ..page };
+    assert_eq!(targets(&store.associations(t, &back).await.unwrap()), [12, 11]);
+
+    let window = AssocRange { time_from: Some(200), time_to: Some(400), ..range("follows", 1, true) };
+    assert_eq!(targets(&store.associations(t, &window).await.unwrap()), [11, 12]);
+}
+
+async fn edge_upsert_overwrites_and_counts_once(store: &dyn TaoStore, t: Tenant) {
+    store.put_association(t, &edge("follows", 1, 2, 1, 10)).await.unwrap();
+    store.put_association(t, &edge("follows", 1, 2, 9, 20)).await.unwrap();
+    assert_eq!(store.count_associations(t, "follows", 1).await.unwrap(), 1);
+
+    let got = store.associations(t, &range("follows", 1, true)).await.unwrap();
+    assert_eq!((got[0].position, got[0].time), (9, 20));
+
+    assert!(store.delete_association(t, "follows", 1, 2).await.unwrap());
+    assert!(!store.delete_association(t, "follows", 1, 2).await.unwrap());
+    assert_eq!(store.count_associations(t, "follows", 1).await.unwrap(), 0);
+    assert!(store.associations(t, &range("follows", 1, true)).await.unwrap().is_empty());
+
+    store.put_association(t, &edge("follows", 1, 2, 1, 10)).await.unwrap();
+    assert_eq!(store.count_associations(t, "follows", 1).await.unwrap(), 1);
+}
+
+async fn inverse_edges_follow(store: &dyn TaoStore, t: Tenant) {
+    store.put_association(t, &edge("has_factor", 1, 2, 5, 10)).await.unwrap();
+
+    let mirrored = store.associations(t, &range("is_securing", 2, true)).await.unwrap();
+    assert_eq!(targets(&mirrored), [1]);
+    assert_eq!((mirrored[0].position, mirrored[0].time), (5, 10));
+    assert_eq!(store.count_associations(t, "is_securing", 2).await.unwrap(), 1);
+
+    // Deleting from the inverse side takes both.
+    assert!(store.delete_association(t, "is_securing", 2, 1).await.unwrap());
+    assert_eq!(store.count_associations(t, "has_factor", 1).await.unwrap(), 0);
+    assert_eq!(store.count_associations(t, "is_securing", 2).await.unwrap(), 0);
+}
+
+// ─────────────────── Batches ───────────────────
+
+async fn batch_put_fails_items_separately(store: &dyn TaoStore, t: Tenant) {
+    let id = store.put_object(t, &object(1, 0, 0, "a")).await.unwrap().id;
+
+    let results = store
+        .put_objects(
+            t,
+            &[object(1, 0, 0, "new"), object(1, id, 3, "stale"), object(1, id, 0, "ok")],
+        )
+        .await
+        .unwrap();
+    assert!(results[0].as_ref().unwrap().created);
+    assert_eq!(results[1].as_ref().unwrap_err().code(), Code::Aborted);
+    assert_eq!(results[2].as_ref().unwrap().version, 1);
+
+    let ids: Vec<_> = [results[0].as_ref().unwrap().id, id, 7].map(|id| (1, id)).into();
+    let got = store.get_objects(t, &ids).await.unwrap();
+    assert_eq!(name(got[0].as_ref().unwrap()), "new");
+    assert_eq!(name(got[1].as_ref().unwrap()), "ok");
+    assert!(got[2].is_none());
+}
+
+async fn batch_edges_write_inverses(store: &dyn TaoStore, t: Tenant) {
+    let got = store
+        .put_associations(t, &[edge("has_factor", 1, 2, 1, 1), edge("follows", 1, 3, 1, 1)])
+        .await
+        .unwrap();
+    assert!(got.iter().all(Result::is_ok));
+    assert_eq!(store.count_associations(t, "has_factor", 1).await.unwrap(), 1);
+    assert_eq!(store.count_associations(t, "is_securing", 2).await.unwrap(), 1);
+    assert_eq!(store.count_associations(t, "follows", 1).await.unwrap(), 1);
+}
+
+async fn batch_edges_fail_per_item(store: &dyn TaoStore, t: Tenant) {
+    let bad = Association {
+        expires_at: Some(i64::MAX as u64),
+        ..edge("has_factor", 1, 3, 1, 1)
+    };
+    let got = store
+        .put_associations(t, &[edge("has_factor", 1, 2, 1, 1), bad, edge("follows", 1, 3, 1, 1)])
+        .await
+        .unwrap();
+    assert!(got[0].is_ok() && got[2].is_ok());
+    assert_eq!(got[1].as_ref().unwrap_err().code(), Code::InvalidArgument);
+
+    // The failed edge took its inverse with it.
+    assert_eq!(store.count_associations(t, "has_factor", 1).await.unwrap(), 1);
+    assert_eq!(store.count_associations(t, "is_securing", 3).await.unwrap(), 0);
+    assert_eq!(store.count_associations(t, "follows", 1).await.unwrap(), 1);
+}
+
+// ─────────────────── Transactions ───────────────────
+
+async fn write_is_all_or_nothing(store: &dyn TaoStore, t: Tenant) {
+    let id = store.put_object(t, &object(1, 0, 0, "a")).await.unwrap().id;
+
+    let ops = [
+        Op::PutObject(object(1, 42, 0, "new")),
+        Op::UpsertAssociation(UpsertAssociationOp {
+            association: Some(edge("follows", 42, id, 1, 1)),
+            ..Default::default()
+        }),
+        Op::DeleteObject(DeleteObjectOp { otype: 1, id, expected_version: Some(9) }),
+    ]
+    .map(|op| WriteOp { op: Some(op) });
+
+    let err = store.write(t, &ops).await.unwrap_err();
+    assert_eq!(err.code(), Code::Aborted);
+    assert!(err.message().starts_with("ops[2]: "), "{}", err.message());
+
+    assert!(store.get_object(t, 1, 42).await.unwrap().is_none());
+    assert_eq!(store.count_associations(t, "follows", 42).await.unwrap(), 0);
+    assert!(store.get_object(t, 1, id).await.unwrap().is_some());
+}
+
+async fn write_links_earlier_puts(store: &dyn TaoStore, t: Tenant) {
+    let ops = [
+        Op::PutObject(object(1, 0, 0, "a")),
+        Op::PutObject(object(2, 0, 0, "b")),
+        Op::UpsertAssociation(UpsertAssociationOp {
+            association: Some(edge("has_factor", 0, 0, 1, 1)),
+            source_from: Some(0),
+            target_from: Some(1),
+        }),
+    ]
+    .map(|op| WriteOp { op: Some(op) });
+
+    let results = store.write(t, &ops).await.unwrap();
+    let (a, b) = (results[0].id, results[1].id);
+    assert!(results[0].created && results[1].created);
+    assert_eq!(targets(&store.associations(t, &range("has_factor", a, true)).await.unwrap()), [b]);
+    assert_eq!(targets(&store.associations(t, &range("is_securing", b, true)).await.unwrap()), [a]);
+}
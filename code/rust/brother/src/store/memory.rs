@@ -0,0 +1,417 @@
+//! src/store/memory.rs
+//! [`TaoStore`] in process, for tests.
+//!
+//! One lock over everything; a multi-op write runs on a copy that
+//! replaces the state only once every op went through.  Rows linger as
+//! they do in Postgres: tombstones until restored, expired rows until
+//! overwritten (there is no reaper here, nor a purge).
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use brother::pb::{write_op::Op, Association, Object, WriteOp, WriteOpResult};
+use tonic::Status;
+
+use super::{at, AssocRange, TaoStore, Written};
+use crate::auth::Tenant;
+use crate::db::version_clash;
+use crate::value;
+
+/// `2025-01-01T00:00:00Z`, the epoch of `tao_next_id`.
+const ID_EPOCH_MS: u64 = 1_735_689_600_000;
+/// Past the last `TIMESTAMPTZ` Postgres has (294276 AD), in epoch-ms.
+const TIMESTAMP_END_MS: u64 = 9_224_318_016_000_000;
+
+#[derive(Clone)]
+struct StoredObject {
+    object: Object,
+    deleted_at: Option<SystemTime>,
+}
+
+#[derive(Clone)]
+struct StoredEdge {
+    edge: Association,
+    deleted: bool,
+}
+
+#[derive(Clone, Default)]
+struct State {
+    objects: HashMap<(Tenant, u32, u64), StoredObject>,
+    /// By `(tenant, type, source_id)`, then `target_id`.
+    edges: HashMap<(Tenant, String, i64), BTreeMap<i64, StoredEdge>>,
+    /// Both directions, as `tao_register_inverse` stores them.
+    inverses: HashMap<String, String>,
+    seq: u64,
+    /// Every id `next_id` handed out, as `tao.object_ids` records them.
+    ids: HashSet<u64>,
+}
+
+pub struct MemoryStore {
+    state: Mutex<State>,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryStore {
+    /// Knows the inverse pairs the migrations register.
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::default(),
+        }
+        .with_inverse("has_factor", "is_securing")
+        .with_inverse("is_delegating", "is_suborning")
+    }
+
+    pub fn with_inverse(self, atype: &str, inverse: &str) -> Self {
+        {
+            let mut state = self.lock();
+            state.inverses.insert(atype.to_owned(), inverse.to_owned());
+            state.inverses.insert(inverse.to_owned(), atype.to_owned());
+        }
+        self
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+fn live(expires_at: Option<u64>) -> bool {
+    expires_at.is_none_or(|at| at > now_ms())
+}
+
+/// Attributes as they read back from JSONB.
+fn stored(attributes: &HashMap<String, brother::pb::Value>) -> HashMap<String, brother::pb::Value> {
+    value::json_to_attrs(value::attrs_to_json(attributes))
+}
+
+impl State {
+    /// Same layout as `tao_next_id` on shard 0, never the same id twice.
+    fn next_id(&mut self, otype: u32) -> u64 {
+        loop {
+            self.seq += 1;
+            let ms = now_ms().saturating_sub(ID_EPOCH_MS);
+            let id = (ms << 22) | ((otype as u64 & 127) << 10) | (self.seq % 1024);
+            if self.ids.insert(id) {
+                return id;
+            }
+        }
+    }
+
+    fn get_object(&self, tenant: Tenant, otype: u32, id: u64) -> Option<Object> {
+        self.objects
+            .get(&(tenant, otype, id))
+            .filter(|s| s.deleted_at.is_none() && live(s.object.expires_at))
+            .map(|s| s.object.clone())
+    }
+
+    fn put_object(&mut self, tenant: Tenant, obj: &Object) -> Result<Written, Status> {
+        let mut id = obj.id;
+        if id == 0 {
+            id = self.next_id(obj.r#type);
+            while self.objects.contains_key(&(tenant, obj.r#type, id)) {
+                id = self.next_id(obj.r#type);
+            }
+        }
+
+        let Some(row) = self.objects.get_mut(&(tenant, obj.r#type, id)) else {
+            let object = Object {
+                tenant: tenant.0,
+                id,
+                version: 0,
+                attributes: stored(&obj.attributes),
+                ..obj.clone()
+            };
+            self.objects.insert(
+                (tenant, obj.r#type, id),
+                StoredObject {
+                    object,
+                    deleted_at: None,
+                },
+            );
+            return Ok(Written {
+                id,
+                created: true,
+                version: 0,
+            });
+        };
+
+        if row.deleted_at.is_some() {
+            return Err(Status::failed_precondition("object is deleted"));
+        }
+        if row.object.version != obj.version {
+            return Err(version_clash(&row.object.version.to_string()));
+        }
+        row.object.attributes = stored(&obj.attributes);
+        row.object.expires_at = obj.expires_at;
+        row.object.version += 1;
+        Ok(Written {
+            id,
+            created: false,
+            version: row.object.version,
+        })
+    }
+
+    fn delete_object(
+        &mut self,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        expected_version: Option<u32>,
+    ) -> Result<bool, Status> {
+        let Some(row) = self
+            .objects
+            .get_mut(&(tenant, otype, id))
+            .filter(|s| s.deleted_at.is_none())
+        else {
+            return Ok(false);
+        };
+        if let Some(expected) = expected_version {
+            if row.object.version != expected {
+                return Err(version_clash(&row.object.version.to_string()));
+            }
+        }
+        row.deleted_at = Some(SystemTime::now());
+        row.object.version += 1;
+        Ok(true)
+    }
+
+    fn restore_object(&mut self, tenant: Tenant, otype: u32, id: u64, window: Duration) -> Option<u32> {
+        let row = self.objects.get_mut(&(tenant, otype, id))?;
+        let age = row.deleted_at?.elapsed().unwrap_or_default();
+        if age >= window {
+            return None;
+        }
+        row.deleted_at = None;
+        row.object.version += 1;
+        Some(row.object.version)
+    }
+
+    fn put_edge(&mut self, tenant: Tenant, a: &Association) {
+        let edge = Association {
+            tenant: tenant.0,
+            attributes: stored(&a.attributes),
+            ..a.clone()
+        };
+        self.edges
+            .entry((tenant, a.r#type.clone(), a.source_id as i64))
+            .or_default()
+            .insert(a.target_id as i64, StoredEdge { edge, deleted: false });
+    }
+
+    fn put_edge_pair(&mut self, tenant: Tenant, a: &Association) -> Result<(), Status> {
+        if a.expires_at.is_some_and(|at| at >= TIMESTAMP_END_MS) {
+            return Err(Status::invalid_argument("timestamp out of range"));
+        }
+        self.put_edge(tenant, a);
+        if let Some(inverse) = self.inverses.get(&a.r#type).cloned() {
+            let mirrored = Association {
+                r#type: inverse,
+                source_id: a.target_id,
+                target_id: a.source_id,
+                ..a.clone()
+            };
+            if mirrored.r#type != a.r#type || mirrored.source_id != a.source_id {
+                self.put_edge(tenant, &mirrored);
+            }
+        }
+        Ok(())
+    }
+
+    fn delete_edge(&mut self, tenant: Tenant, atype: &str, source_id: i64, target_id: i64) -> bool {
+        let edge = self
+            .edges
+            .get_mut(&(tenant, atype.to_owned(), source_id))
+            .and_then(|edges| edges.get_mut(&target_id))
+            .filter(|e| !e.deleted);
+        match edge {
+            Some(e) => {
+                e.deleted = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn delete_edge_pair(&mut self, tenant: Tenant, atype: &str, source_id: i64, target_id: i64) -> bool {
+        let found = self.delete_edge(tenant, atype, source_id, target_id);
+        if let Some(inverse) = self.inverses.get(atype).cloned() {
+            if inverse != atype || source_id != target_id {
+                self.delete_edge(tenant, &inverse, target_id, source_id);
+            }
+        }
+        found
+    }
+
+    fn live_edges(&self, tenant: Tenant, atype: &str, source_id: i64) -> impl Iterator<Item = &Association> {
+        self.edges
+            .get(&(tenant, atype.to_owned(), source_id))
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .filter(|e| !e.deleted && live(e.edge.expires_at))
+            .map(|e| &e.edge)
+    }
+
+    fn write_op(&mut self, tenant: Tenant, op: &WriteOp, results: &[WriteOpResult]) -> Result<WriteOpResult, Status> {
+        Ok(match op.op.as_ref() {
+            Some(Op::PutObject(obj)) => {
+                let w = self.put_object(tenant, obj)?;
+                WriteOpResult {
+                    id: w.id,
+                    created: w.created,
+                    version: w.version,
+                    ..Default::default()
+                }
+            }
+            Some(Op::DeleteObject(d)) => WriteOpResult {
+                found: self.delete_object(tenant, d.otype, d.id, d.expected_version)?,
+                ..Default::default()
+            },
+            Some(Op::UpsertAssociation(u)) => {
+                let mut a = u.association.clone().unwrap_or_default();
+                if let Some(j) = u.source_from {
+                    a.source_id = results[j as usize].id;
+                }
+                if let Some(j) = u.target_from {
+                    a.target_id = results[j as usize].id;
+                }
+                self.put_edge_pair(tenant, &a)?;
+                WriteOpResult::default()
+            }
+            Some(Op::DeleteAssociation(d)) => WriteOpResult {
+                found: self.delete_edge_pair(tenant, &d.r#type, d.source_id, d.target_id),
+                ..Default::default()
+            },
+            None => unreachable!("rejected by validate_ops"),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl TaoStore for MemoryStore {
+    async fn get_object(&self, tenant: Tenant, otype: u32, id: u64) -> Result<Option<Object>, Status> {
+        Ok(self.lock().get_object(tenant, otype, id))
+    }
+
+    async fn get_objects(
+        &self,
+        tenant: Tenant,
+        keys: &[(u32, u64)],
+    ) -> Result<Vec<Option<Object>>, Status> {
+        let state = self.lock();
+        Ok(keys
+            .iter()
+            .map(|&(otype, id)| state.get_object(tenant, otype, id))
+            .collect())
+    }
+
+    async fn put_object(&self, tenant: Tenant, object: &Object) -> Result<Written, Status> {
+        self.lock().put_object(tenant, object)
+    }
+
+    async fn put_objects(
+        &self,
+        tenant: Tenant,
+        objects: &[Object],
+    ) -> Result<Vec<Result<Written, Status>>, Status> {
+        let mut state = self.lock();
+        Ok(objects.iter().map(|o| state.put_object(tenant, o)).collect())
+    }
+
+    async fn delete_object(
+        &self,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        expected_version: Option<u32>,
+    ) -> Result<bool, Status> {
+        self.lock().delete_object(tenant, otype, id, expected_version)
+    }
+
+    async fn restore_object(
+        &self,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        window: Duration,
+    ) -> Result<Option<u32>, Status> {
+        Ok(self.lock().restore_object(tenant, otype, id, window))
+    }
+
+    async fn put_association(&self, tenant: Tenant, association: &Association) -> Result<(), Status> {
+        self.lock().put_edge_pair(tenant, association)
+    }
+
+    async fn put_associations(
+        &self,
+        tenant: Tenant,
+        associations: &[Association],
+    ) -> Result<Vec<Result<(), Status>>, Status> {
+        let mut state = self.lock();
+        Ok(associations.iter().map(|a| state.put_edge_pair(tenant, a)).collect())
+    }
+
+    async fn delete_association(
+        &self,
+        tenant: Tenant,
+        atype: &str,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<bool, Status> {
+        Ok(self.lock().delete_edge_pair(tenant, atype, source_id, target_id))
+    }
+
+    async fn associations(&self, tenant: Tenant, range: &AssocRange) -> Result<Vec<Association>, Status> {
+        let key = |a: &Association| (a.position as i64, a.target_id as i64);
+        let state = self.lock();
+        let mut edges: Vec<Association> = state
+            .live_edges(tenant, &range.atype, range.source_id)
+            .filter(|a| range.time_from.is_none_or(|t| a.time as i64 >= t as i64))
+            .filter(|a| range.time_to.is_none_or(|t| (a.time as i64) < t as i64))
+            .filter(|a| match range.after {
+                Some(after) if range.ascending => key(a) > after,
+                Some(after) => key(a) < after,
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        edges.sort_by_key(key);
+        if !range.ascending {
+            edges.reverse();
+        }
+        edges.truncate(range.limit.max(0) as usize);
+        Ok(edges)
+    }
+
+    async fn count_associations(&self, tenant: Tenant, atype: &str, source_id: i64) -> Result<u64, Status> {
+        Ok(self.lock().live_edges(tenant, atype, source_id).count() as u64)
+    }
+
+    async fn write(&self, tenant: Tenant, ops: &[WriteOp]) -> Result<Vec<WriteOpResult>, Status> {
+        let mut state = self.lock();
+        let mut draft = state.clone();
+        let mut results = Vec::with_capacity(ops.len());
+        for (i, op) in ops.iter().enumerate() {
+            let result = draft
+                .write_op(tenant, op, &results)
+                .map_err(|e| at(&format!("ops[{i}]"), e))?;
+            results.push(result);
+        }
+        *state = draft;
+        Ok(results)
+    }
+}
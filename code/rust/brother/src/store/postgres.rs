@@ -0,0 +1,527 @@
+//! src/store/postgres.rs
+//! [`TaoStore`] on the `tao_*` functions.
+
+use std::{sync::Arc, time::Duration};
+
+use brother::pb::{write_op::Op, Association, Object, WriteOp, WriteOpResult};
+use sqlx::{PgConnection, Row};
+use tonic::Status;
+
+use super::{at, AssocRange, TaoStore, Written};
+use crate::auth::Tenant;
+use crate::db::{db_err, item_err, retry, PgPool, Retry};
+use crate::maintenance;
+use crate::value;
+
+#[derive(Clone)]
+pub struct PgStore {
+    db: Arc<PgPool>,
+}
+
+impl PgStore {
+    pub fn new(db: Arc<PgPool>) -> Self {
+        Self { db }
+    }
+
+    /// Inverse type registered for `atype` in `tao.association_inverses`.
+    async fn inverse_of(
+        conn: &mut PgConnection,
+        atype: &str,
+    ) -> sqlx::Result<Option<String>> {
+        sqlx::query_scalar(
+            r#"SELECT inverse FROM tao.association_inverses WHERE type = $1"#,
+        )
+        .bind(atype)
+        .fetch_optional(conn)
+        .await
+    }
+
+    async fn upsert_object(
+        conn: &mut PgConnection,
+        tenant: Tenant,
+        obj: &Object,
+    ) -> sqlx::Result<Written> {
+        let row = sqlx::query(
+            r#"SELECT id, created, version
+                 FROM tao.tao_upsert_object($1,$2,$3,$4,$5,
+                                            to_timestamp($6::BIGINT / 1000.0))"#,
+        )
+        .bind(tenant.db())
+        .bind(obj.r#type as i32)
+        .bind(obj.id as i64)
+        .bind(obj.version as i32)
+        .bind(value::attrs_to_json(&obj.attributes))
+        .bind(obj.expires_at.map(|t| t as i64))
+        .fetch_one(conn)
+        .await?;
+        Ok(Written {
+            id: row.get::<i64, _>("id") as u64,
+            created: row.get("created"),
+            version: row.get::<i32, _>("version") as u32,
+        })
+    }
+
+    /// Delete an object, optionally only at `expected_version`.
+    async fn delete_object_on(
+        conn: &mut PgConnection,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        expected_version: Option<u32>,
+    ) -> sqlx::Result<bool> {
+        sqlx::query_scalar(
+            r#"SELECT tao.tao_delete_object($1,$2,$3,$4)"#,
+        )
+        .bind(tenant.db())
+        .bind(otype as i32)
+        .bind(id as i64)
+        .bind(expected_version.map(|v| v as i32))
+        .fetch_one(conn)
+        .await
+    }
+
+    async fn upsert_edge(
+        conn: &mut PgConnection,
+        tenant: Tenant,
+        a: &Association,
+    ) -> sqlx::Result<()> {
+        sqlx::query(
+            r#"SELECT tao.tao_upsert_association($1,$2,$3,$4,$5,$6,$7,
+                                                 to_timestamp($8::BIGINT / 1000.0))"#,
+        )
+        .bind(tenant.db())
+        .bind(&a.r#type)
+        .bind(a.source_id as i64)
+        .bind(a.target_id as i64)
+        .bind(a.time as i64)
+        .bind(a.position as i64)
+        .bind(value::attrs_to_json(&a.attributes))
+        .bind(a.expires_at.map(|t| t as i64))
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    async fn delete_edge(
+        conn: &mut PgConnection,
+        tenant: Tenant,
+        atype: &str,
+        source_id: i64,
+        target_id: i64,
+    ) -> sqlx::Result<bool> {
+        sqlx::query_scalar(
+            r#"SELECT tao.tao_delete_association($1,$2,$3,$4)"#,
+        )
+        .bind(tenant.db())
+        .bind(atype)
+        .bind(source_id)
+        .bind(target_id)
+        .fetch_one(conn)
+        .await
+    }
+
+    /// Upsert `a` plus its mirrored inverse edge.  Run inside a transaction.
+    async fn upsert_edge_pair(
+        conn: &mut PgConnection,
+        tenant: Tenant,
+        a: &Association,
+    ) -> sqlx::Result<()> {
+        Self::upsert_edge(conn, tenant, a).await?;
+
+        if let Some(inverse) = Self::inverse_of(conn, &a.r#type).await? {
+            let mirrored = Association {
+                r#type: inverse,
+                source_id: a.target_id,
+                target_id: a.source_id,
+                ..a.clone()
+            };
+            if mirrored.r#type != a.r#type || mirrored.source_id != a.source_id {
+                Self::upsert_edge(conn, tenant, &mirrored).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Delete an edge plus its inverse.  Returns whether the edge itself
+    /// existed.  Run inside a transaction.
+    async fn delete_edge_pair(
+        conn: &mut PgConnection,
+        tenant: Tenant,
+        atype: &str,
+        source_id: i64,
+        target_id: i64,
+    ) -> sqlx::Result<bool> {
+        let found = Self::delete_edge(conn, tenant, atype, source_id, target_id).await?;
+
+        if let Some(inverse) = Self::inverse_of(conn, atype).await? {
+            if inverse != atype || source_id != target_id {
+                Self::delete_edge(conn, tenant, &inverse, target_id, source_id).await?;
+            }
+        }
+        Ok(found)
+    }
+
+    /// One attempt at a `Write`: every op in a single transaction.  The
+    /// error carries the index of the failing op (`None` = begin/commit).
+    async fn write_once(
+        &self,
+        tenant: Tenant,
+        ops: &[WriteOp],
+    ) -> Result<Vec<WriteOpResult>, (Option<usize>, sqlx::Error)> {
+        let mut tx = self.db.begin().await.map_err(|e| (None, e))?;
+        let mut results: Vec<WriteOpResult> = Vec::with_capacity(ops.len());
+
+        for (i, op) in ops.iter().enumerate() {
+            let at = |e| (Some(i), e);
+            let result = match op.op.as_ref() {
+                Some(Op::PutObject(obj)) => {
+                    let w = Self::upsert_object(&mut tx, tenant, obj).await.map_err(at)?;
+                    WriteOpResult {
+                        id: w.id,
+                        created: w.created,
+                        version: w.version,
+                        ..Default::default()
+                    }
+                }
+                Some(Op::DeleteObject(d)) => WriteOpResult {
+                    found: Self::delete_object_on(&mut tx, tenant, d.otype, d.id, d.expected_version)
+                        .await
+                        .map_err(at)?,
+                    ..Default::default()
+                },
+                Some(Op::UpsertAssociation(u)) => {
+                    let mut a = u.association.clone().unwrap_or_default();
+                    if let Some(j) = u.source_from {
+                        a.source_id = results[j as usize].id;
+                    }
+                    if let Some(j) = u.target_from {
+                        a.target_id = results[j as usize].id;
+                    }
+                    Self::upsert_edge_pair(&mut tx, tenant, &a).await.map_err(at)?;
+                    WriteOpResult::default()
+                }
+                Some(Op::DeleteAssociation(d)) => WriteOpResult {
+                    found: Self::delete_edge_pair(&mut tx, tenant, &d.r#type, d.source_id, d.target_id)
+                        .await
+                        .map_err(at)?,
+                    ..Default::default()
+                },
+                None => unreachable!("rejected by validate_ops"),
+            };
+            results.push(result);
+        }
+
+        tx.commit().await.map_err(|e| (None, e))?;
+        Ok(results)
+    }
+}
+
+/// Object columns, `expires_at` as epoch-ms.
+fn object(tenant: Tenant, otype: u32, id: u64, r: &sqlx::postgres::PgRow) -> Object {
+    Object {
+        tenant: tenant.0,
+        r#type: otype,
+        id,
+        version: r.get::<i32, _>("version") as u32,
+        attributes: value::json_to_attrs(r.get("attributes")),
+        expires_at: r.get::<Option<i64>, _>("expires_at").map(|t| t as u64),
+    }
+}
+
+#[tonic::async_trait]
+impl TaoStore for PgStore {
+    async fn get_object(&self, tenant: Tenant, otype: u32, id: u64) -> Result<Option<Object>, Status> {
+        let row = retry!(
+            Retry::Idempotent,
+            sqlx::query(
+                r#"SELECT version, attributes,
+                          (extract(epoch FROM expires_at) * 1000)::BIGINT AS expires_at
+                     FROM tao.objects
+                    WHERE tenant = $1 AND type = $2 AND id = $3
+                      AND deleted_at IS NULL
+                      AND (expires_at IS NULL OR expires_at > now())"#,
+            )
+            .bind(tenant.db())
+            .bind(otype as i32)
+            .bind(id as i64)
+            .fetch_optional(&*self.db)
+        )
+        .map_err(db_err)?;
+
+        Ok(row.map(|r| object(tenant, otype, id, &r)))
+    }
+
+    async fn get_objects(
+        &self,
+        tenant: Tenant,
+        keys: &[(u32, u64)],
+    ) -> Result<Vec<Option<Object>>, Status> {
+        let otypes: Vec<i32> = keys.iter().map(|&(otype, _)| otype as i32).collect();
+        let ids: Vec<i64> = keys.iter().map(|&(_, id)| id as i64).collect();
+        let rows = retry!(
+            Retry::Idempotent,
+            sqlx::query(
+                r#"
+                SELECT k.idx, o.version, o.attributes,
+                       (extract(epoch FROM o.expires_at) * 1000)::BIGINT AS expires_at
+                  FROM unnest($2::INT[], $3::BIGINT[]) WITH ORDINALITY AS k (type, id, idx)
+                  JOIN tao.objects o
+                    ON o.tenant = $1
+                   AND o.type   = k.type
+                   AND o.id     = k.id
+                   AND o.deleted_at IS NULL
+                   AND (o.expires_at IS NULL OR o.expires_at > now())
+                "#,
+            )
+            .bind(tenant.db())
+            .bind(&otypes)
+            .bind(&ids)
+            .fetch_all(&*self.db)
+        )
+        .map_err(db_err)?;
+
+        let mut objects = vec![None; keys.len()];
+        for r in rows {
+            let i = r.get::<i64, _>("idx") as usize - 1;
+            let (otype, id) = keys[i];
+            objects[i] = Some(object(tenant, otype, id, &r));
+        }
+        Ok(objects)
+    }
+
+    async fn put_object(&self, tenant: Tenant, object: &Object) -> Result<Written, Status> {
+        retry!(Retry::Rollback, async {
+            let mut conn = self.db.acquire().await?;
+            Self::upsert_object(&mut conn, tenant, object).await
+        })
+        .map_err(db_err)
+    }
+
+    async fn put_objects(
+        &self,
+        tenant: Tenant,
+        objects: &[Object],
+    ) -> Result<Vec<Result<Written, Status>>, Status> {
+        let otypes: Vec<i32> = objects.iter().map(|o| o.r#type as i32).collect();
+        let ids: Vec<i64> = objects.iter().map(|o| o.id as i64).collect();
+        let versions: Vec<i32> = objects.iter().map(|o| o.version as i32).collect();
+        let attrs: Vec<_> = objects.iter().map(|o| value::attrs_to_json(&o.attributes)).collect();
+        let expires: Vec<_> = objects.iter().map(|o| o.expires_at.map(|t| t as i64)).collect();
+        let rows = retry!(
+            Retry::Rollback,
+            sqlx::query(
+                r#"SELECT idx, id, created, version, err_state, err_message, err_detail
+                     FROM tao.tao_upsert_objects(
+                              $1,$2,$3,$4,$5,
+                              ARRAY(SELECT to_timestamp(e / 1000.0)
+                                      FROM unnest($6::BIGINT[]) WITH ORDINALITY AS x (e, n)
+                                     ORDER BY n))"#,
+            )
+            .bind(tenant.db())
+            .bind(&otypes)
+            .bind(&ids)
+            .bind(&versions)
+            .bind(&attrs)
+            .bind(&expires)
+            .fetch_all(&*self.db)
+        )
+        .map_err(db_err)?;
+
+        let mut results = vec![Err(Status::internal("no result for item")); objects.len()];
+        for r in rows {
+            let i = r.get::<i32, _>("idx") as usize - 1;
+            results[i] = match r.get::<Option<String>, _>("err_state") {
+                None => Ok(Written {
+                    id: r.get::<i64, _>("id") as u64,
+                    created: r.get("created"),
+                    version: r.get::<i32, _>("version") as u32,
+                }),
+                Some(state) => Err(item_err(
+                    &state,
+                    r.get::<Option<&str>, _>("err_message").unwrap_or_default(),
+                    r.get("err_detail"),
+                )),
+            };
+        }
+        Ok(results)
+    }
+
+    async fn delete_object(
+        &self,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        expected_version: Option<u32>,
+    ) -> Result<bool, Status> {
+        retry!(Retry::Rollback, async {
+            let mut conn = self.db.acquire().await?;
+            Self::delete_object_on(&mut conn, tenant, otype, id, expected_version).await
+        })
+        .map_err(db_err)
+    }
+
+    async fn restore_object(
+        &self,
+        tenant: Tenant,
+        otype: u32,
+        id: u64,
+        window: Duration,
+    ) -> Result<Option<u32>, Status> {
+        let version: Option<i32> = retry!(
+            Retry::Rollback,
+            sqlx::query_scalar(r#"SELECT tao.tao_restore_object($1,$2,$3,$4)"#)
+                .bind(tenant.db())
+                .bind(otype as i32)
+                .bind(id as i64)
+                .bind(maintenance::interval(window))
+                .fetch_one(&*self.db)
+        )
+        .map_err(db_err)?;
+
+        Ok(version.map(|v| v as u32))
+    }
+
+    async fn put_association(&self, tenant: Tenant, association: &Association) -> Result<(), Status> {
+        // The edge and its inverse (if registered) land together or not at all.
+        retry!(Retry::Rollback, async {
+            let mut tx = self.db.begin().await?;
+            Self::upsert_edge_pair(&mut tx, tenant, association).await?;
+            tx.commit().await
+        })
+        .map_err(db_err)
+    }
+
+    async fn put_associations(
+        &self,
+        tenant: Tenant,
+        associations: &[Association],
+    ) -> Result<Vec<Result<(), Status>>, Status> {
+        let atypes: Vec<&str> = associations.iter().map(|a| a.r#type.as_str()).collect();
+        let sources: Vec<i64> = associations.iter().map(|a| a.source_id as i64).collect();
+        let targets: Vec<i64> = associations.iter().map(|a| a.target_id as i64).collect();
+        let times: Vec<i64> = associations.iter().map(|a| a.time as i64).collect();
+        let positions: Vec<i64> = associations.iter().map(|a| a.position as i64).collect();
+        let attrs: Vec<_> = associations.iter().map(|a| value::attrs_to_json(&a.attributes)).collect();
+        let expires: Vec<_> = associations.iter().map(|a| a.expires_at.map(|t| t as i64)).collect();
+        // Each edge with its inverse, one sub-transaction apiece.
+        let rows = retry!(
+            Retry::Rollback,
+            sqlx::query(
+                r#"SELECT idx, err_state, err_message, err_detail
+                     FROM tao.tao_upsert_associations($1,$2,$3,$4,$5,$6,$7,$8)"#,
+            )
+            .bind(tenant.db())
+            .bind(&atypes)
+            .bind(&sources)
+            .bind(&targets)
+            .bind(&times)
+            .bind(&positions)
+            .bind(&attrs)
+            .bind(&expires)
+            .fetch_all(&*self.db)
+        )
+        .map_err(db_err)?;
+
+        let mut results = vec![Err(Status::internal("no result for item")); associations.len()];
+        for r in rows {
+            let i = r.get::<i32, _>("idx") as usize - 1;
+            results[i] = match r.get::<Option<String>, _>("err_state") {
+                None => Ok(()),
+                Some(state) => Err(item_err(
+                    &state,
+                    r.get::<Option<&str>, _>("err_message").unwrap_or_default(),
+                    r.get("err_detail"),
+                )),
+            };
+        }
+        Ok(results)
+    }
+
+    async fn delete_association(
+        &self,
+        tenant: Tenant,
+        atype: &str,
+        source_id: i64,
+        target_id: i64,
+    ) -> Result<bool, Status> {
+        retry!(Retry::Rollback, async {
+            let mut tx = self.db.begin().await?;
+            let found = Self::delete_edge_pair(&mut tx, tenant, atype, source_id, target_id).await?;
+            tx.commit().await?;
+            Ok(found)
+        })
+        .map_err(db_err)
+    }
+
+    async fn associations(&self, tenant: Tenant, range: &AssocRange) -> Result<Vec<Association>, Status> {
+        // Keyset paging on (position, target_id): `position` alone is not
+        // unique, `target_id` breaks the tie.  Walks `associations_srcpos_idx`.
+        let (cmp, dir) = if range.ascending { (">", "ASC") } else { ("<", "DESC") };
+        let sql = format!(
+            r#"
+            SELECT target_id, time, position, attributes,
+                   (extract(epoch FROM expires_at) * 1000)::BIGINT AS expires_at
+              FROM tao.associations
+             WHERE tenant    = $1
+               AND type      = $2
+               AND source_id = $3
+               AND deleted_at IS NULL
+               AND (expires_at IS NULL OR expires_at > now())
+               AND ($4::BIGINT IS NULL OR (position, target_id) {cmp} ($4, $5))
+               AND ($6::BIGINT IS NULL OR time >= $6)
+               AND ($7::BIGINT IS NULL OR time <  $7)
+             ORDER BY position {dir}, target_id {dir}
+             LIMIT $8
+            "#
+        );
+
+        let rows = retry!(
+            Retry::Idempotent,
+            sqlx::query(&sql)
+                .bind(tenant.db())
+                .bind(&range.atype)
+                .bind(range.source_id)
+                .bind(range.after.map(|(pos, _)| pos))
+                .bind(range.after.map(|(_, tgt)| tgt))
+                .bind(range.time_from.map(|t| t as i64))
+                .bind(range.time_to.map(|t| t as i64))
+                .bind(range.limit)
+                .fetch_all(&*self.db)
+        )
+        .map_err(db_err)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|r| Association {
+                tenant: tenant.0,
+                r#type: range.atype.clone(),
+                source_id: range.source_id as u64,
+                target_id: r.get::<i64, _>("target_id") as u64,
+                time: r.get::<i64, _>("time") as u64,
+                position: r.get::<i64, _>("position") as u64,
+                attributes: value::json_to_attrs(r.get("attributes")),
+                expires_at: r.get::<Option<i64>, _>("expires_at").map(|t| t as u64),
+            })
+            .collect())
+    }
+
+    async fn count_associations(&self, tenant: Tenant, atype: &str, source_id: i64) -> Result<u64, Status> {
+        let count: i64 = retry!(
+            Retry::Idempotent,
+            sqlx::query_scalar(r#"SELECT tao.tao_count_associations($1,$2,$3)"#)
+                .bind(tenant.db())
+                .bind(atype)
+                .bind(source_id)
+                .fetch_one(&*self.db)
+        )
+        .map_err(db_err)?;
+        Ok(count as u64)
+    }
+
+    async fn write(&self, tenant: Tenant, ops: &[WriteOp]) -> Result<Vec<WriteOpResult>, Status> {
+        retry!(Retry::Rollback, self.write_once(tenant, ops)).map_err(|(i, e)| {
+            let prefix = i.map_or("commit".to_owned(), |i| format!("ops[{i}]"));
+            at(&prefix, db_err(e))
+        })
+    }
+}
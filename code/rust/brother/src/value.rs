@@ -0,0 +1,270 @@
+//! src/value.rs
+//! Lossless mapping between the typed [`Value`] attributes of the API and
+//! the JSONB stored in `objects.attributes` / `associations.attributes`.
+//!
+//! Everything JSON can say natively is stored natively, so other tools can
+//! read and write attributes as plain JSON.  The rest is wrapped in a
+//! single-key tagged object – see the `Value` message in `brother.proto`
+//! for the exact encoding.
+
+use std::collections::HashMap;
+
+use base64::{engine::general_purpose::STANDARD as B64, Engine as _};
+use brother::pb::{value::Kind, ListValue, MapValue, Value};
+use prost_types::NullValue;
+use serde_json::{Map, Number, Value as Json};
+
+const TAG_BYTES: &str = "$bytes";
+const TAG_TIMESTAMP: &str = "$timestamp";
+const TAG_DOUBLE: &str = "$double";
+const TAG_MAP: &str = "$map";
+
+const TAGS: [&str; 4] = [TAG_BYTES, TAG_TIMESTAMP, TAG_DOUBLE, TAG_MAP];
+
+/// Above this an integral `f64` is printed with an exponent, which JSONB
+/// normalises away – it would read back as an integer.
+const EXACT_DOUBLE: f64 = 1e15;
+
+/// `attributes` → the JSONB object that is stored.
+pub fn attrs_to_json(attrs: &HashMap<String, Value>) -> Json {
+    Json::Object(
+        attrs
+            .iter()
+            .map(|(k, v)| (k.clone(), to_json(v)))
+            .collect(),
+    )
+}
+
+/// Stored JSONB → `attributes`.
+///
+/// Never drops data: a document that is not a JSON object (only possible
+/// when written behind the service's back) is returned under the key `""`.
+pub fn json_to_attrs(json: Json) -> HashMap<String, Value> {
+    match json {
+        Json::Object(map) => map.into_iter().map(|(k, v)| (k, from_json(v))).collect(),
+        other => HashMap::from([(String::new(), from_json(other))]),
+    }
+}
+
+pub fn to_json(value: &Value) -> Json {
+    let Some(kind) = &value.kind else {
+        return Json::Null;
+    };
+    match kind {
+        Kind::NullValue(_) => Json::Null,
+        Kind::IntValue(i) => Json::from(*i),
+        Kind::UintValue(u) => Json::from(*u),
+        Kind::DoubleValue(d) => match Number::from_f64(*d) {
+            Some(n) if d.fract() != 0.0 || d.abs() < EXACT_DOUBLE => Json::Number(n),
+            _ => tagged(TAG_DOUBLE, Json::String(d.to_string())),
+        },
+        Kind::BoolValue(b) => Json::Bool(*b),
+        Kind::StringValue(s) => Json::String(s.clone()),
+        Kind::BytesValue(b) => tagged(TAG_BYTES, Json::String(B64.encode(b))),
+        Kind::TimestampValue(t) => tagged(TAG_TIMESTAMP, Json::String(t.to_string())),
+        Kind::ListValue(l) => Json::Array(l.values.iter().map(to_json).collect()),
+        Kind::MapValue(m) => {
+            let map: Map<String, Json> =
+                m.fields.iter().map(|(k, v)| (k.clone(), to_json(v))).collect();
+            if tag_of(&map).is_some() {
+                tagged(TAG_MAP, Json::Object(map))
+            } else {
+                Json::Object(map)
+            }
+        }
+    }
+}
+
+pub fn from_json(json: Json) -> Value {
+    let kind = match json {
+        Json::Null => Kind::NullValue(NullValue::NullValue as i32),
+        Json::Bool(b) => Kind::BoolValue(b),
+        Json::String(s) => Kind::StringValue(s),
+        Json::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Kind::IntValue(i)
+            } else if let Some(u) = n.as_u64() {
+                Kind::UintValue(u)
+            } else {
+                Kind::DoubleValue(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        Json::Array(a) => Kind::ListValue(ListValue {
+            values: a.into_iter().map(from_json).collect(),
+        }),
+        Json::Object(map) => return from_object(map),
+    };
+    Value { kind: Some(kind) }
+}
+
+fn from_object(mut map: Map<String, Json>) -> Value {
+    let Some(tag) = tag_of(&map) else {
+        return plain_map(map);
+    };
+    let kind = match (tag, &map[tag]) {
+        (TAG_BYTES, Json::String(s)) => B64.decode(s).ok().map(Kind::BytesValue),
+        (TAG_TIMESTAMP, Json::String(s)) => s.parse().ok().map(Kind::TimestampValue),
+        (TAG_DOUBLE, Json::String(s)) => s.parse().ok().map(Kind::DoubleValue),
+        (TAG_MAP, Json::Object(_)) => match map.remove(tag) {
+            Some(Json::Object(inner)) => return plain_map(inner),
+            _ => None,
+        },
+        _ => None,
+    };
+    match kind {
+        Some(kind) => Value { kind: Some(kind) },
+        // A malformed tag is just a map that happens to use the key.
+        None => plain_map(map),
+    }
+}
+
+fn plain_map(map: Map<String, Json>) -> Value {
+    Value {
+        kind: Some(Kind::MapValue(MapValue {
+            fields: map.into_iter().map(|(k, v)| (k, from_json(v))).collect(),
+        })),
+    }
+}
+
+/// The tag a single-key object would be read as, if any.
+fn tag_of(map: &Map<String, Json>) -> Option<&'static str> {
+    if map.len() != 1 {
+        return None;
+    }
+    let key = map.keys().next()?;
+    TAGS.into_iter().find(|t| t == key)
+}
+
+fn tagged(tag: &str, inner: Json) -> Json {
+    Json::Object(Map::from_iter([(tag.to_owned(), inner)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::Timestamp;
+    use serde_json::json;
+
+    use super::*;
+
+    fn v(kind: Kind) -> Value {
+        Value { kind: Some(kind) }
+    }
+
+    fn map(fields: &[(&str, Value)]) -> Value {
+        v(Kind::MapValue(MapValue {
+            fields: fields.iter().map(|(k, v)| (k.to_string(), v.clone())).collect(),
+        }))
+    }
+
+    /// Out to JSON text and back, as a stored attribute goes.
+    fn stored(value: &Value) -> Value {
+        let text = serde_json::to_string(&to_json(value)).unwrap();
+        from_json(serde_json::from_str(&text).unwrap())
+    }
+
+    #[test]
+    fn native_kinds_round_trip() {
+        let cases = [
+            v(Kind::NullValue(NullValue::NullValue as i32)),
+            v(Kind::BoolValue(true)),
+            v(Kind::StringValue(String::new())),
+            v(Kind::StringValue("ünïcode \"quoted\"".into())),
+            v(Kind::IntValue(0)),
+            v(Kind::IntValue(i64::MIN)),
+            v(Kind::IntValue(i64::MAX)),
+            v(Kind::UintValue(i64::MAX as u64 + 1)),
+            v(Kind::UintValue(u64::MAX)),
+            v(Kind::DoubleValue(1.5)),
+            v(Kind::DoubleValue(-2.0)),
+            v(Kind::DoubleValue(f64::MIN_POSITIVE)),
+            v(Kind::ListValue(ListValue::default())),
+            v(Kind::ListValue(ListValue {
+                values: vec![v(Kind::IntValue(1)), v(Kind::StringValue("a".into()))],
+            })),
+            map(&[]),
+            map(&[("nested", map(&[("deep", v(Kind::BoolValue(false)))]))]),
+        ];
+        for value in cases {
+            assert_eq!(stored(&value), value, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn uint_in_int_range_reads_back_as_int() {
+        assert_eq!(stored(&v(Kind::UintValue(7))), v(Kind::IntValue(7)));
+        assert_eq!(
+            stored(&v(Kind::UintValue(i64::MAX as u64))),
+            v(Kind::IntValue(i64::MAX))
+        );
+    }
+
+    #[test]
+    fn tagged_kinds_round_trip() {
+        let cases = [
+            (v(Kind::BytesValue(vec![])), json!({"$bytes": ""})),
+            (v(Kind::BytesValue(vec![0, 255, 10])), json!({"$bytes": "AP8K"})),
+            (
+                v(Kind::TimestampValue(Timestamp { seconds: 1_700_000_000, nanos: 123_000_000 })),
+                json!({"$timestamp": "2023-11-14T22:13:20.123Z"}),
+            ),
+            (v(Kind::DoubleValue(f64::INFINITY)), json!({"$double": "inf"})),
+            (v(Kind::DoubleValue(f64::NEG_INFINITY)), json!({"$double": "-inf"})),
+            (v(Kind::DoubleValue(1e20)), json!({"$double": "100000000000000000000"})),
+        ];
+        for (value, json) in cases {
+            assert_eq!(to_json(&value), json);
+            assert_eq!(stored(&value), value, "{value:?}");
+        }
+    }
+
+    #[test]
+    fn nan_round_trips() {
+        let nan = v(Kind::DoubleValue(f64::NAN));
+        assert_eq!(to_json(&nan), json!({"$double": "NaN"}));
+        match stored(&nan).kind {
+            Some(Kind::DoubleValue(d)) => assert!(d.is_nan()),
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn maps_that_look_like_tags_are_wrapped() {
+        for key in TAGS {
+            let value = map(&[(key, v(Kind::StringValue("x".into())))]);
+            assert_eq!(to_json(&value), json!({"$map": {key: "x"}}));
+            assert_eq!(stored(&value), value);
+        }
+        // Two keys cannot be a tag.
+        let value = map(&[("$bytes", v(Kind::IntValue(1))), ("b", v(Kind::IntValue(2)))]);
+        assert_eq!(to_json(&value), json!({"$bytes": 1, "b": 2}));
+        assert_eq!(stored(&value), value);
+    }
+
+    #[test]
+    fn malformed_tags_read_as_plain_maps() {
+        for json in [json!({"$bytes": 5}), json!({"$bytes": "!!"}), json!({"$double": "x"}), json!({"$map": 1})] {
+            let (key, inner) = json.as_object().unwrap().iter().next().unwrap();
+            assert_eq!(from_json(json.clone()), map(&[(key, from_json(inner.clone()))]));
+        }
+    }
+
+    #[test]
+    fn attributes_are_a_json_object() {
+        let attrs = HashMap::from([
+            ("n".to_owned(), v(Kind::IntValue(1))),
+            ("b".to_owned(), v(Kind::BytesValue(vec![1]))),
+        ]);
+        let json = attrs_to_json(&attrs);
+        assert_eq!(json, json!({"n": 1, "b": {"$bytes": "AQ=="}}));
+        assert_eq!(json_to_attrs(json), attrs);
+        assert!(json_to_attrs(json!({})).is_empty());
+    }
+
+    #[test]
+    fn non_object_documents_keep_their_data() {
+        assert_eq!(
+            json_to_attrs(json!([1, 2])),
+            HashMap::from([(String::new(), from_json(json!([1, 2])))])
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! src/watch.rs
+//! Change feeds behind `WatchObjects` / `WatchAssociations`.
+//!
+//! Every change to `tao.objects` / `tao.associations` is appended to
+//! `tao.changelog` by a trigger in the writing transaction.  A feed is a
+//! task that polls the log for its tenant past the last `lsn` it emitted
+//! and pushes events down an mpsc channel until the client goes away.
+//!
+//! `lsn`s count per tenant, and a writer holds its tenant's counter until
+//! it commits (migration 16), so they become visible in order: a feed
+//! never passes a row that has yet to commit.
+//!
+//! When the server shuts down, feeds end with UNAVAILABLE so the drain
+//! does not wait on them; clients resume from their last `lsn`.
+
+use std::{pin::Pin, sync::Arc, time::Duration};
+
+use brother::pb::{AssociationChange, ChangeOp, ObjectChange};
+use sqlx::{postgres::PgRow, Row};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+use tonic::Status;
+
+use crate::auth::Tenant;
+use crate::db::{db_err, retry, PgPool, Retry};
+
+/// Turns `true` when the server starts shutting down.
+pub type Shutdown = tokio::sync::watch::Receiver<bool>;
+
+/// What a streaming RPC hands back to tonic.
+pub type ChangeStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+/// How often an idle feed looks for new rows.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// Rows fetched per poll.
+const PAGE: i64 = 500;
+/// Events buffered per client before the feed waits for it.
+const BUFFER: usize = 256;
+
+/// Which slice of the log a feed follows.
+#[derive(Debug, Clone)]
+pub enum Filter {
+    Objects(Vec<u32>),
+    Associations(Vec<String>),
+}
+
+impl Filter {
+    fn kind(&self) -> &'static str {
+        match self {
+            Filter::Objects(_) => "object",
+            Filter::Associations(_) => "association",
+        }
+    }
+}
+
+/// Start a feed of object changes.
+pub async fn objects(
+    db: Arc<PgPool>,
+    tenant: Tenant,
+    otypes: Vec<u32>,
+    after_lsn: Option<u64>,
+    stop: Shutdown,
+) -> Result<ChangeStream<ObjectChange>, Status> {
+    spawn(db, tenant, Filter::Objects(otypes), after_lsn, stop, |r| ObjectChange {
+        lsn: r.get::<i64, _>("lsn") as u64,
+        op: change_op(r) as i32,
+        otype: r.get::<Option<i32>, _>("otype").unwrap_or_default() as u32,
+        id: r.get::<i64, _>("id") as u64,
+        old_version: r.get::<Option<i32>, _>("old_version").map(|v| v as u32),
+        new_version: r.get::<Option<i32>, _>("new_version").map(|v| v as u32),
+        time: r.get::<i64, _>("at_ms") as u64,
+    })
+    .await
+}
+
+/// Start a feed of association changes.
+pub async fn associations(
+    db: Arc<PgPool>,
+    tenant: Tenant,
+    types: Vec<String>,
+    after_lsn: Option<u64>,
+    stop: Shutdown,
+) -> Result<ChangeStream<AssociationChange>, Status> {
+    spawn(db, tenant, Filter::Associations(types), after_lsn, stop, |r| AssociationChange {
+        lsn: r.get::<i64, _>("lsn") as u64,
+        op: change_op(r) as i32,
+        r#type: r.get::<Option<String>, _>("atype").unwrap_or_default(),
+        source_id: r.get("id"),
+        target_id: r.get::<Option<i64>, _>("target_id").unwrap_or_default(),
+        time: r.get::<i64, _>("at_ms") as u64,
+    })
+    .await
+}
+
+fn change_op(r: &PgRow) -> ChangeOp {
+    match r.get::<&str, _>("op") {
+        "delete" => ChangeOp::ChangeDelete,
+        "expire" => ChangeOp::ChangeExpire,
+        _ => ChangeOp::ChangePut,
+    }
+}
+
+async fn spawn<T: Send + 'static>(
+    db: Arc<PgPool>,
+    tenant: Tenant,
+    filter: Filter,
+    after_lsn: Option<u64>,
+    mut stop: Shutdown,
+    to_event: fn(&PgRow) -> T,
+) -> Result<ChangeStream<T>, Status> {
+    // No resume point: start at the tenant's committed head.
+    let mut after = match after_lsn {
+        Some(lsn) => lsn as i64,
+        None => retry!(
+            Retry::Idempotent,
+            sqlx::query_scalar(
+                r#"SELECT COALESCE(MAX(lsn), 0) FROM tao.changelog_heads
+                    WHERE tenant = $1"#,
+            )
+            .bind(tenant.db())
+            .fetch_one(&*db)
+        )
+        .map_err(db_err)?,
+    };
+
+    let (tx, rx) = mpsc::channel(BUFFER);
+    tokio::spawn(async move {
+        while !tx.is_closed() {
+            let rows = match poll(&db, tenant, &filter, after).await {
+                Ok(rows) => rows,
+                Err(e) => {
+                    let _ = tx.send(Err(db_err(e))).await;
+                    return;
+                }
+            };
+
+            let caught_up = (rows.len() as i64) < PAGE;
+            for r in &rows {
+                after = r.get("lsn");
+                if tx.send(Ok(to_event(r))).await.is_err() {
+                    return; // client went away
+                }
+            }
+            if caught_up {
+                tokio::select! {
+                    _ = tokio::time::sleep(POLL_INTERVAL) => {}
+                    _ = stopping(&mut stop) => {
+                        let _ = tx.send(Err(Status::unavailable("server is shutting down"))).await;
+                        return;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(Box::pin(ReceiverStream::new(rx)))
+}
+
+/// Resolves once shutdown starts; never if nobody can signal it.
+pub async fn stopping(stop: &mut Shutdown) {
+    if stop.wait_for(|s| *s).await.is_err() {
+        std::future::pending::<()>().await;
+    }
+}
+
+async fn poll(
+    db: &PgPool,
+    tenant: Tenant,
+    filter: &Filter,
+    after: i64,
+) -> sqlx::Result<Vec<PgRow>> {
+    let (otypes, atypes): (Vec<i32>, Vec<String>) = match filter {
+        Filter::Objects(t) => (t.iter().map(|t| *t as i32).collect(), Vec::new()),
+        Filter::Associations(t) => (Vec::new(), t.clone()),
+    };
+
+    sqlx::query(
+        r#"
+        SELECT lsn, op, otype, atype, id, target_id, old_version, new_version,
+               (extract(epoch FROM at) * 1000)::BIGINT AS at_ms
+          FROM tao.changelog
+         WHERE tenant = $1
+           AND kind   = $2
+           AND lsn    > $3
+           AND (cardinality($4::INT[])  = 0 OR otype = ANY($4))
+           AND (cardinality($5::TEXT[]) = 0 OR atype = ANY($5))
+         ORDER BY lsn
+         LIMIT $6
+        "#,
+    )
+    .bind(tenant.db())
+    .bind(filter.kind())
+    .bind(after)
+    .bind(otypes)
+    .bind(atypes)
+    .bind(PAGE)
+    .fetch_all(db)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio_stream::StreamExt;
+
+    use super::*;
+    use crate::db;
+
+    async fn put(conn: &mut sqlx::PgConnection, tenant: Tenant) -> i64 {
+        sqlx::query_scalar(r#"SELECT id FROM tao.tao_upsert_object($1, 1, 0, 0, '{}')"#)
+            .bind(tenant.db())
+            .fetch_one(conn)
+            .await
+            .unwrap()
+    }
+
+    /// Two sessions write the tenant at once; the one that wrote first
+    /// commits last.  The feed gets both, in commit order.
+    #[tokio::test]
+    #[ignore = "needs BROTHER_TEST_DATABASE_URL"]
+    async fn interleaved_sessions_are_emitted_in_commit_order() {
+        let pool = db::test_pool().await;
+        let pool = Arc::new(pool);
+        let tenant = db::test_tenant();
+        let (_stop_tx, stop) = tokio::sync::watch::channel(false);
+        let mut feed = objects(pool.clone(), tenant, vec![], None, stop).await.unwrap();
+
+        let mut first = pool.begin().await.unwrap();
+        let first_id = put(&mut first, tenant).await;
+
+        let second = tokio::spawn({
+            let pool = pool.clone();
+            async move {
+                let mut tx = pool.begin().await.unwrap();
+                let id = put(&mut tx, tenant).await;
+                tx.commit().await.unwrap();
+                id
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!second.is_finished(), "second writer must wait for the first");
+
+        first.commit().await.unwrap();
+        let second_id = second.await.unwrap();
+
+        let mut got = Vec::new();
+        while got.len() < 2 {
+            let event = tokio::time::timeout(Duration::from_secs(5), feed.next())
+                .await
+                .expect("feed stalled")
+                .unwrap()
+                .unwrap();
+            got.push((event.lsn, event.id as i64));
+        }
+        assert_eq!(got[0].1, first_id);
+        assert_eq!(got[1].1, second_id);
+        assert_eq!(got[1].0, got[0].0 + 1);
+    }
+}
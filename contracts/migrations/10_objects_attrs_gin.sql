@@ -0,0 +1,13 @@
+/*======================================================================
+  GIN index on object attributes
+  ----------------------------------------------------------------------
+  • Serves QueryObjects the way associations_attrs_gin serves
+    QueryAssociations: `@>` containment and `?` key existence
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE INDEX IF NOT EXISTS objects_attrs_gin
+    ON objects USING gin (attributes);
+
+-- End of migration
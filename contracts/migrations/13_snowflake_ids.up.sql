@@ -0,0 +1,125 @@
+/*======================================================================
+  Snowflake ids  –  tao_next_id / tao_upsert_object with id = 0
+  ----------------------------------------------------------------------
+  A new object id packs, high bit to low:
+
+      1   zero (ids stay positive)
+     41   milliseconds since 2025-01-01 UTC   (good until 2094)
+      5   shard       – `tao.id_shard` of the session, 0..31
+      7   type tag    – low 7 bits of the object type
+     10   sequence    – `object_id_seq` modulo 1024
+
+  • Sortable by creation time, and far above every BIGSERIAL id handed
+    out before, so old and new ids never meet
+  • The sequence is cached per session (YugabyteDB), so two sessions can
+    draw the same low 10 bits in the same millisecond; the create path
+    retries on that collision
+  • `objects.id` keeps its BIGSERIAL default for raw INSERTs
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE SEQUENCE IF NOT EXISTS object_id_seq;
+
+/*--------------------------------------------------------------
+  tao_next_id
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_next_id(
+    p_type INT
+) RETURNS BIGINT LANGUAGE sql VOLATILE AS $$
+    SELECT ((floor(extract(epoch FROM clock_timestamp()) * 1000)::BIGINT
+               - 1735689600000) << 22)
+         | ((COALESCE(NULLIF(current_setting('tao.id_shard', true), ''), '0')::BIGINT & 31) << 17)
+         | ((p_type::BIGINT & 127) << 10)
+         | (nextval('object_id_seq') % 1024);
+$$;
+
+/*--------------------------------------------------------------
+  tao_id_time – when an id from tao_next_id was made
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_id_time(
+    p_id BIGINT
+) RETURNS TIMESTAMPTZ LANGUAGE sql IMMUTABLE AS $$
+    SELECT to_timestamp(((p_id >> 22) + 1735689600000) / 1000.0);
+$$;
+
+/*--------------------------------------------------------------
+  tao_upsert_object – the insert path now takes a generated id
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_upsert_object(
+    p_tenant     BIGINT,
+    p_type       INT,
+    p_id         BIGINT,     -- 0 / NULL ⇒ insert
+    p_exp_ver    INT,        -- expected version
+    p_attrs      JSONB,
+    p_expires_at TIMESTAMPTZ DEFAULT NULL
+) RETURNS TABLE (id BIGINT, created BOOLEAN, version INT)
+LANGUAGE plpgsql AS $$
+DECLARE
+    _created  BOOLEAN := FALSE;
+    _version  INT     := 0;
+    _deleted  TIMESTAMPTZ;
+    _inserted INT;
+BEGIN
+    /* ---------- INSERT path (no id supplied) ----------------------- */
+    IF p_id IS NULL OR p_id = 0 THEN
+        LOOP
+            p_id := tao_next_id(p_type);
+            INSERT INTO objects (tenant, type, id, version, attributes, expires_at)
+                 VALUES (p_tenant, p_type, p_id, 0, p_attrs, p_expires_at)
+            ON CONFLICT ON CONSTRAINT objects_pk DO NOTHING;
+            GET DIAGNOSTICS _inserted = ROW_COUNT;
+            EXIT WHEN _inserted = 1;
+        END LOOP;
+        _created := TRUE;
+
+    ELSE
+        /* ---------- create at explicit id if missing --------------- */
+        INSERT INTO objects (tenant, type, id, version, attributes, expires_at)
+             VALUES (p_tenant, p_type, p_id, 0, p_attrs, p_expires_at)
+        ON CONFLICT ON CONSTRAINT objects_pk DO NOTHING;   -- `id` is also an OUT column
+        GET DIAGNOSTICS _inserted = ROW_COUNT;
+        _created := _inserted = 1;
+
+        /* ---------- UPDATE path with optimistic check -------------- */
+        IF NOT _created THEN
+            UPDATE objects o
+               SET attributes = p_attrs,
+                   expires_at = p_expires_at,
+                   updated_at = now(),
+                   version    = o.version + 1
+             WHERE o.tenant  = p_tenant
+               AND o.type    = p_type
+               AND o.id      = p_id
+               AND o.version = p_exp_ver
+               AND o.deleted_at IS NULL
+         RETURNING o.version INTO _version;
+
+            IF NOT FOUND THEN
+                SELECT o.version, o.deleted_at INTO _version, _deleted
+                  FROM objects o
+                 WHERE o.tenant = p_tenant
+                   AND o.type   = p_type
+                   AND o.id     = p_id;
+
+                IF _deleted IS NOT NULL THEN
+                    RAISE EXCEPTION
+                      'tao_upsert_object: object is deleted (tenant %, type %, id %)',
+                      p_tenant, p_type, p_id
+                      USING ERRCODE = 'TAODL';
+                END IF;
+
+                RAISE EXCEPTION
+                  'tao_upsert_object: version clash (tenant %, type %, id %)',
+                  p_tenant, p_type, p_id
+                  USING ERRCODE = 'TAOVC',
+                        DETAIL  = _version::text;
+            END IF;
+        END IF;
+    END IF;
+
+    RETURN QUERY SELECT p_id, _created, _version;
+END;
+$$;
+
+-- End of migration
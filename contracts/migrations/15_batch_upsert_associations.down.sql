@@ -0,0 +1,12 @@
+/*======================================================================
+  Batch helpers, associations  –  revert
+  ----------------------------------------------------------------------
+  • tao_upsert_associations goes; callers upsert edge by edge
+======================================================================*/
+
+SET search_path TO tao, public;
+
+DROP FUNCTION IF EXISTS tao_upsert_associations(
+    BIGINT, TEXT[], BIGINT[], BIGINT[], BIGINT[], BIGINT[], JSONB[], BIGINT[]);
+
+-- End of revert
@@ -0,0 +1,53 @@
+/*======================================================================
+  Changelog lsns in commit order
+  ----------------------------------------------------------------------
+  • A BIGSERIAL lsn is handed out at insert time, and sessions cache
+    sequence blocks (YugabyteDB: ysql_sequence_cache_minval), so lsn
+    order was not commit order and feeds skipped rows
+  • lsns now count per tenant in changelog_heads: the trigger bumps the
+    tenant's row inside the writing transaction, whose row lock the next
+    writer waits on until commit.  Once a reader sees an lsn, every lower
+    one of that tenant is committed or never will be
+  • The price: a tenant's writes commit one at a time
+  • Counters start at the tenant's highest lsn so far, so resume points
+    held by clients stay valid
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE TABLE IF NOT EXISTS changelog_heads (
+    tenant  BIGINT NOT NULL,
+    lsn     BIGINT NOT NULL,
+
+    CONSTRAINT changelog_heads_pk PRIMARY KEY (tenant)
+);
+
+INSERT INTO changelog_heads (tenant, lsn)
+SELECT tenant, max(lsn) FROM changelog GROUP BY tenant
+ON CONFLICT (tenant) DO NOTHING;
+
+-- lsns repeat across tenants now.
+ALTER TABLE changelog DROP CONSTRAINT IF EXISTS changelog_pk;
+ALTER TABLE changelog ADD CONSTRAINT changelog_pk PRIMARY KEY (tenant, lsn);
+ALTER TABLE changelog ALTER COLUMN lsn DROP DEFAULT;
+DROP SEQUENCE IF EXISTS changelog_lsn_seq;
+
+CREATE OR REPLACE FUNCTION trg_changelog_lsn()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO changelog_heads AS h (tenant, lsn)
+         VALUES (NEW.tenant, 1)
+    ON CONFLICT (tenant) DO UPDATE
+            SET lsn = h.lsn + 1
+    RETURNING h.lsn INTO NEW.lsn;
+    RETURN NEW;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS changelog_lsn ON changelog;
+CREATE TRIGGER changelog_lsn
+BEFORE INSERT ON changelog
+FOR EACH ROW
+EXECUTE FUNCTION trg_changelog_lsn();
+
+-- End of migration
@@ -0,0 +1,168 @@
+/*======================================================================
+  Unique keys per tenant  –  revert
+  ----------------------------------------------------------------------
+  • A key declared by any tenant becomes global again: it applies to
+    every tenant's objects of the type
+  • The values of tenants that had not declared it are claimed now;
+    where their live objects already share a value, the first claims it
+    and the others stay unclaimed until rewritten
+  • The trigger and tao_declare_unique / tao_drop_unique are those of
+    12_expiry and 11_unique_keys
+======================================================================*/
+
+SET search_path TO tao, public;
+
+DROP FUNCTION IF EXISTS tao_declare_unique(BIGINT, INT, TEXT[]);
+DROP FUNCTION IF EXISTS tao_drop_unique(BIGINT, INT, TEXT[]);
+
+ALTER TABLE unique_keys DROP CONSTRAINT IF EXISTS unique_keys_pk;
+
+DELETE FROM unique_keys k
+ USING unique_keys d
+ WHERE d.type = k.type AND d.path = k.path
+   AND (d.created_at, d.tenant) < (k.created_at, k.tenant);
+
+ALTER TABLE unique_keys DROP COLUMN IF EXISTS tenant;
+ALTER TABLE unique_keys ADD CONSTRAINT unique_keys_pk
+    PRIMARY KEY (type, path);
+
+INSERT INTO unique_values (tenant, type, path, value, id)
+     SELECT o.tenant, o.type, k.path, o.attributes #> k.path, o.id
+       FROM objects o
+       JOIN unique_keys k ON k.type = o.type
+      WHERE o.deleted_at IS NULL
+        AND (o.expires_at IS NULL OR o.expires_at > now())
+        AND o.attributes #> k.path IS NOT NULL
+        AND o.attributes #> k.path <> 'null'::jsonb
+ON CONFLICT ON CONSTRAINT unique_values_pk DO NOTHING;
+
+-----------------------------------------------------------------------
+-- 1. Claim / release trigger – as of 12_expiry
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION trg_objects_unique()
+RETURNS trigger LANGUAGE plpgsql AS $$
+DECLARE
+    _path   TEXT[];
+    _value  JSONB;
+    _holder BIGINT;
+BEGIN
+    IF TG_OP = 'UPDATE'
+       AND NEW.attributes IS NOT DISTINCT FROM OLD.attributes
+       AND (NEW.deleted_at IS NULL) = (OLD.deleted_at IS NULL) THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP IN ('UPDATE', 'DELETE') THEN
+        DELETE FROM unique_values
+         WHERE tenant = OLD.tenant AND type = OLD.type AND id = OLD.id;
+    END IF;
+
+    IF TG_OP = 'DELETE' OR NEW.deleted_at IS NOT NULL THEN
+        RETURN NULL;
+    END IF;
+
+    FOR _path IN SELECT k.path FROM unique_keys k WHERE k.type = NEW.type LOOP
+        _value := NEW.attributes #> _path;
+        CONTINUE WHEN _value IS NULL OR _value = 'null'::jsonb;
+
+        -- Waits for a concurrent claimant, then sees its row.
+        INSERT INTO unique_values (tenant, type, path, value, id)
+             VALUES (NEW.tenant, NEW.type, _path, _value, NEW.id)
+        ON CONFLICT ON CONSTRAINT unique_values_pk DO NOTHING;
+
+        IF NOT FOUND THEN
+            UPDATE unique_values u
+               SET id = NEW.id
+              FROM objects o
+             WHERE u.tenant = NEW.tenant AND u.type = NEW.type
+               AND u.path = _path AND u.value = _value
+               AND o.tenant = u.tenant AND o.type = u.type AND o.id = u.id
+               AND o.expires_at <= now();
+        END IF;
+
+        IF NOT FOUND THEN
+            SELECT u.id INTO _holder
+              FROM unique_values u
+             WHERE u.tenant = NEW.tenant AND u.type = NEW.type
+               AND u.path = _path AND u.value = _value;
+
+            RAISE EXCEPTION
+              'unique key % already taken (tenant %, type %)',
+              array_to_string(_path, '.'), NEW.tenant, NEW.type
+              USING ERRCODE = 'TAOUQ',
+                    DETAIL  = jsonb_build_object('path', _path, 'id', _holder)::text;
+        END IF;
+    END LOOP;
+
+    RETURN NULL;
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_declare_unique – as of 11_unique_keys
+  • Declares p_path of p_type unique and claims the values of the
+    existing live objects; TRUE if the key is new
+  • Fails with 'TAOUQ' if existing objects already share a value
+  • Holds off writers to objects while it backfills
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_declare_unique(
+    p_type INT,
+    p_path TEXT[]
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+DECLARE
+    _dup RECORD;
+BEGIN
+    LOCK TABLE objects IN SHARE MODE;
+
+    INSERT INTO unique_keys (type, path) VALUES (p_type, p_path)
+    ON CONFLICT ON CONSTRAINT unique_keys_pk DO NOTHING;
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    SELECT o.tenant, min(o.id) AS id
+      INTO _dup
+      FROM objects o
+     WHERE o.type = p_type
+       AND o.deleted_at IS NULL
+       AND o.attributes #> p_path IS NOT NULL
+       AND o.attributes #> p_path <> 'null'::jsonb
+     GROUP BY o.tenant, o.attributes #> p_path
+    HAVING count(*) > 1
+     LIMIT 1;
+
+    IF FOUND THEN
+        RAISE EXCEPTION
+          'existing objects of type % share a value of %',
+          p_type, array_to_string(p_path, '.')
+          USING ERRCODE = 'TAOUQ',
+                DETAIL  = jsonb_build_object('path', p_path, 'id', _dup.id)::text;
+    END IF;
+
+    INSERT INTO unique_values (tenant, type, path, value, id)
+         SELECT o.tenant, o.type, p_path, o.attributes #> p_path, o.id
+           FROM objects o
+          WHERE o.type = p_type
+            AND o.deleted_at IS NULL
+            AND o.attributes #> p_path IS NOT NULL
+            AND o.attributes #> p_path <> 'null'::jsonb;
+    RETURN TRUE;
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_drop_unique – as of 11_unique_keys
+  • Forgets the key and its claimed values; TRUE if it existed
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_drop_unique(
+    p_type INT,
+    p_path TEXT[]
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+BEGIN
+    DELETE FROM unique_values WHERE type = p_type AND path = p_path;
+    DELETE FROM unique_keys   WHERE type = p_type AND path = p_path;
+    RETURN FOUND;
+END;
+$$;
+
+-- End of revert
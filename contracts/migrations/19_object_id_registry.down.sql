@@ -0,0 +1,27 @@
+/*======================================================================
+  Generated ids are unique across tenants and types  –  revert
+  ----------------------------------------------------------------------
+  • tao_next_id no longer records its ids; a draw may repeat one made
+    in another session, tenant or type (see 19_object_id_registry)
+  • The recorded ids are dropped
+======================================================================*/
+
+SET search_path TO tao, public;
+
+/*--------------------------------------------------------------
+  tao_next_id – as of 13_snowflake_ids
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_next_id(
+    p_type INT
+) RETURNS BIGINT LANGUAGE sql VOLATILE AS $$
+    SELECT ((floor(extract(epoch FROM clock_timestamp()) * 1000)::BIGINT
+               - 1735689600000) << 22)
+         | ((COALESCE(NULLIF(current_setting('tao.id_shard', true), ''), '0')::BIGINT & 31) << 17)
+         | ((p_type::BIGINT & 127) << 10)
+         | (nextval('object_id_seq') % 1024);
+$$;
+
+DROP FUNCTION IF EXISTS tao_purge_object_ids(INTERVAL, INT);
+DROP TABLE IF EXISTS object_ids;
+
+-- End of revert
@@ -0,0 +1,73 @@
+/*======================================================================
+  Generated ids are unique across tenants and types
+  ----------------------------------------------------------------------
+  • tao_next_id could repeat itself: the retry on collision only checked
+    objects_pk (tenant, type, id), the type tag is just the low 7 bits
+    (types 1 and 129 share it), and the per-session cached sequence can
+    repeat its low 10 bits in another session within the same
+    millisecond.  AllocateIds ids were checked against nothing
+  • Every generated id is now recorded in object_ids, keyed on the id
+    alone, in the transaction that draws it; a taken id is drawn again.
+    A rolled-back draw frees its id, which was never used
+  • A draw can only meet ids of the current millisecond (give or take
+    clock skew), so tao_purge_object_ids drops rows by the time the id
+    carries; the maintenance task keeps an hour
+  • The type tag stays a hint: it is not part of uniqueness
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE TABLE IF NOT EXISTS object_ids (
+    id  BIGINT NOT NULL,
+
+    CONSTRAINT object_ids_pk PRIMARY KEY (id)
+);
+
+CREATE OR REPLACE FUNCTION tao_next_id(
+    p_type INT
+) RETURNS BIGINT LANGUAGE plpgsql VOLATILE AS $$
+DECLARE
+    _id BIGINT;
+BEGIN
+    LOOP
+        _id := ((floor(extract(epoch FROM clock_timestamp()) * 1000)::BIGINT
+                   - 1735689600000) << 22)
+             | ((COALESCE(NULLIF(current_setting('tao.id_shard', true), ''), '0')::BIGINT & 31) << 17)
+             | ((p_type::BIGINT & 127) << 10)
+             | (nextval('object_id_seq') % 1024);
+
+        -- Waits for a concurrent draw of the same id, then sees it.
+        INSERT INTO object_ids (id) VALUES (_id)
+        ON CONFLICT ON CONSTRAINT object_ids_pk DO NOTHING;
+        IF FOUND THEN
+            RETURN _id;
+        END IF;
+    END LOOP;
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_purge_object_ids
+  • Removes up to p_limit recorded ids made more than p_window ago
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_purge_object_ids(
+    p_window INTERVAL,
+    p_limit  INT
+) RETURNS BIGINT LANGUAGE plpgsql AS $$
+DECLARE
+    _n BIGINT;
+BEGIN
+    DELETE FROM object_ids k
+     USING (SELECT id
+              FROM object_ids
+             WHERE id < (floor(extract(epoch FROM now() - p_window) * 1000)::BIGINT
+                           - 1735689600000) << 22
+             ORDER BY id
+             LIMIT p_limit) d
+     WHERE k.id = d.id;
+    GET DIAGNOSTICS _n = ROW_COUNT;
+    RETURN _n;
+END;
+$$;
+
+-- End of migration
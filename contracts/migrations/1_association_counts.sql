@@ -0,0 +1,122 @@
+/*======================================================================
+  Association counters  –  O(1) assoc_count(type, source_id)
+  ----------------------------------------------------------------------
+  • One row per (tenant, type, source_id) holding the live edge count
+  • Maintained in the same transaction as the edge itself by
+    tao_upsert_association / tao_delete_association
+  • Back-filled from the existing associations on first run
+======================================================================*/
+
+SET search_path TO tao, public;
+
+-----------------------------------------------------------------------
+-- 1. Counter table
+-----------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS association_counts (
+    tenant      BIGINT  NOT NULL,
+    type        TEXT    NOT NULL,
+    source_id   BIGINT  NOT NULL,
+    count       BIGINT  NOT NULL DEFAULT 0,
+
+    CONSTRAINT association_counts_pk PRIMARY KEY (tenant, type, source_id)
+);
+
+INSERT INTO association_counts (tenant, type, source_id, count)
+     SELECT tenant, type, source_id, count(*)
+       FROM associations
+      GROUP BY tenant, type, source_id
+ON CONFLICT (tenant, type, source_id) DO UPDATE
+        SET count = EXCLUDED.count;
+
+-----------------------------------------------------------------------
+-- 2. Upsert / delete now keep the counter in step
+-----------------------------------------------------------------------
+/*--------------------------------------------------------------
+  tao_upsert_association  (type = TEXT)
+  • Counter is bumped only when the edge did not exist before
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_upsert_association(
+    p_tenant     BIGINT,
+    p_type       TEXT,
+    p_source     BIGINT,
+    p_target     BIGINT,
+    p_time       BIGINT,
+    p_position   BIGINT,
+    p_attrs      JSONB
+) RETURNS VOID LANGUAGE plpgsql AS $$
+DECLARE
+    _inserted INT;
+BEGIN
+    INSERT INTO associations (tenant, type, source_id, target_id, time,
+                               position, attributes)
+         VALUES (p_tenant, p_type, p_source, p_target,
+                 p_time,   p_position, p_attrs)
+    ON CONFLICT (tenant, type, source_id, target_id) DO NOTHING;
+    GET DIAGNOSTICS _inserted = ROW_COUNT;
+
+    IF _inserted = 1 THEN
+        INSERT INTO association_counts (tenant, type, source_id, count)
+             VALUES (p_tenant, p_type, p_source, 1)
+        ON CONFLICT (tenant, type, source_id) DO UPDATE
+                SET count = association_counts.count + 1;
+    ELSE
+        UPDATE associations
+           SET time       = p_time,
+               position   = p_position,
+               attributes = p_attrs
+         WHERE tenant    = p_tenant
+           AND type      = p_type
+           AND source_id = p_source
+           AND target_id = p_target;
+    END IF;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION tao_delete_association(
+    p_tenant BIGINT,
+    p_type   TEXT,
+    p_src    BIGINT,
+    p_tgt    BIGINT
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+BEGIN
+    DELETE FROM associations
+     WHERE tenant    = p_tenant
+       AND type      = p_type
+       AND source_id = p_src
+       AND target_id = p_tgt;
+
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    UPDATE association_counts
+       SET count = count - 1
+     WHERE tenant    = p_tenant
+       AND type      = p_type
+       AND source_id = p_src;
+    RETURN TRUE;
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_count_associations
+  • 0 when the source has never had an edge of this type
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_count_associations(
+    p_tenant BIGINT,
+    p_type   TEXT,
+    p_src    BIGINT
+) RETURNS BIGINT LANGUAGE sql STABLE AS $$
+    SELECT COALESCE(
+        (SELECT count
+           FROM association_counts
+          WHERE tenant    = p_tenant
+            AND type      = p_type
+            AND source_id = p_src),
+        0);
+$$;
+
+-- GRANT SELECT ON association_counts               TO brother_ro;
+-- GRANT EXECUTE ON FUNCTION tao_count_associations TO brother_ro;
+
+-- End of migration
@@ -0,0 +1,15 @@
+/*======================================================================
+  Idempotency claims that never let a write run twice  –  revert
+  ----------------------------------------------------------------------
+  • Claims lose their holder and writing flag.  Keys left writing
+    without a response look like calls still running; they are taken
+    over once abandoned, and their retries may write again
+======================================================================*/
+
+SET search_path TO tao, public;
+
+ALTER TABLE idempotency_keys
+    DROP COLUMN IF EXISTS holder,
+    DROP COLUMN IF EXISTS writing;
+
+-- End of revert
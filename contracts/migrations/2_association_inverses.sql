@@ -0,0 +1,40 @@
+/*======================================================================
+  Inverse association types
+  ----------------------------------------------------------------------
+  • Registry of paired edge types (e.g. has_factor ⇄ is_securing)
+  • Brother writes / deletes an edge and its inverse in one transaction
+  • Pairs are always stored in both directions
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE TABLE IF NOT EXISTS association_inverses (
+    type        TEXT    NOT NULL,
+    inverse     TEXT    NOT NULL,
+
+    CONSTRAINT association_inverses_pk PRIMARY KEY (type)
+);
+
+/*--------------------------------------------------------------
+  tao_register_inverse
+  • Registers p_type ⇄ p_inverse (both directions)
+  • A type may be its own inverse (symmetric edges)
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_register_inverse(
+    p_type    TEXT,
+    p_inverse TEXT
+) RETURNS VOID LANGUAGE plpgsql AS $$
+BEGIN
+    INSERT INTO association_inverses (type, inverse)
+         SELECT DISTINCT t, i
+           FROM (VALUES (p_type, p_inverse), (p_inverse, p_type)) AS v (t, i)
+    ON CONFLICT (type) DO UPDATE
+            SET inverse = EXCLUDED.inverse;
+END;
+$$;
+
+-- Pairs from contracts/types/0_core.ts (EdgeType)
+SELECT tao_register_inverse('has_factor',    'is_securing');
+SELECT tao_register_inverse('is_delegating', 'is_suborning');
+
+-- End of migration
@@ -0,0 +1,42 @@
+/*======================================================================
+  tao_delete_object with an optional version precondition
+  ----------------------------------------------------------------------
+  • p_exp_ver NULL ⇒ unconditional, same as the 3-argument form
+  • Otherwise a mismatch raises SQLSTATE 'TAOVC' with the current
+    version in DETAIL, exactly like tao_upsert_object
+  • A missing row is not a clash: returns FALSE
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE OR REPLACE FUNCTION tao_delete_object(
+    p_tenant  BIGINT,
+    p_type    INT,
+    p_id      BIGINT,
+    p_exp_ver INT
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+DECLARE
+    _version INT;
+BEGIN
+    IF p_exp_ver IS NOT NULL THEN
+        SELECT version INTO _version
+          FROM objects
+         WHERE tenant = p_tenant
+           AND type   = p_type
+           AND id     = p_id
+           FOR UPDATE;
+
+        IF FOUND AND _version <> p_exp_ver THEN
+            RAISE EXCEPTION
+              'tao_delete_object: version clash (tenant %, type %, id %)',
+              p_tenant, p_type, p_id
+              USING ERRCODE = 'TAOVC',
+                    DETAIL  = _version::text;
+        END IF;
+    END IF;
+
+    RETURN tao_delete_object(p_tenant, p_type, p_id);
+END;
+$$;
+
+-- End of migration
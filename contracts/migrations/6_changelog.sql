@@ -0,0 +1,86 @@
+/*======================================================================
+  Change-data log  –  outbox behind WatchObjects / WatchAssociations
+  ----------------------------------------------------------------------
+  • One row per put / delete of an object or association, written by
+    AFTER triggers, i.e. in the same transaction as every tao_* change
+  • lsn (BIGSERIAL) orders events; `at` is the wall-clock insert time
+    readers use to let in-flight transactions settle
+  • Object events carry the version before and after the change
+======================================================================*/
+
+SET search_path TO tao, public;
+
+-----------------------------------------------------------------------
+-- 1. Log table
+-----------------------------------------------------------------------
+CREATE TABLE IF NOT EXISTS changelog (
+    lsn          BIGSERIAL   NOT NULL,
+    tenant       BIGINT      NOT NULL,
+    kind         TEXT        NOT NULL CHECK (kind IN ('object', 'association')),
+    op           TEXT        NOT NULL CHECK (op   IN ('put', 'delete')),
+    otype        INT,                    -- objects
+    atype        TEXT,                   -- associations
+    id           BIGINT      NOT NULL,   -- object id / association source
+    target_id    BIGINT,                 -- associations
+    old_version  INT,                    -- objects, NULL on insert
+    new_version  INT,                    -- objects, NULL on delete
+    at           TIMESTAMPTZ NOT NULL DEFAULT clock_timestamp(),
+
+    CONSTRAINT changelog_pk PRIMARY KEY (lsn)
+);
+
+CREATE INDEX IF NOT EXISTS changelog_feed_idx
+    ON changelog (tenant, kind, lsn);
+
+-----------------------------------------------------------------------
+-- 2. Triggers
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION trg_objects_changelog()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO changelog (tenant, kind, op, otype, id, old_version)
+             VALUES (OLD.tenant, 'object', 'delete', OLD.type, OLD.id, OLD.version);
+        RETURN OLD;
+    END IF;
+
+    INSERT INTO changelog (tenant, kind, op, otype, id, old_version, new_version)
+         VALUES (NEW.tenant, 'object', 'put', NEW.type, NEW.id,
+                 CASE WHEN TG_OP = 'UPDATE' THEN OLD.version END,
+                 NEW.version);
+    RETURN NEW;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS objects_changelog ON objects;
+CREATE TRIGGER objects_changelog
+AFTER INSERT OR UPDATE OR DELETE ON objects
+FOR EACH ROW
+EXECUTE FUNCTION trg_objects_changelog();
+
+CREATE OR REPLACE FUNCTION trg_associations_changelog()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        INSERT INTO changelog (tenant, kind, op, atype, id, target_id)
+             VALUES (OLD.tenant, 'association', 'delete', OLD.type,
+                     OLD.source_id, OLD.target_id);
+        RETURN OLD;
+    END IF;
+
+    INSERT INTO changelog (tenant, kind, op, atype, id, target_id)
+         VALUES (NEW.tenant, 'association', 'put', NEW.type,
+                 NEW.source_id, NEW.target_id);
+    RETURN NEW;
+END;
+$$;
+
+DROP TRIGGER IF EXISTS associations_changelog ON associations;
+CREATE TRIGGER associations_changelog
+AFTER INSERT OR UPDATE OR DELETE ON associations
+FOR EACH ROW
+EXECUTE FUNCTION trg_associations_changelog();
+
+-- GRANT SELECT ON changelog TO brother_ro;
+
+-- End of migration
@@ -0,0 +1,395 @@
+/*======================================================================
+  Soft delete  –  tombstones, restore, retention purge
+  ----------------------------------------------------------------------
+  • tao_delete_object / tao_delete_association now set `deleted_at`
+    instead of removing the row; readers skip tombstoned rows
+  • Writing to a tombstoned object raises SQLSTATE 'TAODL'; it comes
+    back only through tao_restore_object.  Re-upserting a tombstoned
+    edge simply revives it
+  • tao_purge_tombstones physically removes tombstones past retention,
+    in bounded batches; purging emits no changelog events
+======================================================================*/
+
+SET search_path TO tao, public;
+
+-----------------------------------------------------------------------
+-- 1. Tombstone columns
+-----------------------------------------------------------------------
+ALTER TABLE objects      ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+ALTER TABLE associations ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS objects_tombstone_idx
+    ON objects (deleted_at) WHERE deleted_at IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS associations_tombstone_idx
+    ON associations (deleted_at) WHERE deleted_at IS NOT NULL;
+
+-----------------------------------------------------------------------
+-- 2. Objects
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION tao_upsert_object(
+    p_tenant   BIGINT,
+    p_type     INT,
+    p_id       BIGINT,     -- 0 / NULL ⇒ insert
+    p_exp_ver  INT,        -- expected version
+    p_attrs    JSONB
+) RETURNS TABLE (id BIGINT, created BOOLEAN, version INT)
+LANGUAGE plpgsql AS $$
+DECLARE
+    _created  BOOLEAN := FALSE;
+    _version  INT     := 0;
+    _deleted  TIMESTAMPTZ;
+    _inserted INT;
+BEGIN
+    /* ---------- INSERT path (no id supplied) ----------------------- */
+    IF p_id IS NULL OR p_id = 0 THEN
+        INSERT INTO objects (tenant, type, version, attributes)
+             VALUES (p_tenant, p_type, 0, p_attrs)
+          RETURNING objects.id INTO p_id;
+        _created := TRUE;
+
+    ELSE
+        /* ---------- create at explicit id if missing --------------- */
+        INSERT INTO objects (tenant, type, id, version, attributes)
+             VALUES (p_tenant, p_type, p_id, 0, p_attrs)
+        ON CONFLICT ON CONSTRAINT objects_pk DO NOTHING;   -- `id` is also an OUT column
+        GET DIAGNOSTICS _inserted = ROW_COUNT;
+        _created := _inserted = 1;
+
+        /* ---------- UPDATE path with optimistic check -------------- */
+        IF NOT _created THEN
+            UPDATE objects o
+               SET attributes = p_attrs,
+                   updated_at = now(),
+                   version    = o.version + 1
+             WHERE o.tenant  = p_tenant
+               AND o.type    = p_type
+               AND o.id      = p_id
+               AND o.version = p_exp_ver
+               AND o.deleted_at IS NULL
+         RETURNING o.version INTO _version;
+
+            IF NOT FOUND THEN
+                SELECT o.version, o.deleted_at INTO _version, _deleted
+                  FROM objects o
+                 WHERE o.tenant = p_tenant
+                   AND o.type   = p_type
+                   AND o.id     = p_id;
+
+                IF _deleted IS NOT NULL THEN
+                    RAISE EXCEPTION
+                      'tao_upsert_object: object is deleted (tenant %, type %, id %)',
+                      p_tenant, p_type, p_id
+                      USING ERRCODE = 'TAODL';
+                END IF;
+
+                RAISE EXCEPTION
+                  'tao_upsert_object: version clash (tenant %, type %, id %)',
+                  p_tenant, p_type, p_id
+                  USING ERRCODE = 'TAOVC',
+                        DETAIL  = _version::text;
+            END IF;
+        END IF;
+    END IF;
+
+    RETURN QUERY SELECT p_id, _created, _version;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION tao_delete_object(
+    p_tenant BIGINT,
+    p_type   INT,
+    p_id     BIGINT
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+BEGIN
+    UPDATE objects
+       SET deleted_at = now()
+     WHERE tenant = p_tenant
+       AND type   = p_type
+       AND id     = p_id
+       AND deleted_at IS NULL;
+    RETURN FOUND;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION tao_delete_object(
+    p_tenant  BIGINT,
+    p_type    INT,
+    p_id      BIGINT,
+    p_exp_ver INT
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+DECLARE
+    _version INT;
+BEGIN
+    IF p_exp_ver IS NOT NULL THEN
+        SELECT version INTO _version
+          FROM objects
+         WHERE tenant = p_tenant
+           AND type   = p_type
+           AND id     = p_id
+           AND deleted_at IS NULL
+           FOR UPDATE;
+
+        IF FOUND AND _version <> p_exp_ver THEN
+            RAISE EXCEPTION
+              'tao_delete_object: version clash (tenant %, type %, id %)',
+              p_tenant, p_type, p_id
+              USING ERRCODE = 'TAOVC',
+                    DETAIL  = _version::text;
+        END IF;
+    END IF;
+
+    RETURN tao_delete_object(p_tenant, p_type, p_id);
+END;
+$$;
+
+/*--------------------------------------------------------------
+  tao_restore_object
+  • Undeletes a tombstone younger than p_window
+  • Returns the new version, NULL if there was nothing to restore
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_restore_object(
+    p_tenant BIGINT,
+    p_type   INT,
+    p_id     BIGINT,
+    p_window INTERVAL
+) RETURNS INT LANGUAGE plpgsql AS $$
+DECLARE
+    _version INT;
+BEGIN
+    UPDATE objects
+       SET deleted_at = NULL
+     WHERE tenant = p_tenant
+       AND type   = p_type
+       AND id     = p_id
+       AND deleted_at > now() - p_window
+ RETURNING version INTO _version;
+    RETURN _version;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 3. Associations
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION tao_upsert_association(
+    p_tenant     BIGINT,
+    p_type       TEXT,
+    p_source     BIGINT,
+    p_target     BIGINT,
+    p_time       BIGINT,
+    p_position   BIGINT,
+    p_attrs      JSONB
+) RETURNS VOID LANGUAGE plpgsql AS $$
+DECLARE
+    _inserted INT;
+    _revived  BOOLEAN;
+BEGIN
+    INSERT INTO associations (tenant, type, source_id, target_id, time,
+                               position, attributes)
+         VALUES (p_tenant, p_type, p_source, p_target,
+                 p_time,   p_position, p_attrs)
+    ON CONFLICT (tenant, type, source_id, target_id) DO NOTHING;
+    GET DIAGNOSTICS _inserted = ROW_COUNT;
+
+    IF _inserted = 0 THEN
+        SELECT deleted_at IS NOT NULL INTO _revived
+          FROM associations
+         WHERE tenant    = p_tenant
+           AND type      = p_type
+           AND source_id = p_source
+           AND target_id = p_target
+           FOR UPDATE;
+
+        UPDATE associations
+           SET time       = p_time,
+               position   = p_position,
+               attributes = p_attrs,
+               deleted_at = NULL
+         WHERE tenant    = p_tenant
+           AND type      = p_type
+           AND source_id = p_source
+           AND target_id = p_target;
+    END IF;
+
+    IF _inserted = 1 OR _revived THEN
+        INSERT INTO association_counts (tenant, type, source_id, count)
+             VALUES (p_tenant, p_type, p_source, 1)
+        ON CONFLICT (tenant, type, source_id) DO UPDATE
+                SET count = association_counts.count + 1;
+    END IF;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION tao_delete_association(
+    p_tenant BIGINT,
+    p_type   TEXT,
+    p_src    BIGINT,
+    p_tgt    BIGINT
+) RETURNS BOOLEAN LANGUAGE plpgsql AS $$
+BEGIN
+    UPDATE associations
+       SET deleted_at = now()
+     WHERE tenant    = p_tenant
+       AND type      = p_type
+       AND source_id = p_src
+       AND target_id = p_tgt
+       AND deleted_at IS NULL;
+
+    IF NOT FOUND THEN
+        RETURN FALSE;
+    END IF;
+
+    UPDATE association_counts
+       SET count = count - 1
+     WHERE tenant    = p_tenant
+       AND type      = p_type
+       AND source_id = p_src;
+    RETURN TRUE;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 4. Retention purge
+-----------------------------------------------------------------------
+/*--------------------------------------------------------------
+  tao_purge_tombstones
+  • Removes up to p_limit objects and p_limit associations whose
+    tombstone is older than p_retention
+  • Returns the number of rows removed; done once it is < p_limit
+--------------------------------------------------------------*/
+CREATE OR REPLACE FUNCTION tao_purge_tombstones(
+    p_retention INTERVAL,
+    p_limit     INT
+) RETURNS BIGINT LANGUAGE plpgsql AS $$
+DECLARE
+    _objects BIGINT;
+    _assocs  BIGINT;
+BEGIN
+    DELETE FROM objects o
+     USING (SELECT tenant, type, id
+              FROM objects
+             WHERE deleted_at < now() - p_retention
+             LIMIT p_limit) d
+     WHERE o.tenant = d.tenant
+       AND o.type   = d.type
+       AND o.id     = d.id;
+    GET DIAGNOSTICS _objects = ROW_COUNT;
+
+    DELETE FROM associations a
+     USING (SELECT tenant, type, source_id, target_id
+              FROM associations
+             WHERE deleted_at < now() - p_retention
+             LIMIT p_limit) d
+     WHERE a.tenant    = d.tenant
+       AND a.type      = d.type
+       AND a.source_id = d.source_id
+       AND a.target_id = d.target_id;
+    GET DIAGNOSTICS _assocs = ROW_COUNT;
+
+    RETURN _objects + _assocs;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 5. Changelog: tombstoning is a delete, reviving is a put,
+--    purging a tombstone is silent
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION trg_objects_changelog()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        IF OLD.deleted_at IS NULL THEN
+            INSERT INTO changelog (tenant, kind, op, otype, id, old_version)
+                 VALUES (OLD.tenant, 'object', 'delete', OLD.type, OLD.id, OLD.version);
+        END IF;
+        RETURN OLD;
+    END IF;
+
+    IF TG_OP = 'UPDATE' AND NEW.deleted_at IS NOT NULL THEN
+        IF OLD.deleted_at IS NULL THEN
+            INSERT INTO changelog (tenant, kind, op, otype, id, old_version)
+                 VALUES (OLD.tenant, 'object', 'delete', OLD.type, OLD.id, OLD.version);
+        END IF;
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO changelog (tenant, kind, op, otype, id, old_version, new_version)
+         VALUES (NEW.tenant, 'object', 'put', NEW.type, NEW.id,
+                 CASE WHEN TG_OP = 'UPDATE' THEN OLD.version END,
+                 NEW.version);
+    RETURN NEW;
+END;
+$$;
+
+CREATE OR REPLACE FUNCTION trg_associations_changelog()
+RETURNS trigger LANGUAGE plpgsql AS $$
+BEGIN
+    IF (TG_OP = 'DELETE' AND OLD.deleted_at IS NULL)
+    OR (TG_OP = 'UPDATE' AND NEW.deleted_at IS NOT NULL AND OLD.deleted_at IS NULL) THEN
+        INSERT INTO changelog (tenant, kind, op, atype, id, target_id)
+             VALUES (OLD.tenant, 'association', 'delete', OLD.type,
+                     OLD.source_id, OLD.target_id);
+    ELSIF TG_OP <> 'DELETE' AND NEW.deleted_at IS NULL THEN
+        INSERT INTO changelog (tenant, kind, op, atype, id, target_id)
+             VALUES (NEW.tenant, 'association', 'put', NEW.type,
+                     NEW.source_id, NEW.target_id);
+    END IF;
+
+    IF TG_OP = 'DELETE' THEN
+        RETURN OLD;
+    END IF;
+    RETURN NEW;
+END;
+$$;
+
+-----------------------------------------------------------------------
+-- 6. Batch upsert: a tombstoned object fails only its own item
+-----------------------------------------------------------------------
+CREATE OR REPLACE FUNCTION tao_upsert_objects(
+    p_tenant    BIGINT,
+    p_types     INT[],
+    p_ids       BIGINT[],
+    p_exp_vers  INT[],
+    p_attrs     JSONB[]
+) RETURNS TABLE (
+    idx         INT,
+    id          BIGINT,
+    created     BOOLEAN,
+    version     INT,
+    err_state   TEXT,
+    err_message TEXT,
+    err_detail  TEXT
+) LANGUAGE plpgsql AS $$
+BEGIN
+    FOR i IN 1 .. COALESCE(array_length(p_types, 1), 0) LOOP
+        idx         := i;
+        err_state   := NULL;
+        err_message := NULL;
+        err_detail  := NULL;
+
+        BEGIN
+            SELECT u.id, u.created, u.version
+              INTO id, created, version
+              FROM tao_upsert_object(p_tenant, p_types[i], p_ids[i],
+                                     p_exp_vers[i], p_attrs[i]) AS u;
+        EXCEPTION
+            WHEN SQLSTATE 'TAOVC'
+              OR SQLSTATE 'TAODL'
+              OR data_exception
+              OR integrity_constraint_violation
+              OR raise_exception THEN
+                id      := p_ids[i];
+                created := FALSE;
+                version := NULL;
+                GET STACKED DIAGNOSTICS
+                    err_state   = RETURNED_SQLSTATE,
+                    err_message = MESSAGE_TEXT,
+                    err_detail  = PG_EXCEPTION_DETAIL;
+        END;
+
+        RETURN NEXT;
+    END LOOP;
+END;
+$$;
+
+-- End of migration
@@ -0,0 +1,28 @@
+/*======================================================================
+  Type schemas
+  ----------------------------------------------------------------------
+  • One optional attribute schema per object type / association type,
+    shared by every tenant (types are global, see contracts/types)
+  • The document is the JSON form of TypeSchema, written and enforced
+    by Brother (src/schema.rs) – the database only stores it
+======================================================================*/
+
+SET search_path TO tao, public;
+
+CREATE TABLE IF NOT EXISTS object_schemas (
+    type        INT         NOT NULL,
+    schema      JSONB       NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT object_schemas_pk PRIMARY KEY (type)
+);
+
+CREATE TABLE IF NOT EXISTS association_schemas (
+    type        TEXT        NOT NULL,
+    schema      JSONB       NOT NULL,
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+
+    CONSTRAINT association_schemas_pk PRIMARY KEY (type)
+);
+
+-- End of migration